@@ -1,6 +1,21 @@
 use num_bigint::BigInt as bui;
 use num_bigint::ParseBigIntError;
-use num_traits::Num;
+use num_bigint::RandBigInt;
+use num_bigint::Sign;
+use num_integer::Integer;
+use num_traits::{Num, One};
+use rand::{CryptoRng, RngCore};
+
+// a mod n in [0, n), also for negative a
+pub fn modulo(a: &bui, n: &bui) -> bui {
+    ((a % n) + n) % n
+}
+
+// a^-1 mod n, or None when gcd(a, n) != 1
+pub fn mod_inverse(a: &bui, n: &bui) -> Option<bui> {
+    let e = a.extended_gcd(n);
+    e.gcd.is_one().then(|| modulo(&e.x, n))
+}
 
 pub trait FromHex {
     fn from_hex(s: &str) -> Result<bui, ParseBigIntError>;
@@ -12,3 +27,42 @@ impl FromHex for bui {
         bui::from_str_radix(s, 16)
     }
 }
+
+// Hex constants in tests
+#[cfg(test)]
+pub fn int(s: &str) -> bui {
+    bui::from_hex(s).unwrap()
+}
+
+pub trait Wipe {
+    fn wipe(&mut self);
+}
+
+impl Wipe for bui {
+    // Overwrites the digits in place; copies left behind by earlier
+    // reallocations of the buffer are out of reach
+    fn wipe(&mut self) {
+        let len = (self.bits() as usize).div_ceil(32);
+        self.assign_from_slice(Sign::Plus, &vec![0; len]);
+        std::hint::black_box(&*self);
+    }
+}
+
+// The nonce loop shared by the signing and encryption schemes: f is the
+// deterministic core for one k, and k is drawn from [1, n) until f accepts
+// it. Every k is wiped once tried. f must fail only for a bad k, any other
+// error would be retried forever.
+pub fn retry_with_nonce<T>(
+    rng: &mut (impl RngCore + CryptoRng),
+    n: &bui,
+    mut f: impl FnMut(&bui) -> Result<T, String>,
+) -> T {
+    loop {
+        let mut k = rng.gen_bigint_range(&bui::one(), n);
+        let res = f(&k);
+        k.wipe();
+        if let Ok(v) = res {
+            break v;
+        }
+    }
+}
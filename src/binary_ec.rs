@@ -0,0 +1,459 @@
+// Ordinary binary curves y^2 + x y = x^3 + a x^2 + b over GF(2^m), b != 0.
+// Points are affine at the interface; scalar multiplication runs in
+// López-Dahab coordinates (X : Y : Z), x = X / Z and y = Y / Z^2, and inverts
+// once at the end.
+
+use crate::gf2m::Gf2m;
+
+use num_bigint::BigInt as bui;
+use num_bigint::RandBigInt;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BinPoint {
+    Infinity,
+    Affine(bui, bui),
+}
+
+// Z = 0 is the point at infinity
+#[derive(Clone, Debug)]
+pub struct LdPoint {
+    x: bui,
+    y: bui,
+    z: bui,
+}
+
+#[derive(Clone, Debug)]
+pub struct BinaryCurve {
+    field: Gf2m,
+    a: bui,
+    b: bui,
+    n: bui,      // order of the base point
+    h: u32,      // cofactor
+    p: BinPoint, // base point
+}
+
+impl BinPoint {
+    pub fn is_infinity(&self) -> bool {
+        matches!(self, BinPoint::Infinity)
+    }
+}
+
+impl BinaryCurve {
+    pub fn new(
+        field: Gf2m,
+        a: bui,
+        b: bui,
+        n: bui,
+        h: u32,
+        g_x: bui,
+        g_y: bui,
+    ) -> Result<Self, String> {
+        let curve = Self {
+            field,
+            a,
+            b,
+            n,
+            h,
+            p: BinPoint::Affine(g_x, g_y),
+        };
+        if curve.b.is_zero() || !curve.on_curve(&curve.p) {
+            return Err(String::from("Base point is not on the curve"));
+        }
+        Ok(curve)
+    }
+
+    pub fn field(&self) -> &Gf2m {
+        &self.field
+    }
+
+    pub fn get_ref_a(&self) -> &bui {
+        &self.a
+    }
+
+    pub fn get_ref_b(&self) -> &bui {
+        &self.b
+    }
+
+    pub fn get_ref_n(&self) -> &bui {
+        &self.n
+    }
+
+    pub fn get_ref_p(&self) -> &BinPoint {
+        &self.p
+    }
+
+    pub fn cofactor(&self) -> u32 {
+        self.h
+    }
+
+    pub fn on_curve(&self, p: &BinPoint) -> bool {
+        let f = &self.field;
+        match p {
+            BinPoint::Infinity => true,
+            BinPoint::Affine(x, y) => {
+                if !f.contains(x) || !f.contains(y) {
+                    return false;
+                }
+                let x2 = f.square(x);
+                let left = f.square(y) ^ f.mul(x, y);
+                let right = f.mul(&x2, x) ^ f.mul(&self.a, &x2) ^ &self.b;
+                left == right
+            }
+        }
+    }
+
+    pub fn neg(&self, p: &BinPoint) -> BinPoint {
+        match p {
+            BinPoint::Infinity => BinPoint::Infinity,
+            BinPoint::Affine(x, y) => BinPoint::Affine(x.clone(), x ^ y),
+        }
+    }
+
+    pub fn double(&self, p: &BinPoint) -> BinPoint {
+        let f = &self.field;
+        match p {
+            BinPoint::Affine(x, y) if !x.is_zero() => {
+                // l = x + y / x, x3 = l^2 + l + a, y3 = x^2 + (l + 1) x3
+                let l = x ^ f.mul(y, &f.inv(x).unwrap());
+                let x3 = f.square(&l) ^ &l ^ &self.a;
+                let y3 = f.square(x) ^ f.mul(&(l ^ bui::one()), &x3);
+                BinPoint::Affine(x3, y3)
+            }
+            // x = 0 is the point of order 2
+            _ => BinPoint::Infinity,
+        }
+    }
+
+    pub fn add(&self, p1: &BinPoint, p2: &BinPoint) -> BinPoint {
+        let f = &self.field;
+        match (p1, p2) {
+            (BinPoint::Infinity, _) => p2.clone(),
+            (_, BinPoint::Infinity) => p1.clone(),
+            (BinPoint::Affine(x1, y1), BinPoint::Affine(x2, y2)) => {
+                if x1 == x2 {
+                    return if y1 == y2 {
+                        self.double(p1)
+                    } else {
+                        BinPoint::Infinity
+                    };
+                }
+                // l = (y1 + y2) / (x1 + x2), x3 = l^2 + l + x1 + x2 + a,
+                // y3 = l (x1 + x3) + x3 + y1
+                let l = f.mul(&(y1 ^ y2), &f.inv(&(x1 ^ x2)).unwrap());
+                let x3 = f.square(&l) ^ &l ^ x1 ^ x2 ^ &self.a;
+                let y3 = f.mul(&l, &(x1 ^ &x3)) ^ &x3 ^ y1;
+                BinPoint::Affine(x3, y3)
+            }
+        }
+    }
+
+    pub fn ld_infinity(&self) -> LdPoint {
+        LdPoint {
+            x: bui::one(),
+            y: bui::zero(),
+            z: bui::zero(),
+        }
+    }
+
+    pub fn to_ld(&self, p: &BinPoint) -> LdPoint {
+        match p {
+            BinPoint::Infinity => self.ld_infinity(),
+            BinPoint::Affine(x, y) => LdPoint {
+                x: x.clone(),
+                y: y.clone(),
+                z: bui::one(),
+            },
+        }
+    }
+
+    pub fn to_affine(&self, p: &LdPoint) -> BinPoint {
+        let f = &self.field;
+        if p.z.is_zero() {
+            return BinPoint::Infinity;
+        }
+        let z_inv = f.inv(&p.z).unwrap();
+        BinPoint::Affine(f.mul(&p.x, &z_inv), f.mul(&p.y, &f.square(&z_inv)))
+    }
+
+    // Z3 = X1^2 Z1^2, X3 = X1^4 + b Z1^4,
+    // Y3 = b Z1^4 Z3 + X3 (a Z3 + Y1^2 + b Z1^4)
+    pub fn ld_double(&self, p: &LdPoint) -> LdPoint {
+        let f = &self.field;
+        if p.z.is_zero() || p.x.is_zero() {
+            return self.ld_infinity();
+        }
+        let x2 = f.square(&p.x);
+        let z2 = f.square(&p.z);
+        let bz4 = f.mul(&self.b, &f.square(&z2));
+        let z3 = f.mul(&x2, &z2);
+        let x3 = f.square(&x2) ^ &bz4;
+        let t = f.mul(&self.a, &z3) ^ f.square(&p.y) ^ &bz4;
+        let y3 = f.mul(&bz4, &z3) ^ f.mul(&x3, &t);
+        LdPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    // Frobenius (X^2 : Y^2 : Z^2); an endomorphism only when a and b are in
+    // GF(2), that is on the Koblitz curves
+    pub fn ld_frobenius(&self, p: &LdPoint) -> LdPoint {
+        let f = &self.field;
+        LdPoint {
+            x: f.square(&p.x),
+            y: f.square(&p.y),
+            z: f.square(&p.z),
+        }
+    }
+
+    // add-2005-dl
+    pub fn ld_add(&self, p1: &LdPoint, p2: &LdPoint) -> LdPoint {
+        let f = &self.field;
+        if p1.z.is_zero() {
+            return p2.clone();
+        }
+        if p2.z.is_zero() {
+            return p1.clone();
+        }
+        let a = f.mul(&p1.x, &p2.z);
+        let b = f.mul(&p2.x, &p1.z);
+        let g = f.mul(&p1.y, &f.square(&p2.z));
+        let h = f.mul(&p2.y, &f.square(&p1.z));
+        let e = &a ^ &b;
+        let i = &g ^ &h;
+        if e.is_zero() {
+            // same x: P1 = P2 or P1 = -P2
+            return if i.is_zero() {
+                self.ld_double(p1)
+            } else {
+                self.ld_infinity()
+            };
+        }
+        let c = f.square(&a);
+        let d = f.square(&b);
+        let ff = &c ^ &d;
+        let j = f.mul(&i, &e);
+        let z3 = f.mul(&f.mul(&ff, &p1.z), &p2.z);
+        let x3 = f.mul(&a, &(h ^ &d)) ^ f.mul(&b, &(c ^ &g));
+        let y3 = f.mul(&(f.mul(&a, &j) ^ f.mul(&ff, &g)), &ff) ^ f.mul(&(j ^ &z3), &x3);
+        LdPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    // mixed addition with an affine (x2, y2): A = y2 Z1^2 + Y1, B = x2 Z1 + X1,
+    // C = Z1 B, D = B^2 (C + a Z1^2), Z3 = C^2, E = A C, X3 = A^2 + D + E,
+    // Y3 = (E + Z3)(X3 + x2 Z3) + (x2 + y2) Z3^2
+    pub fn ld_add_mixed(&self, p1: &LdPoint, p2: &BinPoint) -> LdPoint {
+        let f = &self.field;
+        let BinPoint::Affine(x2, y2) = p2 else {
+            return p1.clone();
+        };
+        if p1.z.is_zero() {
+            return self.to_ld(p2);
+        }
+        let z1_2 = f.square(&p1.z);
+        let a = f.mul(y2, &z1_2) ^ &p1.y;
+        let b = f.mul(x2, &p1.z) ^ &p1.x;
+        if b.is_zero() {
+            return if a.is_zero() {
+                self.ld_double(p1)
+            } else {
+                self.ld_infinity()
+            };
+        }
+        let c = f.mul(&p1.z, &b);
+        let d = f.mul(&f.square(&b), &(&c ^ f.mul(&self.a, &z1_2)));
+        let z3 = f.square(&c);
+        let e = f.mul(&a, &c);
+        let x3 = f.square(&a) ^ &d ^ &e;
+        let y3 = f.mul(&(&e ^ &z3), &(&x3 ^ f.mul(x2, &z3))) ^ f.mul(&(x2 ^ y2), &f.square(&z3));
+        LdPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub fn scalar_mul(&self, p: &BinPoint, k: &bui) -> BinPoint {
+        let mut r_0 = self.ld_infinity();
+        let mut r_1 = self.to_ld(p);
+        for i in format!("{:b}", k).chars() {
+            if i == '0' {
+                r_1 = self.ld_add(&r_0, &r_1);
+                r_0 = self.ld_double(&r_0);
+            } else {
+                r_0 = self.ld_add(&r_0, &r_1);
+                r_1 = self.ld_double(&r_1);
+            }
+        }
+
+        self.to_affine(&r_0)
+    }
+
+    // Straus with 4-bit windows: affine tables, a López-Dahab accumulator
+    // and mixed additions
+    pub fn multi_scalar_mul(&self, terms: &[(BinPoint, bui)]) -> BinPoint {
+        const W: usize = 4;
+
+        let tables: Vec<Vec<BinPoint>> = terms
+            .iter()
+            .map(|(p, _)| {
+                let mut table = vec![BinPoint::Infinity, p.clone()];
+                for i in 2..(1 << W) {
+                    let next = self.add(&table[i - 1], p);
+                    table.push(next);
+                }
+                table
+            })
+            .collect();
+        let digits: Vec<Vec<u8>> = terms.iter().map(|(_, k)| k.to_radix_be(16).1).collect();
+        let len = digits.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut acc = self.ld_infinity();
+        for i in 0..len {
+            for _ in 0..W {
+                acc = self.ld_double(&acc);
+            }
+            for (table, digits) in tables.iter().zip(&digits) {
+                let offset = len - digits.len();
+                if i >= offset && digits[i - offset] != 0 {
+                    acc = self.ld_add_mixed(&acc, &table[digits[i - offset] as usize]);
+                }
+            }
+        }
+
+        self.to_affine(&acc)
+    }
+
+    // a point with the given x, whichever of the two y; with x = 0 the
+    // single point of order 2
+    pub fn lift_x(&self, x: &bui) -> Option<BinPoint> {
+        let f = &self.field;
+        if !f.contains(x) {
+            return None;
+        }
+        if x.is_zero() {
+            return Some(BinPoint::Affine(bui::zero(), f.sqrt(&self.b)));
+        }
+        // y = x z with z^2 + z = x + a + b / x^2
+        let c = x ^ &self.a ^ f.mul(&self.b, &f.square(&f.inv(x).unwrap()));
+        if !f.trace(&c).is_zero() {
+            return None;
+        }
+        let z = f.half_trace(&c);
+        Some(BinPoint::Affine(x.clone(), f.mul(x, &z)))
+    }
+
+    pub fn random_point_with_rng(&self, rng: &mut (impl RngCore + CryptoRng)) -> BinPoint {
+        loop {
+            let x = bui::from(rng.gen_biguint(self.field.m() as u64));
+            if let Some(p) = self.lift_x(&x) {
+                break p;
+            }
+        }
+    }
+
+    pub fn validate_public_key(&self, q: &BinPoint) -> Result<(), String> {
+        if q.is_infinity() {
+            return Err(String::from("Public key is the point at infinity"));
+        }
+        if !self.on_curve(q) {
+            return Err(String::from("Public key is not on the curve"));
+        }
+        if !self.scalar_mul(q, &self.n).is_infinity() {
+            return Err(String::from("Public key is not in the subgroup of order n"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_group_law() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let curve = curves::dstu4145(163).unwrap();
+        let p = curve.random_point_with_rng(&mut rng);
+        assert!(curve.on_curve(&p));
+        assert!(curve.add(&p, &curve.neg(&p)).is_infinity());
+
+        let p2 = curve.double(&p);
+        let p3 = curve.add(&p2, &p);
+        assert_eq!(p3, curve.scalar_mul(&p, &bui::from(3)));
+        assert_eq!(curve.add(&p3, &curve.neg(&p2)), p);
+        assert!(curve.on_curve(&p3));
+
+        // [h n] kills every point, and the point of order 2 has x = 0
+        let hn = curve.get_ref_n() * curve.cofactor();
+        assert!(curve.scalar_mul(&p, &hn).is_infinity());
+        let t = curve.lift_x(&bui::zero()).unwrap();
+        assert!(curve.on_curve(&t));
+        assert!(curve.double(&t).is_infinity());
+        assert!(curve.validate_public_key(&t).is_err());
+    }
+
+    // the López-Dahab formulas against the affine ones
+    #[test]
+    fn test_lopez_dahab() {
+        let mut rng = StdRng::seed_from_u64(1999);
+        for curve in [
+            curves::b163(),
+            curves::k233(),
+            curves::dstu4145(173).unwrap(),
+        ] {
+            let p = curve.random_point_with_rng(&mut rng);
+            let q = curve.random_point_with_rng(&mut rng);
+            let (p_ld, q_ld) = (curve.to_ld(&p), curve.to_ld(&q));
+            // a non-trivial Z on both sides
+            let p2_ld = curve.ld_double(&p_ld);
+            let p3_ld = curve.ld_add(&p2_ld, &p_ld);
+            let p3 = curve.add(&curve.double(&p), &p);
+            assert_eq!(curve.to_affine(&p2_ld), curve.double(&p));
+            assert_eq!(curve.to_affine(&p3_ld), p3);
+            assert_eq!(
+                curve.to_affine(&curve.ld_add_mixed(&p2_ld, &q)),
+                curve.add(&curve.double(&p), &q)
+            );
+            assert_eq!(
+                curve.to_affine(&curve.ld_add(&p2_ld, &q_ld)),
+                curve.add(&curve.double(&p), &q)
+            );
+            assert_eq!(
+                curve.to_affine(&curve.ld_add(&p2_ld, &curve.ld_double(&q_ld))),
+                curve.add(&curve.double(&p), &curve.double(&q))
+            );
+            assert_eq!(
+                curve.to_affine(&curve.ld_add(&p3_ld, &curve.ld_add(&p2_ld, &p_ld))),
+                curve.double(&p3)
+            );
+            assert!(curve
+                .ld_add(&p3_ld, &curve.to_ld(&curve.neg(&p3)))
+                .z
+                .is_zero());
+            assert!(curve.ld_add_mixed(&p3_ld, &curve.neg(&p3)).z.is_zero());
+            assert_eq!(
+                curve.to_affine(&curve.ld_add_mixed(&p3_ld, &p3)),
+                curve.double(&p3)
+            );
+
+            let k = bui::from(rng.gen_biguint(96));
+            let l = bui::from(rng.gen_biguint(96));
+            let kp = curve.scalar_mul(&p, &k);
+            let lq = curve.scalar_mul(&q, &l);
+            assert_eq!(
+                curve.multi_scalar_mul(&[(p.clone(), k), (q, l)]),
+                curve.add(&kp, &lq)
+            );
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// Runner for the NIST CAVP ECDSA response files, checked in under
+// test_vectors/cavp. SigGen.txt is the FIPS 186-4 ECDSA2VS file as published;
+// its keys also feed the KeyPair check. Every file must cover each curve in
+// CURVES; a curve without vectors is a failure, while the K- and B-curve
+// sections are skipped. sigver_boringssl.rsp and pkv_synthetic.rsp are not
+// NIST files, see their headers.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::bigint_utils::FromHex;
+use crate::curves::CurveId;
+use crate::ec::Point;
+use crate::sign_ecdsa::{SigningKey, VerifyingKey};
+
+use num_bigint::BigInt as bui;
+use num_traits::One;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+// secp256k1 is not a NIST curve and gets its coverage from Wycheproof
+pub const CURVES: [CurveId; 4] = [CurveId::P224, CurveId::P256, CurveId::P384, CurveId::P521];
+
+pub struct Section {
+    pub name: String,
+    pub records: Vec<HashMap<String, String>>,
+}
+
+pub enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+pub struct Report {
+    pub results: Vec<(String, Outcome)>,
+}
+
+impl Report {
+    pub fn count(&self, pass: bool) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, outcome)| match outcome {
+                Outcome::Pass => pass,
+                Outcome::Fail(_) => !pass,
+                Outcome::Skip(_) => false,
+            })
+            .count()
+    }
+
+    pub fn failures(&self) -> Vec<&String> {
+        self.results
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, Outcome::Fail(_)))
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, outcome) in &self.results {
+            match outcome {
+                Outcome::Pass => writeln!(f, "{name}: pass")?,
+                Outcome::Fail(why) => writeln!(f, "{name}: FAIL ({why})")?,
+                Outcome::Skip(why) => writeln!(f, "{name}: skip ({why})")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// "[P-256,SHA-256]" headers open sections, blank lines separate records,
+// '#' starts a comment and everything else is "key = value"
+pub fn parse_rsp(text: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut record: HashMap<String, String> = HashMap::new();
+
+    fn flush(sections: &mut [Section], record: &mut HashMap<String, String>) {
+        // "N = 10" style counters are not vectors
+        if record.len() > 1 {
+            if let Some(section) = sections.last_mut() {
+                section.records.push(std::mem::take(record));
+            }
+        }
+        record.clear();
+    }
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            flush(&mut sections, &mut record);
+        } else if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            flush(&mut sections, &mut record);
+            sections.push(Section {
+                name: name.to_string(),
+                records: Vec::new(),
+            });
+        } else if let Some((key, value)) = line.split_once('=') {
+            record.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    flush(&mut sections, &mut record);
+
+    sections
+}
+
+pub fn hash(name: &str, m: &[u8]) -> Option<Vec<u8>> {
+    match name {
+        "SHA-224" => Some(Sha224::digest(m).to_vec()),
+        "SHA-256" => Some(Sha256::digest(m).to_vec()),
+        "SHA-384" => Some(Sha384::digest(m).to_vec()),
+        "SHA-512" => Some(Sha512::digest(m).to_vec()),
+        _ => None,
+    }
+}
+
+fn field<'a>(record: &'a HashMap<String, String>, key: &str) -> Result<&'a str, String> {
+    record
+        .get(key)
+        .map(String::as_str)
+        .ok_or_else(|| format!("missing {key}"))
+}
+
+fn int(record: &HashMap<String, String>, key: &str) -> Result<bui, String> {
+    bui::from_hex(field(record, key)?).map_err(|e| format!("{key}: {e}"))
+}
+
+fn bytes(record: &HashMap<String, String>, key: &str) -> Result<Vec<u8>, String> {
+    hex::decode(field(record, key)?).map_err(|e| format!("{key}: {e}"))
+}
+
+// The digest of the record: "Msg" hashed with the section hash, or a
+// precomputed "Digest"
+fn digest(record: &HashMap<String, String>, hash_name: &str) -> Result<Option<Vec<u8>>, String> {
+    if record.contains_key("Msg") {
+        return Ok(hash(hash_name, &bytes(record, "Msg")?));
+    }
+    bytes(record, "Digest").map(Some)
+}
+
+fn point(record: &HashMap<String, String>) -> Result<Point, String> {
+    Ok(Point::new(
+        int(record, "Qx")?,
+        int(record, "Qy")?,
+        Some(bui::one()),
+    ))
+}
+
+fn expects_pass(record: &HashMap<String, String>) -> Result<bool, String> {
+    Ok(field(record, "Result")?.starts_with('P'))
+}
+
+fn run(
+    file: &str,
+    text: &str,
+    check: impl Fn(CurveId, &str, &HashMap<String, String>) -> Result<Outcome, String>,
+) -> Report {
+    let mut results = Vec::new();
+    let mut covered = Vec::new();
+    for section in parse_rsp(text) {
+        let (curve, hash_name) = section
+            .name
+            .split_once(',')
+            .unwrap_or((section.name.as_str(), ""));
+        let id = CurveId::from_name(curve).filter(|id| CURVES.contains(id));
+        if let Some(id) = id.filter(|_| !section.records.is_empty()) {
+            covered.push(id);
+        }
+        for (i, record) in section.records.iter().enumerate() {
+            let name = format!("{file} [{}] #{}", section.name, i + 1);
+            let outcome = match id {
+                Some(id) => check(id, hash_name, record).unwrap_or_else(Outcome::Fail),
+                None => Outcome::Skip(format!("unsupported curve {curve}")),
+            };
+            results.push((name, outcome));
+        }
+    }
+    for id in CURVES.into_iter().filter(|id| !covered.contains(id)) {
+        let name = format!("{file} [{}]", id.name());
+        results.push((
+            name,
+            Outcome::Fail(String::from("no vectors for this curve")),
+        ));
+    }
+    Report { results }
+}
+
+pub fn run_sig_ver(text: &str) -> Report {
+    run("SigVer", text, |id, hash_name, record| {
+        let Some(digest) = digest(record, hash_name)? else {
+            return Ok(Outcome::Skip(format!("unsupported hash {hash_name}")));
+        };
+        let sign = (int(record, "R")?, int(record, "S")?);
+        let accepted = VerifyingKey::from_point(id.ec(), point(record)?)
+            .and_then(|q_a| q_a.verify_digest(&digest, &sign));
+
+        Ok(match (expects_pass(record)?, accepted) {
+            (true, Ok(())) | (false, Err(_)) => Outcome::Pass,
+            (true, Err(why)) => Outcome::Fail(why),
+            (false, Ok(())) => Outcome::Fail(String::from("invalid signature accepted")),
+        })
+    })
+}
+
+pub fn run_sig_gen(text: &str) -> Report {
+    run("SigGen", text, |id, hash_name, record| {
+        let Some(digest) = digest(record, hash_name)? else {
+            return Ok(Outcome::Skip(format!("unsupported hash {hash_name}")));
+        };
+        let ec = id.ec();
+        let key = SigningKey::from_scalar(ec.clone(), int(record, "d")?)?;
+        if key.verifying_key().as_point().get_xy(&ec) != (int(record, "Qx")?, int(record, "Qy")?) {
+            return Ok(Outcome::Fail(String::from("Q does not match d")));
+        }
+
+        let (sign, _) = key.sign_digest_with_k(&digest, &int(record, "k")?)?;
+        Ok(if sign == (int(record, "R")?, int(record, "S")?) {
+            Outcome::Pass
+        } else {
+            Outcome::Fail(String::from("(R, S) does not match"))
+        })
+    })
+}
+
+pub fn run_key_pair(text: &str) -> Report {
+    run("KeyPair", text, |id, _, record| {
+        let ec = id.ec();
+        let key = SigningKey::from_scalar(ec.clone(), int(record, "d")?)?;
+        Ok(
+            if key.verifying_key().as_point().get_xy(&ec)
+                == (int(record, "Qx")?, int(record, "Qy")?)
+            {
+                Outcome::Pass
+            } else {
+                Outcome::Fail(String::from("Q does not match d"))
+            },
+        )
+    })
+}
+
+pub fn run_pkv(text: &str) -> Report {
+    run("PKV", text, |id, _, record| {
+        let valid = VerifyingKey::from_point(id.ec(), point(record)?);
+        Ok(match (expects_pass(record)?, valid) {
+            (true, Ok(_)) | (false, Err(_)) => Outcome::Pass,
+            (true, Err(why)) => Outcome::Fail(why),
+            (false, Ok(_)) => Outcome::Fail(String::from("invalid public key accepted")),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load(name: &str) -> String {
+        let path = format!("{}/test_vectors/cavp/{name}", env!("CARGO_MANIFEST_DIR"));
+        fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"))
+    }
+
+    fn check(report: Report, expected: usize) {
+        print!("{report}");
+        assert!(report.failures().is_empty(), "{:?}", report.failures());
+        assert_eq!(report.count(true), expected);
+    }
+
+    #[test]
+    fn test_parse_rsp() {
+        let sections = parse_rsp(
+            "# comment\n\n[P-256]\n\nN = 2\n\nd = 01\nQx = 02\n\nd = 03\nQx = 04\n[P-384]\nN = 0\n",
+        );
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].name, "P-256");
+        assert_eq!(sections[0].records.len(), 2);
+        assert_eq!(sections[0].records[1]["Qx"], "04");
+        assert!(sections[1].records.is_empty());
+    }
+
+    #[test]
+    fn test_sig_ver() {
+        check(run_sig_ver(&load("sigver_boringssl.rsp")), 338);
+    }
+
+    #[test]
+    fn test_sig_gen() {
+        check(run_sig_gen(&load("SigGen.txt")), 240);
+    }
+
+    #[test]
+    fn test_key_pair() {
+        check(run_key_pair(&load("SigGen.txt")), 240);
+    }
+
+    #[test]
+    fn test_pkv() {
+        check(run_pkv(&load("pkv_synthetic.rsp")), 32);
+    }
+
+    #[test]
+    fn test_missing_curve() {
+        let text = "[P-256]\n\nd = 01\nQx = 6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296\nQy = 4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5\n";
+        let report = run_key_pair(text);
+        assert_eq!(report.count(true), 1);
+        assert_eq!(
+            report.failures(),
+            ["KeyPair [P-224]", "KeyPair [P-384]", "KeyPair [P-521]"]
+        );
+    }
+}
@@ -0,0 +1,642 @@
+use crate::bigint_utils::FromHex;
+use crate::binary_ec::BinaryCurve;
+use crate::ec::{Point, EC};
+use crate::edwards::Edwards;
+use crate::gf2m::Gf2m;
+use crate::montgomery::Montgomery;
+
+use num_bigint::BigInt as bui;
+use num_traits::One;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveId {
+    P224,
+    P256,
+    P384,
+    P521,
+    Secp256k1,
+}
+
+impl CurveId {
+    pub const ALL: [CurveId; 5] = [
+        CurveId::P224,
+        CurveId::P256,
+        CurveId::P384,
+        CurveId::P521,
+        CurveId::Secp256k1,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CurveId::P224 => "P-224",
+            CurveId::P256 => "P-256",
+            CurveId::P384 => "P-384",
+            CurveId::P521 => "P-521",
+            CurveId::Secp256k1 => "secp256k1",
+        }
+    }
+
+    // SEC 2 name
+    pub fn sec_name(&self) -> &'static str {
+        match self {
+            CurveId::P224 => "secp224r1",
+            CurveId::P256 => "secp256r1",
+            CurveId::P384 => "secp384r1",
+            CurveId::P521 => "secp521r1",
+            CurveId::Secp256k1 => "secp256k1",
+        }
+    }
+
+    // DER contents of the namedCurve object identifier
+    pub fn oid(&self) -> &'static [u8] {
+        match self {
+            CurveId::P224 => &[0x2b, 0x81, 0x04, 0x00, 0x21],
+            CurveId::P256 => &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07],
+            CurveId::P384 => &[0x2b, 0x81, 0x04, 0x00, 0x22],
+            CurveId::P521 => &[0x2b, 0x81, 0x04, 0x00, 0x23],
+            CurveId::Secp256k1 => &[0x2b, 0x81, 0x04, 0x00, 0x0a],
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CurveId> {
+        Self::ALL
+            .into_iter()
+            .find(|id| id.name() == name || id.sec_name() == name)
+    }
+
+    pub fn from_oid(oid: &[u8]) -> Option<CurveId> {
+        Self::ALL.into_iter().find(|id| id.oid() == oid)
+    }
+
+    pub fn ec(&self) -> EC {
+        match self {
+            CurveId::P224 => p224(),
+            CurveId::P256 => p256(),
+            CurveId::P384 => p384(),
+            CurveId::P521 => p521(),
+            CurveId::Secp256k1 => secp256k1(),
+        }
+    }
+}
+
+fn from_params(a: &str, b: &str, q: &str, n: &str, g_x: &str, g_y: &str) -> EC {
+    let g = Point::new(
+        bui::from_hex(g_x).unwrap(),
+        bui::from_hex(g_y).unwrap(),
+        Some(bui::one()),
+    );
+    EC::new(
+        bui::from_hex(a).unwrap(),
+        bui::from_hex(b).unwrap(),
+        bui::from_hex(q).unwrap(),
+        Some(bui::from_hex(n).unwrap()),
+        Some(g),
+    )
+}
+
+// FIPS 186-4, D.1.2.2
+pub fn p224() -> EC {
+    from_params(
+        "fffffffffffffffffffffffffffffffefffffffffffffffffffffffe",
+        "b4050a850c04b3abf54132565044b0b7d7bfd8ba270b39432355ffb4",
+        "ffffffffffffffffffffffffffffffff000000000000000000000001",
+        "ffffffffffffffffffffffffffff16a2e0b8f03e13dd29455c5c2a3d",
+        "b70e0cbd6bb4bf7f321390b94a03c1d356c21122343280d6115c1d21",
+        "bd376388b5f723fb4c22dfe6cd4375a05a07476444d5819985007e34",
+    )
+}
+
+// FIPS 186-4, D.1.2.3
+pub fn p256() -> EC {
+    from_params(
+        "ffffffff00000001000000000000000000000000fffffffffffffffffffffffc",
+        "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b",
+        "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+        "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551",
+        "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+        "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5",
+    )
+}
+
+// FIPS 186-4, D.1.2.4
+pub fn p384() -> EC {
+    from_params(
+        "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000fffffffc",
+        "b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef",
+        "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff",
+        "ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973",
+        "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7",
+        "3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f",
+    )
+}
+
+// FIPS 186-4, D.1.2.5
+pub fn p521() -> EC {
+    from_params(
+        "01fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffc",
+        "0051953eb9618e1c9a1f929a21a0b68540eea2da725b99b315f3b8b489918ef109e156193951ec7e937b1652c0bd3bb1bf073573df883d2c34f1ef451fd46b503f00",
+        "01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "01fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffa51868783bf2f966b7fcc0148f709a5d03bb5c9b8899c47aebb6fb71e91386409",
+        "00c6858e06b70404e9cd9e3ecb662395b4429c648139053fb521f828af606b4d3dbaa14b5e77efe75928fe1dc127a2ffa8de3348b3c1856a429bf97e7e31c2e5bd66",
+        "011839296a789a3bc0045c8a5fb42c7d1bd998f54449579b446817afbd17273e662c97ee72995ef42640c550b9013fad0761353c7086a272c24088be94769fd16650",
+    )
+}
+
+// SEC 2, 2.4.1
+pub fn secp256k1() -> EC {
+    from_params(
+        "0",
+        "7",
+        "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+    )
+}
+
+// GB/T 32918.5-2017: the SM2 recommended curve
+pub fn sm2p256v1() -> EC {
+    from_params(
+        "fffffffeffffffffffffffffffffffffffffffff00000000fffffffffffffffc",
+        "28e9fa9e9d9f5e344d5a9e4bcf6509a7f39789f515ab8f92ddbcbd414d940e93",
+        "fffffffeffffffffffffffffffffffffffffffff00000000ffffffffffffffff",
+        "fffffffeffffffffffffffffffffffff7203df6b21c6052b53bbf40939d54123",
+        "32c4ae2c1f1981195f9904466a39c9948fe30bbff2660be1715a4589334c74c7",
+        "bc3736a2f4f6779c59bdcee36b692153d0a9877cc62a474002df32e52139f0a0",
+    )
+}
+
+// RFC 5639, 3.4
+pub fn brainpool_p256r1() -> EC {
+    from_params(
+        "7d5a0975fc2c3057eef67530417affe7fb8055c126dc5c6ce94a4b44f330b5d9",
+        "26dc5c6ce94a4b44f330b5d9bbd77cbf958416295cf7e1ce6bccdc18ff8c07b6",
+        "a9fb57dba1eea9bc3e660a909d838d726e3bf623d52620282013481d1f6e5377",
+        "a9fb57dba1eea9bc3e660a909d838d718c397aa3b561a6f7901e0e82974856a7",
+        "8bd2aeb9cb7e57cb2c4b482ffc81b7afb9de27e1e3bd23c23a4453bd9ace3262",
+        "547ef835c3dac4fd97f8461a14611dc9c27745132ded8e545c1d54c72f046997",
+    )
+}
+
+// RFC 5639, 3.6
+pub fn brainpool_p384r1() -> EC {
+    from_params(
+        "7bc382c63d8c150c3c72080ace05afa0c2bea28e4fb22787139165efba91f90f8aa5814a503ad4eb04a8c7dd22ce2826",
+        "04a8c7dd22ce28268b39b55416f0447c2fb77de107dcd2a62e880ea53eeb62d57cb4390295dbc9943ab78696fa504c11",
+        "8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b412b1da197fb71123acd3a729901d1a71874700133107ec53",
+        "8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b31f166e6cac0425a7cf3ab6af6b7fc3103b883202e9046565",
+        "1d1c64f068cf45ffa2a63a81b7c13f6b8847a3e77ef14fe3db7fcafe0cbd10e8e826e03436d646aaef87b2e247d4af1e",
+        "8abe1d7520f9c2a45cb1eb8e95cfd55262b70b29feec5864e19c054ff99129280e4646217791811142820341263c5315",
+    )
+}
+
+// RFC 8032, 5.1: -x^2 + y^2 = 1 + d x^2 y^2 over 2^255 - 19, cofactor 8
+pub fn edwards25519() -> Edwards {
+    let q =
+        bui::from_hex("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed").unwrap();
+    Edwards::new(
+        &q - 1u32,
+        bui::from_hex("52036cee2b6ffe738cc740797779e89800700a4d4141d8ab75eb4dca135978a3").unwrap(),
+        q,
+        bui::from_hex("1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed").unwrap(),
+        8,
+        bui::from_hex("216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a").unwrap(),
+        bui::from_hex("6666666666666666666666666666666666666666666666666666666666666658").unwrap(),
+    )
+    .unwrap()
+}
+
+// RFC 7748, 4.1: v^2 = u^3 + 486662 u^2 + u over 2^255 - 19, base u = 9
+pub fn curve25519() -> Montgomery {
+    Montgomery::new((bui::one() << 255) - 19, 121665, 255, 3, 9)
+}
+
+// RFC 7748, 4.2: v^2 = u^3 + 156326 u^2 + u over 2^448 - 2^224 - 1, base u = 5
+pub fn curve448() -> Montgomery {
+    Montgomery::new(
+        (bui::one() << 448) - (bui::one() << 224) - 1,
+        39081,
+        448,
+        2,
+        5,
+    )
+}
+
+// FIPS 186-4, D.1.3: the Koblitz curves K-m (a in {0, 1}, b = 1) and the
+// pseudo-random B-m (a = 1). Columns: name, m, middle exponents of f, a,
+// cofactor, b, n, base point.
+type NistBinaryParams = (
+    &'static str,
+    usize,
+    &'static [usize],
+    u32,
+    u32,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+);
+
+#[rustfmt::skip]
+const NIST_BINARY: [NistBinaryParams; 10] = [
+    ("K-163", 163, &[7, 6, 3], 1, 2,
+     "1",
+     "4000000000000000000020108a2e0cc0d99f8a5ef",
+     "2fe13c0537bbc11acaa07d793de4e6d5e5c94eee8",
+     "289070fb05d38ff58321f2e800536d538ccdaa3d9"),
+    ("B-163", 163, &[7, 6, 3], 1, 2,
+     "20a601907b8c953ca1481eb10512f78744a3205fd",
+     "40000000000000000000292fe77e70c12a4234c33",
+     "3f0eba16286a2d57ea0991168d4994637e8343e36",
+     "d51fbc6c71a0094fa2cdd545b11c5c0c797324f1"),
+    ("K-233", 233, &[74], 0, 4,
+     "1",
+     "8000000000000000000000000000069d5bb915bcd46efb1ad5f173abdf",
+     "17232ba853a7e731af129f22ff4149563a419c26bf50a4c9d6eefad6126",
+     "1db537dece819b7f70f555a67c427a8cd9bf18aeb9b56e0c11056fae6a3"),
+    ("B-233", 233, &[74], 1, 2,
+     "66647ede6c332c7f8c0923bb58213b333b20e9ce4281fe115f7d8f90ad",
+     "1000000000000000000000000000013e974e72f8a6922031d2603cfe0d7",
+     "fac9dfcbac8313bb2139f1bb755fef65bc391f8b36f8f8eb7371fd558b",
+     "1006a08a41903350678e58528bebf8a0beff867a7ca36716f7e01f81052"),
+    ("K-283", 283, &[12, 7, 5], 0, 4,
+     "1",
+     "1ffffffffffffffffffffffffffffffffffe9ae2ed07577265dff7f94451e061e163c61",
+     "503213f78ca44883f1a3b8162f188e553cd265f23c1567a16876913b0c2ac2458492836",
+     "1ccda380f1c9e318d90f95d07e5426fe87e45c0e8184698e45962364e34116177dd2259"),
+    ("B-283", 283, &[12, 7, 5], 1, 2,
+     "27b680ac8b8596da5a4af8a19a0303fca97fd7645309fa2a581485af6263e313b79a2f5",
+     "3ffffffffffffffffffffffffffffffffffef90399660fc938a90165b042a7cefadb307",
+     "5f939258db7dd90e1934f8c70b0dfec2eed25b8557eac9c80e2e198f8cdbecd86b12053",
+     "3676854fe24141cb98fe6d4b20d02b4516ff702350eddb0826779c813f0df45be8112f4"),
+    ("K-409", 409, &[87], 0, 4,
+     "1",
+     "7ffffffffffffffffffffffffffffffffffffffffffffffffffe5f83b2d4ea20400ec4557d5ed3e3e7ca5b4b5c83b8e01e5fcf",
+     "60f05f658f49c1ad3ab1890f7184210efd0987e307c84c27accfb8f9f67cc2c460189eb5aaaa62ee222eb1b35540cfe9023746",
+     "1e369050b7c4e42acba1dacbf04299c3460782f918ea427e6325165e9ea10e3da5f6c42e9c55215aa9ca27a5863ec48d8e0286b"),
+    ("B-409", 409, &[87], 1, 2,
+     "21a5c2c8ee9feb5c4b9a753b7b476b7fd6422ef1f3dd674761fa99d6ac27c8a9a197b272822f6cd57a55aa4f50ae317b13545f",
+     "10000000000000000000000000000000000000000000000000001e2aad6a612f33307be5fa47c3c9e052f838164cd37d9a21173",
+     "15d4860d088ddb3496b0c6064756260441cde4af1771d4db01ffe5b34e59703dc255a868a1180515603aeab60794e54bb7996a7",
+     "61b1cfab6be5f32bbfa78324ed106a7636b9c5a7bd198d0158aa4f5488d08f38514f1fdf4b4f40d2181b3681c364ba0273c706"),
+    ("K-571", 571, &[10, 5, 2], 0, 4,
+     "1",
+     "20000000000000000000000000000000000000000000000000000000000000000000000131850e1f19a63e4b391a8db917f4138b630d84be5d639381e91deb45cfe778f637c1001",
+     "26eb7a859923fbc82189631f8103fe4ac9ca2970012d5d46024804801841ca44370958493b205e647da304db4ceb08cbbd1ba39494776fb988b47174dca88c7e2945283a01c8972",
+     "349dc807f4fbf374f4aeade3bca95314dd58cec9f307a54ffc61efc006d8a2c9d4979c0ac44aea74fbebbb9f772aedcb620b01a7ba7af1b320430c8591984f601cd4c143ef1c7a3"),
+    ("B-571", 571, &[10, 5, 2], 1, 2,
+     "2f40e7e2221f295de297117b7f3d62f5c6a97ffcb8ceff1cd6ba8ce4a9a18ad84ffabbd8efa59332be7ad6756a66e294afd185a78ff12aa520e4de739baca0c7ffeff7f2955727a",
+     "3ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe661ce18ff55987308059b186823851ec7dd9ca1161de93d5174d66e8382e9bb2fe84e47",
+     "303001d34b856296c16c0d40d3cd7750a93d1d2955fa80aa5f40fc8db7b2abdbde53950f4c0d293cdd711a35b67fb1499ae60038614f1394abfa3b4c850d927e1e7769c8eec2d19",
+     "37bf27342da639b6dccfffeb73d69d78c6c27a6009cbbca1980f8533921e8a684423e43bab08a576291af8f461bb2a8b3531d2f0485c19b16e2f1516e23dd3c1a4827af1b8ac15b"),
+];
+
+pub fn nist_binary(name: &str) -> Option<BinaryCurve> {
+    let &(_, m, ks, a, h, b, n, g_x, g_y) = NIST_BINARY.iter().find(|c| c.0 == name)?;
+    let curve = BinaryCurve::new(
+        Gf2m::new(m, ks),
+        bui::from(a),
+        bui::from_hex(b).unwrap(),
+        bui::from_hex(n).unwrap(),
+        h,
+        bui::from_hex(g_x).unwrap(),
+        bui::from_hex(g_y).unwrap(),
+    )
+    .unwrap();
+    Some(curve)
+}
+
+pub fn nist_binary_names() -> impl Iterator<Item = &'static str> {
+    NIST_BINARY.iter().map(|c| c.0)
+}
+
+pub fn k163() -> BinaryCurve {
+    nist_binary("K-163").unwrap()
+}
+
+pub fn b163() -> BinaryCurve {
+    nist_binary("B-163").unwrap()
+}
+
+pub fn k233() -> BinaryCurve {
+    nist_binary("K-233").unwrap()
+}
+
+pub fn k283() -> BinaryCurve {
+    nist_binary("K-283").unwrap()
+}
+
+pub fn k409() -> BinaryCurve {
+    nist_binary("K-409").unwrap()
+}
+
+pub fn k571() -> BinaryCurve {
+    nist_binary("K-571").unwrap()
+}
+
+// DSTU 4145-2002, table 6.1: y^2 + x y = x^3 + A x^2 + B over GF(2^m) with
+// f(t) = t^m + sum t^k + 1. The cofactor is 2 for A = 1 and 4 for A = 0.
+// Columns: m, middle exponents of f, A, B, n, base point.
+type BinaryParams = (
+    usize,
+    &'static [usize],
+    u32,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+);
+
+#[rustfmt::skip]
+const DSTU4145: [BinaryParams; 10] = [
+    (163, &[7, 6, 3], 1,
+     "5FF6108462A2DC8210AB403925E638A19C1455D21",
+     "400000000000000000002BEC12BE2262D39BCF14D",
+     "2E2F85F5DD74CE983A5C4237229DAF8A3F35823BE",
+     "3826F008A8C51D7B95284D9D03FF0E00CE2CD723A"),
+    (167, &[6], 1,
+     "6EE3CEEB230811759F20518A0930F1A4315A827DAC",
+     "3FFFFFFFFFFFFFFFFFFFFFB12EBCC7D7F29FF7701F",
+     "7A1F6653786A68192803910A3D30B2A2018B21CD54",
+     "5F49EB26781C0EC6B8909156D98ED435E45FD59918"),
+    (173, &[10, 2, 1], 0,
+     "108576C80499DB2FC16EDDF6853BBB278F6B6FB437D9",
+     "800000000000000000000189B4E67606E3825BB2831",
+     "4D41A619BCC6EADF0448FA22FAD567A9181D37389CA",
+     "10B51CC12849B234C75E6DD2028BF7FF5C1CE0D991A1"),
+    (179, &[4, 2, 1], 1,
+     "4A6E0856526436F2F88DD07A341E32D04184572BEB710",
+     "3FFFFFFFFFFFFFFFFFFFFFFB981960435FE5AB64236EF",
+     "6BA06FE51464B2BD26DC57F48819BA9954667022C7D03",
+     "25FBC363582DCEC065080CA8287AAFF09788A66DC3A9E"),
+    (191, &[9], 1,
+     "7BC86E2102902EC4D5890E8B6B4981FF27E0482750FEFC03",
+     "40000000000000000000000069A779CAC1DABC6788F7474F",
+     "714114B762F2FF4A7912A6D2AC58B9B5C2FCFE76DAEB7129",
+     "29C41E568B77C617EFE5902F11DB96FA9613CD8D03DB08DA"),
+    (233, &[9, 4, 1], 1,
+     "06973B15095675534C7CF7E64A21BD54EF5DD3B8A0326AA936ECE454D2C",
+     "1000000000000000000000000000013E974E72F8A6922031D2603CFE0D7",
+     "3FCDA526B6CDF83BA1118DF35B3C31761D3545F32728D003EEB25EFE96",
+     "9CA8B57A934C54DEEDA9E54A7BBAD95E3B2E91C54D32BE0B9DF96D8D35"),
+    (257, &[12], 0,
+     "1CEF494720115657E18F938D7A7942394FF9425C1458C57861F9EEA6ADBE3BE10",
+     "800000000000000000000000000000006759213AF182E987D3E17714907D470D",
+     "002A29EF207D0E9B6C55CD260B306C7E007AC491CA1B10C62334A9E8DCD8D20FB7",
+     "010686D41FF744D4449FCCF6D8EEA03102E6812C93A9D60B978B702CF156D814EF"),
+    (307, &[8, 4, 2], 1,
+     "393C7F7D53666B5054B5E6C6D3DE94F4296C0C599E2E2E241050DF18B6090BDC90186904968BB",
+     "3FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC079C2F3825DA70D390FBBA588D4604022B7B7",
+     "216EE8B189D291A0224984C1E92F1D16BF75CCD825A087A239B276D3167743C52C02D6E7232AA",
+     "5D9306BACD22B7FAEB09D2E049C6E2866C5D1677762A8F2F2DC9A11C7F7BE8340AB2237C7F2A0"),
+    (367, &[21], 1,
+     "43FC8AD242B0B7A6F3D1627AD5654447556B47BF6AA4A64B0C2AFE42CADAB8F93D92394C79A79755437B56995136",
+     "40000000000000000000000000000000000000000000009C300B75A3FA824F22428FD28CE8812245EF44049B2D49",
+     "324A6EDDD512F08C49A99AE0D3F961197A76413E7BE81A400CA681E09639B5FE12E59A109F78BF4A373541B3B9A1",
+     "1AB597A5B4477F59E39539007C7F977D1A567B92B043A49C6B61984C3FE3481AAF454CD41BA1F051626442B3C10"),
+    (431, &[5, 3, 1], 1,
+     "03CE10490F6A708FC26DFE8C3D27C4F94E690134D5BFF988D8D28AAEAEDE975936C66BAC536B18AE2DC312CA493117DAA469C640CAF3",
+     "3FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFBA3175458009A8C0A724F02F81AA8A1FCBAF80D90C7A95110504CF",
+     "1A62BA79D98133A16BBAE7ED9A8E03C32E0824D57AEF72F88986874E5AAE49C27BED49A2A95058068426C2171E99FD3B43C5947C857D",
+     "70B5E1E14031C1F70BBEFE96BDDE66F451754B4CA5F48DA241F331AA396B8D1839A855C1769B1EA14BA53308B5E2723724E090E02DB9"),
+];
+
+// The standard's table stops at m = 431; there is no recommended 509-bit
+// curve, so any other m is None
+pub fn dstu4145(m: usize) -> Option<BinaryCurve> {
+    let &(m, ks, a, b, n, g_x, g_y) = DSTU4145.iter().find(|c| c.0 == m)?;
+    let curve = BinaryCurve::new(
+        Gf2m::new(m, ks),
+        bui::from(a),
+        bui::from_hex(b).unwrap(),
+        bui::from_hex(n).unwrap(),
+        if a == 1 { 2 } else { 4 },
+        bui::from_hex(g_x).unwrap(),
+        bui::from_hex(g_y).unwrap(),
+    )
+    .unwrap();
+    Some(curve)
+}
+
+pub fn dstu4145_degrees() -> impl Iterator<Item = usize> {
+    DSTU4145.iter().map(|c| c.0)
+}
+
+// GOST R 34.10-2012 parameter sets in short Weierstrass form, under their
+// OpenSSL names: the standard's two test curves (appendix A), the tc26 sets
+// of R 1323565.1.024-2019 and RFC 7836, where 256-B, C and D are the CryptoPro
+// A, B and C sets of RFC 4357. Columns: name, a, b, p, q, base point.
+type GostParams = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+);
+
+#[rustfmt::skip]
+const GOST3410: [GostParams; 7] = [
+    ("id-GostR3410-2001-TestParamSet",
+     "7",
+     "5FBFF498AA938CE739B8E022FBAFEF40563F6E6A3472FC2A514C0CE9DAE23B7E",
+     "8000000000000000000000000000000000000000000000000000000000000431",
+     "8000000000000000000000000000000150FE8A1892976154C59CFC193ACCF5B3",
+     "2",
+     "8E2A8A0E65147D4BD6316030E16D19C85C97F0A9CA267122B96ABBCEA7E8FC8"),
+    ("id-tc26-gost-3410-2012-256-paramSetB",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFD94",
+     "A6",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFD97",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF6C611070995AD10045841B09B761B893",
+     "1",
+     "8D91E471E0989CDA27DF505A453F2B7635294F2DDF23E3B122ACC99C9E9F1E14"),
+    ("id-tc26-gost-3410-2012-256-paramSetC",
+     "8000000000000000000000000000000000000000000000000000000000000C96",
+     "3E1AF419A269A5F866A7D3C25C3DF80AE979259373FF2B182F49D4CE7E1BBC8B",
+     "8000000000000000000000000000000000000000000000000000000000000C99",
+     "800000000000000000000000000000015F700CFFF1A624E5E497161BCC8A198F",
+     "1",
+     "3FA8124359F96680B83D1C3EB2C070E5C545C9858D03ECFB744BF8D717717EFC"),
+    ("id-tc26-gost-3410-2012-256-paramSetD",
+     "9B9F605F5A858107AB1EC85E6B41C8AACF846E86789051D37998F7B9022D7598",
+     "805A",
+     "9B9F605F5A858107AB1EC85E6B41C8AACF846E86789051D37998F7B9022D759B",
+     "9B9F605F5A858107AB1EC85E6B41C8AA582CA3511EDDFB74F02F3A6598980BB9",
+     "0",
+     "41ECE55743711A8C3CBF3783CD08C0EE4D4DC440D4641A8F366E550DFDB3BB67"),
+    ("id-tc26-gost-3410-2012-512-paramSetTest",
+     "7",
+     "1CFF0806A31116DA29D8CFA54E57EB748BC5F377E49400FDD788B649ECA1AC4361834013B2AD7322480A89CA58E0CF74BC9E540C2ADD6897FAD0A3084F302ADC",
+     "4531ACD1FE0023C7550D267B6B2FEE80922B14B2FFB90F04D4EB7C09B5D2D15DF1D852741AF4704A0458047E80E4546D35B8336FAC224DD81664BBF528BE6373",
+     "4531ACD1FE0023C7550D267B6B2FEE80922B14B2FFB90F04D4EB7C09B5D2D15DA82F2D7ECB1DBAC719905C5EECC423F1D86E25EDBE23C595D644AAF187E6E6DF",
+     "24D19CC64572EE30F396BF6EBBFD7A6C5213B3B3D7057CC825F91093A68CD762FD60611262CD838DC6B60AA7EEE804E28BC849977FAC33B4B530F1B120248A9A",
+     "2BB312A43BD2CE6E0D020613C857ACDDCFBF061E91E5F2C3F32447C259F39B2C83AB156D77F1496BF7EB3351E1EE4E43DC1A18B91B24640B6DBB92CB1ADD371E"),
+    ("id-tc26-gost-3410-2012-512-paramSetA",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFDC4",
+     "E8C2505DEDFC86DDC1BD0B2B6667F1DA34B82574761CB0E879BD081CFD0B6265EE3CB090F30D27614CB4574010DA90DD862EF9D4EBEE4761503190785A71C760",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFDC7",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF27E69532F48D89116FF22B8D4E0560609B4B38ABFAD2B85DCACDB1411F10B275",
+     "3",
+     "7503CFE87A836AE3A61B8816E25450E6CE5E1C93ACF1ABC1778064FDCBEFA921DF1626BE4FD036E93D75E6A50E3A41E98028FE5FC235F5B889A589CB5215F2A4"),
+    ("id-tc26-gost-3410-2012-512-paramSetB",
+     "8000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000006C",
+     "687D1B459DC841457E3E06CF6F5E2517B97C7D614AF138BCBF85DC806C4B289F3E965D2DB1416D217F8B276FAD1AB69C50F78BEE1FA3106EFB8CCBC7C5140116",
+     "8000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000006F",
+     "800000000000000000000000000000000000000000000000000000000000000149A1EC142565A545ACFDB77BD9D40CFA8B996712101BEA0EC6346C54374F25BD",
+     "2",
+     "1A8F7EDA389B094C2C071E3647A8940F3C123B697578C213BE6DD9E6C8EC7335DCB228FD1EDF4A39152CBCAAF8C0398828041055F94CEEEC7E21340780FE41BD"),
+];
+
+// The tc26 sets given as twisted Edwards curves e u^2 + v^2 = 1 + d u^2 v^2
+// with cofactor 4. Columns: name, e, d, p, q, base point (u, v).
+#[rustfmt::skip]
+const GOST3410_EDWARDS: [GostParams; 2] = [
+    ("id-tc26-gost-3410-2012-256-paramSetA",
+     "1",
+     "0605F6B7C183FA81578BC39CFAD518132B9DF62897009AF7E522C32D6DC7BFFB",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFD97",
+     "400000000000000000000000000000000FD8CDDFC87B6635C115AF556C360C67",
+     "D",
+     "60CA1E32AA475B348488C38FAB07649CE7EF8DBE87F22E81F92B2592DBA300E7"),
+    ("id-tc26-gost-3410-2012-512-paramSetC",
+     "1",
+     "9E4F5D8C017D8D9F13A5CF3CDF5BFE4DAB402D54198E31EBDE28A0621050439CA6B39E0A515C06B304E2CE43E79E369E91A0CFC2BC2A22B4CA302DBB33EE7550",
+     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFDC7",
+     "3FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC98CDBA46506AB004C33A9FF5147502CC8EDA9E7A769A12694623CEF47F023ED",
+     "12",
+     "469AF79D1FB1F5E16B99592B77A01E2A0FDFB0D01794368D9A56117F7B38669522DD4B650CF789EEBF068C5D139732F0905622C04B2BAAE7600303EE73001A3D"),
+];
+
+pub fn gost3410_edwards(name: &str) -> Option<Edwards> {
+    let &(_, e, d, p, q, u, v) = GOST3410_EDWARDS.iter().find(|c| c.0 == name)?;
+    let curve = Edwards::new(
+        bui::from_hex(e).unwrap(),
+        bui::from_hex(d).unwrap(),
+        bui::from_hex(p).unwrap(),
+        bui::from_hex(q).unwrap(),
+        4,
+        bui::from_hex(u).unwrap(),
+        bui::from_hex(v).unwrap(),
+    )
+    .unwrap();
+    Some(curve)
+}
+
+// Any of the sets above in Weierstrass form, the Edwards ones mapped over
+pub fn gost3410(name: &str) -> Option<EC> {
+    if let Some(ed) = gost3410_edwards(name) {
+        return Some(ed.to_weierstrass());
+    }
+    let &(_, a, b, p, q, x, y) = GOST3410.iter().find(|c| c.0 == name)?;
+    Some(from_params(a, b, p, q, x, y))
+}
+
+pub fn gost3410_names() -> impl Iterator<Item = &'static str> {
+    GOST3410.iter().chain(&GOST3410_EDWARDS).map(|c| c.0)
+}
+
+// y^2 = x^3 + 6 over F_463, prime order 421: small enough to hit the edge
+// cases that are negligible on real curves
+#[cfg(test)]
+pub fn toy_463() -> EC {
+    let g = Point::new(bui::from(3), bui::from(251), Some(bui::one()));
+    EC::new(
+        bui::from(0),
+        bui::from(6),
+        bui::from(463),
+        Some(bui::from(421)),
+        Some(g),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::Zero;
+
+    #[test]
+    fn test_generators() {
+        for id in CurveId::ALL {
+            let ec = id.ec();
+            let g = ec.get_ref_p();
+            assert!(ec.on_curve(g), "{}", id.name());
+
+            let o_e = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+            assert!(
+                ec.scalar_mul(g, ec.get_ref_n()).same_coords(&o_e),
+                "{}",
+                id.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_generators() {
+        for (name, ec) in [
+            ("sm2p256v1", sm2p256v1()),
+            ("brainpoolP256r1", brainpool_p256r1()),
+            ("brainpoolP384r1", brainpool_p384r1()),
+        ] {
+            let g = ec.get_ref_p();
+            assert!(ec.on_curve(g), "{name}");
+            assert!(ec.scalar_mul(g, ec.get_ref_n()).is_infinity(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_dstu4145_base_points() {
+        for m in dstu4145_degrees() {
+            let curve = dstu4145(m).unwrap();
+            let p = curve.get_ref_p();
+            assert!(
+                curve.scalar_mul(p, curve.get_ref_n()).is_infinity(),
+                "m = {m}"
+            );
+            assert!(curve.get_ref_n().bits() <= m as u64, "m = {m}");
+        }
+        assert!(dstu4145(509).is_none());
+    }
+
+    #[test]
+    fn test_nist_binary_base_points() {
+        for name in nist_binary_names() {
+            let curve = nist_binary(name).unwrap();
+            let p = curve.get_ref_p();
+            assert!(
+                curve.scalar_mul(p, curve.get_ref_n()).is_infinity(),
+                "{name}"
+            );
+        }
+        assert!(nist_binary("K-512").is_none());
+    }
+
+    #[test]
+    fn test_gost3410_params() {
+        for name in gost3410_names() {
+            let ec = gost3410(name).unwrap();
+            let g = ec.get_ref_p();
+            assert!(ec.on_curve(g), "{name}");
+            assert!(ec.scalar_mul(g, ec.get_ref_n()).is_infinity(), "{name}");
+        }
+        // the 256-bit Edwards set maps onto a = C2173F15..., b = 295F9BAE...
+        let ec = gost3410("id-tc26-gost-3410-2012-256-paramSetA").unwrap();
+        assert_eq!(
+            *ec.get_ref_a(),
+            bui::from_hex("C2173F1513981673AF4892C23035A27CE25E2013BF95AA33B22C656F277E7335")
+                .unwrap()
+        );
+        assert_eq!(
+            *ec.get_ref_b(),
+            bui::from_hex("295F9BAE7428ED9CCC20E7C359A9D41A22FCCD9108E17BF7BA9337A6F8AE9513")
+                .unwrap()
+        );
+        assert_eq!(
+            ec.get_ref_p().get_xy(&ec).0,
+            bui::from_hex("91E38443A5E82C0D880923425712B2BB658B9196932E02C78B2582FE742DAA28")
+                .unwrap()
+        );
+        assert!(gost3410("id-tc26-gost-3410-2012-256-paramSetE").is_none());
+    }
+}
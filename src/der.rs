@@ -0,0 +1,196 @@
+// Strict DER for the two structures the crate exchanges: ECDSA-Sig-Value
+// (RFC 3279, 2.2.3) and SubjectPublicKeyInfo with a named curve (RFC 5480)
+
+use crate::curves::CurveId;
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+
+const SEQUENCE: u8 = 0x30;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+
+// 1.2.840.10045.2.1
+const ID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+// Splits one TLV off the front of the input, rejecting non-minimal lengths
+fn read_tlv(bytes: &[u8], tag: u8) -> Result<(&[u8], &[u8]), String> {
+    match bytes.first() {
+        Some(&t) if t == tag => {}
+        Some(t) => return Err(format!("Expected tag {tag:#04x}, found {t:#04x}")),
+        None => return Err(String::from("Unexpected end of input")),
+    }
+    let first = *bytes.get(1).ok_or("Missing length")? as usize;
+    let (len, offset) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let n = first & 0x7f;
+        if n == 0 || n > 4 {
+            return Err(String::from("Unsupported length encoding"));
+        }
+        let len_bytes = bytes.get(2..2 + n).ok_or("Truncated length")?;
+        if len_bytes[0] == 0 {
+            return Err(String::from("Length is not minimal"));
+        }
+        let len = len_bytes
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        if len < 0x80 {
+            return Err(String::from("Length is not minimal"));
+        }
+        (len, 2 + n)
+    };
+    let end = offset.checked_add(len).ok_or("Length overflow")?;
+    let content = bytes.get(offset..end).ok_or("Truncated value")?;
+    Ok((content, &bytes[end..]))
+}
+
+fn write_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let len_bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|&b| b == 0)
+            .collect();
+        out.push(0x80 | len_bytes.len() as u8);
+        out.extend(len_bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+// Non-negative INTEGER in its shortest form
+fn read_uint(bytes: &[u8]) -> Result<(bui, &[u8]), String> {
+    let (content, rest) = read_tlv(bytes, INTEGER)?;
+    match content {
+        [] => return Err(String::from("Empty INTEGER")),
+        [b, ..] if b & 0x80 != 0 => return Err(String::from("Negative INTEGER")),
+        [0, b, ..] if b & 0x80 == 0 => return Err(String::from("INTEGER is not minimal")),
+        _ => {}
+    }
+    Ok((bui::from_bytes_be(Sign::Plus, content), rest))
+}
+
+fn write_uint(v: &bui) -> Vec<u8> {
+    let mut content = v.to_bytes_be().1;
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    write_tlv(INTEGER, &content)
+}
+
+pub fn decode_signature(bytes: &[u8]) -> Result<(bui, bui), String> {
+    let (seq, rest) = read_tlv(bytes, SEQUENCE)?;
+    if !rest.is_empty() {
+        return Err(String::from("Trailing data after signature"));
+    }
+    let (r, seq) = read_uint(seq)?;
+    let (s, seq) = read_uint(seq)?;
+    if !seq.is_empty() {
+        return Err(String::from("Trailing data inside signature"));
+    }
+    Ok((r, s))
+}
+
+pub fn encode_signature(sign: &(bui, bui)) -> Vec<u8> {
+    let mut content = write_uint(&sign.0);
+    content.extend(write_uint(&sign.1));
+    write_tlv(SEQUENCE, &content)
+}
+
+// Returns the curve and the SEC 1 encoded point; explicit parameters and
+// unknown curves are rejected
+pub fn decode_spki(bytes: &[u8]) -> Result<(CurveId, Vec<u8>), String> {
+    let (spki, rest) = read_tlv(bytes, SEQUENCE)?;
+    if !rest.is_empty() {
+        return Err(String::from("Trailing data after public key"));
+    }
+    let (algorithm, spki) = read_tlv(spki, SEQUENCE)?;
+    let (key, spki) = read_tlv(spki, BIT_STRING)?;
+    if !spki.is_empty() {
+        return Err(String::from("Trailing data inside public key"));
+    }
+
+    let (oid, algorithm) = read_tlv(algorithm, OBJECT_IDENTIFIER)?;
+    if oid != ID_EC_PUBLIC_KEY {
+        return Err(String::from("Not an EC public key"));
+    }
+    let (curve, algorithm) = read_tlv(algorithm, OBJECT_IDENTIFIER)
+        .map_err(|_| String::from("Only named curves are supported"))?;
+    if !algorithm.is_empty() {
+        return Err(String::from("Trailing data inside algorithm identifier"));
+    }
+    let id = CurveId::from_oid(curve).ok_or("Unsupported curve")?;
+
+    match key {
+        [0, point @ ..] => Ok((id, point.to_vec())),
+        _ => Err(String::from("BIT STRING has unused bits")),
+    }
+}
+
+pub fn encode_spki(id: CurveId, point: &[u8]) -> Vec<u8> {
+    let mut algorithm = write_tlv(OBJECT_IDENTIFIER, ID_EC_PUBLIC_KEY);
+    algorithm.extend(write_tlv(OBJECT_IDENTIFIER, id.oid()));
+
+    let mut key = vec![0];
+    key.extend_from_slice(point);
+
+    let mut content = write_tlv(SEQUENCE, &algorithm);
+    content.extend(write_tlv(BIT_STRING, &key));
+    write_tlv(SEQUENCE, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::FromHex;
+
+    #[test]
+    fn test_signature_round_trip() {
+        let sign = (
+            bui::from_hex("b292a619339f6e567a305c951c0dcbcc42d16e47f219f9e98e76e09d8770b34a")
+                .unwrap(),
+            bui::from_hex("0177e60492c5a8242f76f07bfe3661bde59ec2a17ce5bd2dab2abebdf89a62e2")
+                .unwrap(),
+        );
+        let der = encode_signature(&sign);
+        assert_eq!(
+            hex::encode(&der),
+            "3045022100b292a619339f6e567a305c951c0dcbcc42d16e47f219f9e98e76e09d8770b34a02200177e60492c5a8242f76f07bfe3661bde59ec2a17ce5bd2dab2abebdf89a62e2"
+        );
+        assert_eq!(decode_signature(&der).unwrap(), sign);
+    }
+
+    #[test]
+    fn test_signature_rejects_ber() {
+        for bad in [
+            "3007020101020101",     // length past the end
+            "30060201010201010000", // trailing data
+            "308106020101020101",   // long form for a short length
+            "300702020001020101",   // non-minimal INTEGER
+            "3006020181020101",     // negative r
+            "30050200020101",       // empty INTEGER
+        ] {
+            assert!(
+                decode_signature(&hex::decode(bad).unwrap()).is_err(),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_spki_round_trip() {
+        let point = hex::decode("0462d5bd3372af75fe85a040715d0f502428e07046868b0bfdfa61d731afe44f26ac333a93a9e70a81cd5a95b5bf8d13990eb741c8c38872b4a07d275a014e30cf").unwrap();
+        let der = encode_spki(CurveId::P256, &point);
+        assert_eq!(
+            hex::encode(&der[..27]),
+            "3059301306072a8648ce3d020106082a8648ce3d03010703420004"
+        );
+        assert_eq!(decode_spki(&der).unwrap(), (CurveId::P256, point));
+    }
+}
@@ -1,32 +1,44 @@
-use std::str::FromStr;
-
-use crate::ec::{Point, EC};
+use crate::bigint_utils::Wipe;
+use crate::binary_ec::{BinPoint, BinaryCurve};
+use crate::ec::{to_fixed_be, Point, EC};
+use crate::keygen::{gen_binary_key_pair_with_rng, gen_key_pair_with_rng, KeyGenMethod};
+use crate::montgomery::Montgomery;
 
 use num_bigint::BigInt as bui;
-use num_bigint::RandBigInt;
-use num_bigint::ToBigInt;
 use num_traits::One;
+use rand::{CryptoRng, RngCore};
 
-struct DH {
+pub struct DH {
     ec: EC,
     d_a: Option<bui>,
 }
 
 impl DH {
-    fn init(ec: EC) -> DH {
-        let mut rng = rand::thread_rng();
+    pub fn init(ec: EC) -> DH {
+        Self::init_with_rng(ec, &mut rand::thread_rng())
+    }
 
-        let d = rng.gen_bigint_range(&(2).to_bigint().unwrap(), ec.get_ref_n());
+    pub fn init_with_rng(ec: EC, rng: &mut (impl RngCore + CryptoRng)) -> DH {
+        let (d, _) = gen_key_pair_with_rng(&ec, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
 
         Self { ec, d_a: Some(d) }
     }
 
-    fn send(&self) -> Point {
+    pub fn from_scalar(ec: EC, d: bui) -> Result<DH, String> {
+        if d < bui::one() || &d >= ec.get_ref_n() {
+            return Err(String::from("Private key is out of range"));
+        }
+        Ok(Self { ec, d_a: Some(d) })
+    }
+
+    pub fn send(&self) -> Point {
         self.ec
             .scalar_mul(self.ec.get_ref_p(), self.d_a.as_ref().unwrap())
     }
 
-    fn recieve(&self, q_b: &Point) -> Result<Point, String> {
+    pub fn recieve(&self, q_b: &Point) -> Result<Point, String> {
+        self.ec.validate_public_key(q_b)?;
         match &self.d_a {
             Some(d) => Ok(self.ec.scalar_mul(q_b, d)),
             None => Err(String::from("You don't have private parametrs")),
@@ -34,14 +46,106 @@ impl DH {
     }
 }
 
+// DH over a binary curve (X9.63 ECDH): the shared value is x(d Q_b)
+pub struct BinaryDh {
+    curve: BinaryCurve,
+    d: bui,
+}
+
+impl BinaryDh {
+    pub fn init(curve: BinaryCurve) -> BinaryDh {
+        Self::init_with_rng(curve, &mut rand::thread_rng())
+    }
+
+    pub fn init_with_rng(curve: BinaryCurve, rng: &mut (impl RngCore + CryptoRng)) -> BinaryDh {
+        let (d, _) = gen_binary_key_pair_with_rng(&curve, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+
+        Self { curve, d }
+    }
+
+    pub fn from_scalar(curve: BinaryCurve, d: bui) -> Result<BinaryDh, String> {
+        if d < bui::one() || &d >= curve.get_ref_n() {
+            return Err(String::from("Private key is out of range"));
+        }
+        Ok(Self { curve, d })
+    }
+
+    pub fn send(&self) -> BinPoint {
+        self.curve.scalar_mul(self.curve.get_ref_p(), &self.d)
+    }
+
+    // the x coordinate of d Q_b as a field-size big-endian string
+    pub fn recieve(&self, q_b: &BinPoint) -> Result<Vec<u8>, String> {
+        self.curve.validate_public_key(q_b)?;
+        let BinPoint::Affine(x, _) = self.curve.scalar_mul(q_b, &self.d) else {
+            return Err(String::from("Shared point is the point at infinity"));
+        };
+        Ok(to_fixed_be(&x, self.curve.field().byte_len()))
+    }
+}
+
+impl Drop for BinaryDh {
+    fn drop(&mut self) {
+        self.d.wipe();
+    }
+}
+
+// X25519 / X448 counterpart of DH: the private key is a clamped scalar and
+// the public values are RFC 7748 u-coordinates
+pub struct Xdh {
+    curve: Montgomery,
+    k: Vec<u8>,
+}
+
+impl Xdh {
+    pub fn init_with_rng(curve: Montgomery, rng: &mut (impl RngCore + CryptoRng)) -> Xdh {
+        let mut k = vec![0u8; curve.byte_len()];
+        rng.fill_bytes(&mut k);
+        Self { curve, k }
+    }
+
+    pub fn from_bytes(curve: Montgomery, k: &[u8]) -> Result<Xdh, String> {
+        curve.clamp(k)?;
+        Ok(Self {
+            curve,
+            k: k.to_vec(),
+        })
+    }
+
+    pub fn send(&self) -> Vec<u8> {
+        self.curve
+            .scalar_mul(&self.k, &self.curve.base_u())
+            .unwrap()
+    }
+
+    // RFC 7748, 6: a small-order u gives the all-zero secret, which is refused
+    pub fn recieve(&self, u: &[u8]) -> Result<Vec<u8>, String> {
+        let shared = self.curve.scalar_mul(&self.k, u)?;
+        if shared.iter().all(|&b| b == 0) {
+            return Err(String::from("Shared secret is zero"));
+        }
+        Ok(shared)
+    }
+}
+
+impl Drop for Xdh {
+    fn drop(&mut self) {
+        self.k.fill(0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::bigint_utils::FromHex;
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
     use std::time::{Duration, Instant};
 
     #[test]
-    fn test_DH() {
+    fn test_dh() {
         let p = Point::new(
             bui::from_hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296")
                 .unwrap(),
@@ -63,15 +167,15 @@ mod tests {
         let t = 100;
         for _ in 0..t {
             let start = Instant::now();
-            let Alice = DH::init(ec_p256.clone());
-            let Bob = DH::init(ec_p256.clone());
+            let alice = DH::init(ec_p256.clone());
+            let bob = DH::init(ec_p256.clone());
 
-            let bob_sec = Alice.recieve(&Bob.send());
-            let alice_sec = Bob.recieve(&Alice.send());
+            let bob_sec = alice.recieve(&bob.send());
+            let alice_sec = bob.recieve(&alice.send());
 
             total += start.elapsed();
 
-            assert!(Point::cmp(
+            assert!(Point::same_coords(
                 &ec_p256.convert(&bob_sec.unwrap()).unwrap(),
                 &ec_p256.convert(&alice_sec.unwrap()).unwrap()
             ));
@@ -79,4 +183,128 @@ mod tests {
 
         println!("DH time: {:?}", total / t);
     }
+
+    #[test]
+    fn test_dh_seeded() {
+        let ec = curves::p256();
+        let mut rng = ChaCha20Rng::seed_from_u64(4);
+        let alice = DH::init_with_rng(ec.clone(), &mut rng);
+        let bob = DH::init_with_rng(ec.clone(), &mut rng);
+
+        let (bob_sec, _) = alice.recieve(&bob.send()).unwrap().get_xy(&ec);
+        let (alice_sec, _) = bob.recieve(&alice.send()).unwrap().get_xy(&ec);
+        assert_eq!(bob_sec, alice_sec);
+        assert_eq!(
+            bob_sec,
+            bui::from_hex("4b75cb14c9d41d089191f0774dff8f65f01cba0f20f4a70e49a696fcbfbc2743")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_xdh_rfc7748() {
+        // RFC 7748, 6.1 and 6.2
+        for (curve, a, a_pub, b, b_pub, shared) in [
+            (
+                curves::curve25519(),
+                "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+                "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a",
+                "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+                "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f",
+                "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742",
+            ),
+            (
+                curves::curve448(),
+                "9a8f4925d1519f5775cf46b04b5800d4ee9ee8bae8bc5565d498c28dd9c9baf574a9419744897391006382a6f127ab1d9ac2d8c0a598726b",
+                "9b08f7cc31b7e3e67d22d5aea121074a273bd2b83de09c63faa73d2c22c5d9bbc836647241d953d40c5b12da88120d53177f80e532c41fa0",
+                "1c306a7ac2a0e2e0990b294470cba339e6453772b075811d8fad0d1d6927c120bb5ee8972b0d3e21374c9c921b09d1b0366f10b65173992d",
+                "3eb7a829b0cd20f5bcfc0b599b6feccf6da4627107bdb0d4f345b43027d8b972fc3e34fb4232a13ca706dcb57aec3dae07bdc1c67bf33609",
+                "07fff4181ac6cc95ec1c16a94a0f74d12da232ce40a77552281d282bb60c0b56fd2464c335543936521c24403085d59a449a5037514a879d",
+            ),
+        ] {
+            let alice = Xdh::from_bytes(curve.clone(), &hex::decode(a).unwrap()).unwrap();
+            let bob = Xdh::from_bytes(curve, &hex::decode(b).unwrap()).unwrap();
+            assert_eq!(hex::encode(alice.send()), a_pub);
+            assert_eq!(hex::encode(bob.send()), b_pub);
+            assert_eq!(hex::encode(alice.recieve(&bob.send()).unwrap()), shared);
+            assert_eq!(hex::encode(bob.recieve(&alice.send()).unwrap()), shared);
+        }
+    }
+
+    #[test]
+    fn test_xdh_rejects_small_order() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let alice = Xdh::init_with_rng(curves::curve25519(), &mut rng);
+        let bob = Xdh::init_with_rng(curves::curve25519(), &mut rng);
+        assert_eq!(
+            alice.recieve(&bob.send()).unwrap(),
+            bob.recieve(&alice.send()).unwrap()
+        );
+        // u = 0 and u = 1 have small order
+        let mut u = [0u8; 32];
+        assert!(alice.recieve(&u).is_err());
+        u[0] = 1;
+        assert!(alice.recieve(&u).is_err());
+        assert!(alice.recieve(&u[..31]).is_err());
+        assert!(Xdh::from_bytes(curves::curve448(), &[0; 32]).is_err());
+    }
+
+    // test_vectors/openssl/binary_curves.csv, see the README there
+    #[test]
+    fn test_binary_dh_openssl() {
+        let path = format!(
+            "{}/test_vectors/openssl/binary_curves.csv",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+        let mut count = 0;
+        for line in text.lines().skip(1) {
+            let fields: Vec<&str> = line.split(',').collect();
+            let [name, d, _, _, _, _, _, peer_d, shared] = fields[..] else {
+                panic!("malformed line {line}");
+            };
+            let curve = curves::nist_binary(name).unwrap();
+            let alice = BinaryDh::from_scalar(curve.clone(), bui::from_hex(d).unwrap()).unwrap();
+            let bob = BinaryDh::from_scalar(curve, bui::from_hex(peer_d).unwrap()).unwrap();
+            assert_eq!(
+                hex::encode(alice.recieve(&bob.send()).unwrap()),
+                shared,
+                "{name}"
+            );
+            assert_eq!(
+                hex::encode(bob.recieve(&alice.send()).unwrap()),
+                shared,
+                "{name}"
+            );
+            count += 1;
+        }
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_binary_dh_rejects_invalid_point() {
+        let curve = curves::b163();
+        let alice = BinaryDh::init(curve.clone());
+        let BinPoint::Affine(x, y) = alice.send() else {
+            panic!("public key is the point at infinity");
+        };
+        assert!(alice.recieve(&BinPoint::Affine(x, y ^ bui::one())).is_err());
+        assert!(alice.recieve(&BinPoint::Infinity).is_err());
+        // the point of order 2 is on the curve but outside the subgroup
+        assert!(alice
+            .recieve(&curve.lift_x(&bui::from(0)).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_dh_rejects_invalid_point() {
+        let ec = curves::p256();
+        let alice = DH::init(ec.clone());
+        let (x, y) = ec.get_ref_p().get_xy(&ec);
+        let off_curve = Point::new(x, y + 1, Some(bui::one()));
+        assert!(alice.recieve(&off_curve).is_err());
+        assert!(alice
+            .recieve(&ec.scalar_mul(ec.get_ref_p(), ec.get_ref_n()))
+            .is_err());
+    }
 }
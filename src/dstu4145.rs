@@ -0,0 +1,313 @@
+// DSTU 4145-2002 signatures over binary curves. The public key is Q = -d P,
+// so verification adds s P + r Q = e P with no inversion mod n. The digest is
+// taken to GF(2^m) and multiplied by x(e P); r is that field element read as an
+// integer and cut to L(n) - 1 bits. The standard hashes with GOST 34.311,
+// which the crate does not have, so only the *_digest methods are exposed and
+// the caller chooses the hash.
+
+use crate::bigint_utils::Wipe;
+use crate::binary_ec::{BinPoint, BinaryCurve};
+use crate::keygen::{gen_binary_key_pair_with_rng, KeyGenMethod};
+
+use num_bigint::BigInt as bui;
+use num_bigint::RandBigInt;
+use num_bigint::Sign;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+pub struct SigningKey {
+    d: bui,
+    verifying_key: VerifyingKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
+    curve: BinaryCurve,
+    q: BinPoint,
+}
+
+// The offline half of signing: a random e with F_e = x(e P) != 0
+pub struct Presignature {
+    e: bui,
+    f_e: bui,
+    // order of the curve the presignature was made for
+    n: bui,
+}
+
+// h(T): the digest as a big-endian integer, cut to the low m bits; 0 is
+// replaced by 1
+pub fn hash_to_field(curve: &BinaryCurve, digest: &[u8]) -> bui {
+    let m = curve.field().m();
+    let h: bui = bui::from_bytes_be(Sign::Plus, digest) & ((bui::one() << m) - 1);
+    if h.is_zero() {
+        bui::one()
+    } else {
+        h
+    }
+}
+
+// The field element y read as an integer, keeping its L(n) - 1 low bits
+fn truncate(y: &bui, n: &bui) -> bui {
+    y & ((bui::one() << (n.bits() - 1)) - 1)
+}
+
+impl Presignature {
+    pub fn generate_with_rng(
+        curve: &BinaryCurve,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Presignature {
+        let n = curve.get_ref_n();
+        loop {
+            let e = rng.gen_bigint_range(&bui::one(), n);
+            if let Ok(presig) = Self::from_scalar(curve, e) {
+                break presig;
+            }
+        }
+    }
+
+    pub(crate) fn from_scalar(curve: &BinaryCurve, e: bui) -> Result<Presignature, String> {
+        let n = curve.get_ref_n();
+        if e <= bui::zero() || &e >= n {
+            return Err(String::from("Nonce must be in [1, n - 1]"));
+        }
+        match curve.scalar_mul(curve.get_ref_p(), &e) {
+            BinPoint::Affine(x, _) if !x.is_zero() => Ok(Presignature {
+                e,
+                f_e: x,
+                n: n.clone(),
+            }),
+            _ => Err(String::from("F_e = 0, another nonce is needed")),
+        }
+    }
+
+    pub fn f_e(&self) -> &bui {
+        &self.f_e
+    }
+}
+
+impl Drop for Presignature {
+    fn drop(&mut self) {
+        self.e.wipe();
+    }
+}
+
+impl SigningKey {
+    pub fn random(curve: BinaryCurve) -> SigningKey {
+        Self::random_with_rng(curve, &mut rand::thread_rng())
+    }
+
+    pub fn random_with_rng(curve: BinaryCurve, rng: &mut (impl RngCore + CryptoRng)) -> SigningKey {
+        // Q = -dP, so negate the dP of the generated pair
+        let (d, q) = gen_binary_key_pair_with_rng(&curve, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+        let q = curve.neg(&q);
+
+        Self {
+            d,
+            verifying_key: VerifyingKey { curve, q },
+        }
+    }
+
+    pub fn from_scalar(curve: BinaryCurve, d: bui) -> Result<SigningKey, String> {
+        if d <= bui::zero() || &d >= curve.get_ref_n() {
+            return Err(String::from("Private key must be in [1, n - 1]"));
+        }
+        let q = curve.neg(&curve.scalar_mul(curve.get_ref_p(), &d));
+
+        Ok(Self {
+            d,
+            verifying_key: VerifyingKey { curve, q },
+        })
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    pub fn sign_digest(&self, digest: &[u8]) -> Result<(bui, bui), String> {
+        self.sign_digest_with_rng(digest, &mut rand::thread_rng())
+    }
+
+    pub fn sign_digest_with_rng(
+        &self,
+        digest: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        if digest.is_empty() {
+            return Err(String::from("Digest is empty"));
+        }
+        loop {
+            let presig = Presignature::generate_with_rng(&self.verifying_key.curve, rng);
+            if let Ok(sign) = self.sign_digest_with_presig(digest, presig) {
+                break Ok(sign);
+            }
+        }
+    }
+
+    // r = trunc(h F_e), s = (e + d r) mod n; fails when r = 0 or s = 0, and
+    // the presignature is consumed either way
+    pub fn sign_digest_with_presig(
+        &self,
+        digest: &[u8],
+        presig: Presignature,
+    ) -> Result<(bui, bui), String> {
+        let curve = &self.verifying_key.curve;
+        let n = curve.get_ref_n();
+        if presig.n != *n {
+            return Err(String::from("Presignature was made for another curve"));
+        }
+        let h = hash_to_field(curve, digest);
+        let y = curve.field().mul(&h, &presig.f_e);
+        let r = truncate(&y, n);
+        if r.is_zero() {
+            return Err(String::from("r = 0, another nonce is needed"));
+        }
+        let s = (&presig.e + &self.d * &r) % n;
+        if s.is_zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        Ok((r, s))
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.d.wipe();
+    }
+}
+
+impl VerifyingKey {
+    pub fn from_point(curve: BinaryCurve, q: BinPoint) -> Result<VerifyingKey, String> {
+        curve.validate_public_key(&q)?;
+        Ok(Self { curve, q })
+    }
+
+    pub fn as_point(&self) -> &BinPoint {
+        &self.q
+    }
+
+    pub fn curve(&self) -> &BinaryCurve {
+        &self.curve
+    }
+
+    // R = s P + r Q, then r must equal trunc(h x(R))
+    pub fn verify_digest(&self, digest: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        let curve = &self.curve;
+        let n = curve.get_ref_n();
+        let (r, s) = sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
+
+        let big_r = curve.multi_scalar_mul(&[
+            (curve.get_ref_p().clone(), s.clone()),
+            (self.q.clone(), r.clone()),
+        ]);
+        let BinPoint::Affine(x, _) = big_r else {
+            return Err(String::from("R is the point at infinity"));
+        };
+        let h = hash_to_field(curve, digest);
+        if truncate(&curve.field().mul(&h, &x), n) == *r {
+            Ok(())
+        } else {
+            Err(String::from("Signature does not match"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::int;
+    use crate::curves;
+    use crate::sign_ecdsa::sha256;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // DSTU 4145-2002, appendix B: the m = 163 curve with the example base point
+    #[test]
+    fn test_worked_example() {
+        let table = curves::dstu4145(163).unwrap();
+        let curve = BinaryCurve::new(
+            table.field().clone(),
+            table.get_ref_a().clone(),
+            table.get_ref_b().clone(),
+            table.get_ref_n().clone(),
+            table.cofactor(),
+            int("72D867F93A93AC27DF9FF01AFFE74885C8C540420"),
+            int("0224A9C3947852B97C5599D5F4AB81122ADC3FD9B"),
+        )
+        .unwrap();
+        let key = SigningKey::from_scalar(
+            curve.clone(),
+            int("183F60FDF7951FF47D67193F8D073790C1C9B5A3E"),
+        )
+        .unwrap();
+        assert_eq!(
+            *key.verifying_key().as_point(),
+            BinPoint::Affine(
+                int("57DE7FDE023FF929CB6AC785CE4B79CF64ABDC2DA"),
+                int("3E85444324BCF06AD85ABF6AD7B5F34770532B9AA"),
+            )
+        );
+
+        let digest =
+            hex::decode("09C9C44277910C9AAEE486883A2EB95B7180166DDF73532EEB76EDAEF52247FF")
+                .unwrap();
+        assert_eq!(
+            hash_to_field(&curve, &digest),
+            int("03A2EB95B7180166DDF73532EEB76EDAEF52247FF")
+        );
+        let presig =
+            Presignature::from_scalar(&curve, int("1025E40BD97DB012B7A1D79DE8E12932D247F61C6"))
+                .unwrap();
+        assert_eq!(
+            *presig.f_e(),
+            int("42A7D756D70E1C9BA62D2CB43707C35204EF3C67C")
+        );
+
+        let sign = key.sign_digest_with_presig(&digest, presig).unwrap();
+        assert_eq!(sign.0, int("274EA2C0CAA014A0D80A424F59ADE7A93068D08A7"));
+        assert_eq!(sign.1, int("2100D86957331832B8E8C230F5BD6A332B3615ACA"));
+        assert!(key.verifying_key().verify_digest(&digest, &sign).is_ok());
+
+        let mut other = digest.clone();
+        other[31] ^= 1;
+        assert!(key.verifying_key().verify_digest(&other, &sign).is_err());
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = StdRng::seed_from_u64(4145);
+        // A = 1 and A = 0 curves
+        for m in [163, 257] {
+            let key = SigningKey::random_with_rng(curves::dstu4145(m).unwrap(), &mut rng);
+            let vk = key.verifying_key();
+            let vk = VerifyingKey::from_point(vk.curve().clone(), vk.as_point().clone()).unwrap();
+            let digest = sha256(b"dstu 4145");
+            let sign = key.sign_digest_with_rng(&digest, &mut rng).unwrap();
+            assert!(vk.verify_digest(&digest, &sign).is_ok());
+            assert!(vk.verify_digest(&sha256(b"dstu 4146"), &sign).is_err());
+            let n = vk.curve().get_ref_n();
+            assert!(vk
+                .verify_digest(&digest, &(sign.0.clone(), n.clone()))
+                .is_err());
+        }
+
+        // the thread_rng wrappers sign the same way
+        let key = SigningKey::random(curves::dstu4145(163).unwrap());
+        let digest = sha256(b"dstu 4145");
+        let sign = key.sign_digest(&digest).unwrap();
+        assert!(key.verifying_key().verify_digest(&digest, &sign).is_ok());
+    }
+
+    #[test]
+    fn test_hash_to_field() {
+        let curve = curves::dstu4145(163).unwrap();
+        // only the low 163 bits count, and an all-zero value becomes 1
+        let mut digest = [0u8; 32];
+        digest[0] = 0xff;
+        assert!(hash_to_field(&curve, &digest).is_one());
+        digest[31] = 2;
+        assert_eq!(hash_to_field(&curve, &digest), bui::from(2));
+    }
+}
@@ -1,10 +1,13 @@
 #![allow(unused)]
 
-use crate::bigint_utils::FromHex;
+use crate::bigint_utils::{modulo, FromHex};
+use crate::glv;
 use num_bigint::BigInt as bui;
 use num_bigint::RandBigInt;
+use num_bigint::Sign;
 use num_integer::Integer;
 use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
 use std::{convert, str::FromStr};
 #[derive(Clone, Debug)]
 pub struct Point {
@@ -31,13 +34,17 @@ impl Point {
         }
     }
 
-    pub fn cmp(&self, p: &Point) -> bool {
+    pub fn same_coords(&self, p: &Point) -> bool {
         if self.x == p.x && self.y == p.y && self.z == p.z {
             return true;
         }
         false
     }
 
+    pub fn is_infinity(&self) -> bool {
+        matches!(&self.z, Some(z) if z.is_zero())
+    }
+
     pub fn get_xy(&self, ec: &EC) -> (bui, bui) {
         match self.z.as_ref() {
             Some(z) => {
@@ -57,6 +64,14 @@ impl EC {
         Self { a, b, q, n, p }
     }
 
+    pub fn get_ref_a(&self) -> &bui {
+        &self.a
+    }
+
+    pub fn get_ref_b(&self) -> &bui {
+        &self.b
+    }
+
     pub fn get_ref_q(&self) -> &bui {
         &self.q
     }
@@ -101,7 +116,7 @@ impl EC {
             (Some(x), Some(y), Some(z)) => {
                 let o_e = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
 
-                if p.cmp(&o_e) {
+                if p.same_coords(&o_e) {
                     return Ok(o_e);
                 }
 
@@ -131,16 +146,16 @@ impl EC {
         match (&p1.z, &p2.z) {
             (Some(z1), Some(z2)) => {
                 let o_e = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
-                if p1.cmp(&o_e) {
+                if p1.same_coords(&o_e) {
                     // println!("p2: {:?}", p2);
                     return Ok(p2.clone());
-                } else if p2.cmp(&o_e) {
+                } else if p2.same_coords(&o_e) {
                     return Ok(p1.clone());
                 }
-                let u1 = p2.y.as_ref().unwrap() * z1;
-                let u2 = p1.y.as_ref().unwrap() * z2;
-                let v1 = p2.x.as_ref().unwrap() * z1;
-                let v2 = p1.x.as_ref().unwrap() * z2;
+                let u1 = modulo(&(p2.y.as_ref().unwrap() * z1), &self.q);
+                let u2 = modulo(&(p1.y.as_ref().unwrap() * z2), &self.q);
+                let v1 = modulo(&(p2.x.as_ref().unwrap() * z1), &self.q);
+                let v2 = modulo(&(p1.x.as_ref().unwrap() * z2), &self.q);
                 if v1 == v2 {
                     if u1 != u2 {
                         return Ok(o_e);
@@ -175,7 +190,157 @@ impl EC {
         }
     }
 
+    // Full public key validation (SEC 1, 3.2.2.1): Q != O, affine coordinates
+    // in [0, q), Q on the curve and nQ = O
+    pub fn validate_public_key(&self, p: &Point) -> Result<(), String> {
+        if p.is_infinity() {
+            return Err(String::from("Public key is the point at infinity"));
+        }
+        let (x, y) = match &p.z {
+            Some(z) if !z.is_one() => p.get_xy(self),
+            _ => (
+                p.x.clone().ok_or("point without x")?,
+                p.y.clone().ok_or("point without y")?,
+            ),
+        };
+        if x.sign() == Sign::Minus || x >= self.q || y.sign() == Sign::Minus || y >= self.q {
+            return Err(String::from("Public key coordinates are out of range"));
+        }
+        let affine = Point::new(x, y, None);
+        if !self.on_curve(&affine) {
+            return Err(String::from("Public key is not on the curve"));
+        }
+        let p = self.convert(&affine)?;
+        // the ladder: GLV reduces the scalar mod n first and would always
+        // give O here
+        if !self.ladder(&p, self.get_ref_n()).is_infinity() {
+            return Err(String::from("Public key is not in the subgroup of order n"));
+        }
+        Ok(())
+    }
+
+    // Same a, b, q, n and base point
+    pub fn same_curve(&self, other: &EC) -> bool {
+        self.a == other.a
+            && self.b == other.b
+            && self.q == other.q
+            && self.n == other.n
+            && self.p.as_ref().map(|p| p.get_xy(self)) == other.p.as_ref().map(|p| p.get_xy(other))
+    }
+
+    pub fn field_len(&self) -> usize {
+        self.q.bits().div_ceil(8) as usize
+    }
+
+    // SEC 1, 2.3.3: 04 || X || Y, or 02/03 || X with the parity of Y
+    pub fn encode_point(&self, p: &Point, compressed: bool) -> Vec<u8> {
+        if p.is_infinity() {
+            return vec![0];
+        }
+        let (x, y) = p.get_xy(self);
+        let len = self.field_len();
+
+        if compressed {
+            let mut out = vec![if y.is_odd() { 3 } else { 2 }];
+            out.extend(to_fixed_be(&x, len));
+            out
+        } else {
+            let mut out = vec![4];
+            out.extend(to_fixed_be(&x, len));
+            out.extend(to_fixed_be(&y, len));
+            out
+        }
+    }
+
+    // SEC 1, 2.3.4; the point at infinity is rejected
+    pub fn decode_point(&self, bytes: &[u8]) -> Result<Point, String> {
+        let len = self.field_len();
+        let coordinate = |b: &[u8]| {
+            let v = bui::from_bytes_be(Sign::Plus, b);
+            if v >= self.q {
+                Err(String::from("Coordinate is out of range"))
+            } else {
+                Ok(v)
+            }
+        };
+
+        match bytes.first() {
+            Some(4) if bytes.len() == 1 + 2 * len => {
+                let p = Point::new(
+                    coordinate(&bytes[1..=len])?,
+                    coordinate(&bytes[1 + len..])?,
+                    None,
+                );
+                if !self.on_curve(&p) {
+                    return Err(String::from("Point is not on the curve"));
+                }
+                self.convert(&p)
+            }
+            Some(&prefix @ (2 | 3)) if bytes.len() == 1 + len => self
+                .lift_x(&coordinate(&bytes[1..])?, prefix == 3)
+                .ok_or_else(|| String::from("Point is not on the curve")),
+            _ => Err(String::from("Invalid point encoding")),
+        }
+    }
+
+    pub fn neg(&self, p: &Point) -> Point {
+        if p.is_infinity() {
+            return p.clone();
+        }
+        let y = p.y.as_ref().expect("point without y");
+        Point {
+            x: p.x.clone(),
+            y: Some(modulo(&-y, &self.q)),
+            z: p.z.clone(),
+        }
+    }
+
+    // Point with the given x-coordinate and y parity, if x is on the curve
+    pub fn lift_x(&self, x: &bui, odd: bool) -> Option<Point> {
+        if x.sign() == Sign::Minus || x >= &self.q {
+            return None;
+        }
+        let alpha = modulo(&(x.pow(3) + &self.a * x + &self.b), &self.q);
+        let y = if alpha.is_zero() {
+            alpha
+        } else if legendre_symbol(&alpha, &self.q) == bui::one() {
+            solve(&alpha, &self.q)
+        } else {
+            return None;
+        };
+        let y = if y.is_odd() == odd {
+            y
+        } else {
+            modulo(&-y, &self.q)
+        };
+        Some(Point::new(x.clone(), y, Some(bui::one())))
+    }
+
+    // k P for a secret k, one addition and one doubling per bit: on
+    // secp256k1 over the two halves of the GLV split, elsewhere the ladder.
+    // P must be on the curve; keys are validated when they are built.
     pub fn scalar_mul(&self, p: &Point, k: &bui) -> Point {
+        if let Some(glv) = glv::for_curve(self) {
+            if !p.is_infinity() {
+                return glv.scalar_mul_regular(self, p, k);
+            }
+        }
+        self.ladder(p, k)
+    }
+
+    // k P for a public k, such as in verification. On secp256k1 this goes
+    // through GLV with wNAFs, whose running time depends on k
+    pub fn scalar_mul_vartime(&self, p: &Point, k: &bui) -> Point {
+        if let Some(glv) = glv::for_curve(self) {
+            if !p.is_infinity() {
+                return glv.scalar_mul(self, p, k);
+            }
+        }
+        self.ladder(p, k)
+    }
+
+    // Montgomery ladder over the bits of k, for any curve
+    pub fn ladder(&self, p: &Point, k: &bui) -> Point {
         let mut r_0 = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
         let mut r_1 = p.clone();
         let bitstring = format!("{:b}", k);
@@ -193,7 +358,58 @@ impl EC {
         r_0
     }
 
+    // sum(k_i * P_i) for public k_i. On secp256k1 every term is split into
+    // two half-length ones through the GLV endomorphism first.
+    pub fn multi_scalar_mul(&self, terms: &[(Point, bui)]) -> Point {
+        if let Some(glv) = glv::for_curve(self) {
+            if terms.iter().all(|(p, _)| !p.is_infinity()) {
+                return self.straus(&glv.split(self, terms));
+            }
+        }
+        self.straus(terms)
+    }
+
+    // Straus' interleaving with 4-bit fixed windows, sharing one chain of
+    // doublings between all terms
+    fn straus(&self, terms: &[(Point, bui)]) -> Point {
+        const W: usize = 4;
+        let o_e = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+
+        let tables: Vec<Vec<Point>> = terms
+            .iter()
+            .map(|(p, _)| {
+                let mut table = vec![o_e.clone(), p.clone()];
+                for i in 2..(1 << W) {
+                    let next = self.add(&table[i - 1], p).unwrap();
+                    table.push(next);
+                }
+                table
+            })
+            .collect();
+        let digits: Vec<Vec<u8>> = terms.iter().map(|(_, k)| k.to_radix_be(16).1).collect();
+        let len = digits.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut acc = o_e;
+        for i in 0..len {
+            for _ in 0..W {
+                acc = self.double(&acc).unwrap();
+            }
+            for (table, digits) in tables.iter().zip(&digits) {
+                let offset = len - digits.len();
+                if i >= offset && digits[i - offset] != 0 {
+                    acc = self.add(&acc, &table[digits[i - offset] as usize]).unwrap();
+                }
+            }
+        }
+
+        acc
+    }
+
     pub fn gen_point_p256() -> (Point, EC) {
+        Self::gen_point_p256_with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn gen_point_p256_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> (Point, EC) {
         let ec = EC::new(
             bui::from_str(
                 "115792089210356248762697446949407573530086143415290314195533631308867097853948",
@@ -214,7 +430,6 @@ impl EC {
             None,
         );
 
-        let mut rng = rand::thread_rng();
         loop {
             let x_0 = rng.gen_bigint_range(&bui::one(), &ec.q);
             let x = (x_0.pow(3) + &ec.a * &x_0 + &ec.b) % &ec.q;
@@ -227,8 +442,21 @@ impl EC {
     }
 }
 
-fn modulo(a: &bui, n: &bui) -> bui {
-    ((a % n) + n) % n
+// v as exactly len big-endian bytes, usually len = field_len(); v must fit
+pub fn to_fixed_be(v: &bui, len: usize) -> Vec<u8> {
+    let bytes = v.to_bytes_be().1;
+    let mut out = vec![0u8; len - bytes.len()];
+    out.extend(bytes);
+    out
+}
+
+// Square root of a modulo the odd prime p, if a is a quadratic residue
+pub(crate) fn sqrt_mod(a: &bui, p: &bui) -> Option<bui> {
+    let a = modulo(a, p);
+    if a.is_zero() {
+        return Some(a);
+    }
+    (legendre_symbol(&a, p) == bui::one()).then(|| solve(&a, p))
 }
 
 use num_bigint::ToBigInt;
@@ -255,13 +483,11 @@ fn solve(x: &bui, p: &bui) -> bui {
         return x.modpow(&((p + 1) >> 2), p);
     }
 
-    let mut rng = rand::thread_rng();
-    let z = loop {
-        let k: bui = rng.gen_bigint_range(&bui::one(), &q);
-        if legendre_symbol(&k, p) == -1.to_bigint().unwrap() {
-            break k;
-        }
-    };
+    // the smallest non-residue keeps the root deterministic
+    let mut z = bui::from(2);
+    while legendre_symbol(&z, p) != -1.to_bigint().unwrap() {
+        z += 1;
+    }
 
     let mut c = z.modpow(&q, p);
 
@@ -279,13 +505,13 @@ fn solve(x: &bui, p: &bui) -> bui {
         let mut i = 0;
 
         let i = loop {
-            if t.modpow(&(2 >> i).to_bigint().unwrap(), p) == bui::one() {
+            if t.modpow(&(bui::one() << i), p) == bui::one() {
                 break i;
             }
             i += 1;
         };
 
-        let b = c.modpow(&((2 >> (m - i - 1)).to_bigint().unwrap()), p);
+        let b = c.modpow(&(bui::one() << (m - i - 1)), p);
 
         r = (r * &b) % p;
         t = (t * b.pow(2)) % p;
@@ -298,13 +524,15 @@ fn solve(x: &bui, p: &bui) -> bui {
 mod tests {
     use super::*;
     use num_bigint::BigInt as bui;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
 
     #[test]
     fn test_point_cmp_equal() {
         let (p1, _) = EC::gen_point_p256();
         let p2 = p1.clone();
 
-        assert!(p1.cmp(&p2));
+        assert!(p1.same_coords(&p2));
     }
 
     #[test]
@@ -333,7 +561,7 @@ mod tests {
 
             let result_add = ec.add(&p1, &p1).unwrap();
             let result_double = ec.double(&p1).unwrap();
-            assert!(result_add.cmp(&result_double));
+            assert!(result_add.same_coords(&result_double));
         }
     }
 
@@ -346,7 +574,75 @@ mod tests {
                     .unwrap();
             let o_e = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
             let res = ec.scalar_mul(&p, ec.n.as_ref().unwrap());
-            assert!(res.cmp(&o_e));
+            assert!(res.same_coords(&o_e));
         }
     }
+
+    #[test]
+    fn test_ec_add_same_point_other_z() {
+        for _ in 0..100 {
+            let (p, ec) = EC::gen_point_p256();
+            let two = bui::from(2);
+            let p2 = Point::new(
+                p.x.as_ref().unwrap() * &two,
+                p.y.as_ref().unwrap() * &two,
+                Some(two),
+            );
+
+            let result_add = ec.add(&p, &p2).unwrap();
+            let result_double = ec.double(&p).unwrap();
+            assert_eq!(result_add.get_xy(&ec), result_double.get_xy(&ec));
+        }
+    }
+
+    #[test]
+    fn test_multi_scalar_mul() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let (p1, ec) = EC::gen_point_p256();
+            let (p2, _) = EC::gen_point_p256();
+            let k1 = rng.gen_bigint_range(&bui::zero(), ec.get_ref_n());
+            let k2 = rng.gen_bigint_range(&bui::zero(), ec.get_ref_n());
+
+            let expected = ec
+                .add(&ec.scalar_mul(&p1, &k1), &ec.scalar_mul(&p2, &k2))
+                .unwrap();
+            let result = ec.multi_scalar_mul(&[(p1, k1), (p2, k2)]);
+            assert_eq!(result.get_xy(&ec), expected.get_xy(&ec));
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_point() {
+        let ec = crate::curves::p256();
+        let p = ec.scalar_mul(ec.get_ref_p(), &bui::from(12345));
+        for compressed in [false, true] {
+            let bytes = ec.encode_point(&p, compressed);
+            assert_eq!(bytes.len(), if compressed { 33 } else { 65 });
+            let q = ec.decode_point(&bytes).unwrap();
+            assert_eq!(q.get_xy(&ec), p.get_xy(&ec));
+        }
+
+        let mut bad = ec.encode_point(&p, false);
+        bad[64] ^= 1;
+        assert!(ec.decode_point(&bad).is_err());
+        assert!(ec.decode_point(&bad[..64]).is_err());
+        assert!(ec.decode_point(&[0]).is_err());
+    }
+
+    #[test]
+    fn test_gen_point_p256_seeded() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let (p, ec) = EC::gen_point_p256_with_rng(&mut rng);
+        assert!(ec.on_curve(&p));
+        assert_eq!(
+            p.get_xy(&ec),
+            (
+                bui::from_hex("6e5444e5790776a78bf5be960a7b073e272b497da1b770c69e6360455044379b")
+                    .unwrap(),
+                bui::from_hex("018321d347ec2ebe75cb6295a8609098828aa48bf66732e80731cc824431a469")
+                    .unwrap()
+            )
+        );
+    }
 }
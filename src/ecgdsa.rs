@@ -0,0 +1,235 @@
+// EC-GDSA (BSI TR-03111, 4.2.1.3; ISO/IEC 14888-3:2018, 6.6). Like
+// EC-KCDSA the public key is Y = x^-1 G. With h the leftmost bits(n) bits
+// of the digest, r = x(k G) mod n and s = (k r - h) x mod n, so the signer
+// never inverts k; the verifier checks r against x(r^-1 h G + r^-1 s Y).
+
+use std::marker::PhantomData;
+
+use crate::bigint_utils::{mod_inverse, modulo, retry_with_nonce, Wipe};
+use crate::ec::{Point, EC};
+use crate::keygen::{gen_key_pair_with_rng, KeyGenMethod};
+use crate::sign_ecdsa::digest_to_int;
+use crate::signature::{Signer, Verifier};
+
+use num_bigint::BigInt as bui;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+pub struct SigningKey<D: Digest = Sha256> {
+    x: bui,
+    verifying_key: VerifyingKey<D>,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey<D: Digest = Sha256> {
+    ec: EC,
+    y: Point,
+    hash: PhantomData<D>,
+}
+
+impl<D: Digest> SigningKey<D> {
+    // Y = x^-1 G, so a key pair (d, d G) from keygen gives x = d^-1 and Y = d G
+    pub fn random_with_rng(ec: EC, rng: &mut (impl RngCore + CryptoRng)) -> SigningKey<D> {
+        let (mut d, q) = gen_key_pair_with_rng(&ec, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+        let x = mod_inverse(&d, ec.get_ref_n()).unwrap();
+        d.wipe();
+        let (y_x, y_y) = q.get_xy(&ec);
+        let y = Point::new(y_x, y_y, Some(bui::one()));
+
+        Self {
+            x,
+            verifying_key: VerifyingKey {
+                ec,
+                y,
+                hash: PhantomData,
+            },
+        }
+    }
+
+    pub fn from_scalar(ec: EC, x: bui) -> Result<SigningKey<D>, String> {
+        let n = ec.get_ref_n();
+        if x <= bui::zero() || &x >= n {
+            return Err(String::from("Private key must be in [1, n - 1]"));
+        }
+        let x_inv = mod_inverse(&x, n).ok_or_else(|| String::from("x is not invertible mod n"))?;
+        let (y_x, y_y) = ec.scalar_mul(ec.get_ref_p(), &x_inv).get_xy(&ec);
+        let y = Point::new(y_x, y_y, Some(bui::one()));
+
+        Ok(Self {
+            x,
+            verifying_key: VerifyingKey {
+                ec,
+                y,
+                hash: PhantomData,
+            },
+        })
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey<D> {
+        &self.verifying_key
+    }
+
+    pub fn sign_with_rng(
+        &self,
+        m: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        let n = self.verifying_key.ec.get_ref_n();
+        Ok(retry_with_nonce(rng, n, |k| self.sign_with_k(m, k)))
+    }
+
+    // r = x(k G) mod n, s = x (k r - h) mod n; fails when r = 0 or s = 0
+    pub(crate) fn sign_with_k(&self, m: &[u8], k: &bui) -> Result<(bui, bui), String> {
+        let ec = &self.verifying_key.ec;
+        let n = ec.get_ref_n();
+        if *k <= bui::zero() || k >= n {
+            return Err(String::from("Nonce must be in [1, n - 1]"));
+        }
+        let h = digest_to_int(&D::digest(m), n);
+
+        let (x_1, _) = ec.scalar_mul(ec.get_ref_p(), k).get_xy(ec);
+        let r = x_1 % n;
+        if r.is_zero() {
+            return Err(String::from("r = 0, another nonce is needed"));
+        }
+        let s = modulo(&((k * &r - h) * &self.x), n);
+        if s.is_zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        Ok((r, s))
+    }
+}
+
+impl<D: Digest> Drop for SigningKey<D> {
+    fn drop(&mut self) {
+        self.x.wipe();
+    }
+}
+
+impl<D: Digest> Signer<(bui, bui)> for SigningKey<D> {
+    fn sign(&self, m: &[u8]) -> Result<(bui, bui), String> {
+        self.sign_with_rng(m, &mut rand::thread_rng())
+    }
+}
+
+impl<D: Digest> VerifyingKey<D> {
+    pub fn from_point(ec: EC, y: Point) -> Result<VerifyingKey<D>, String> {
+        ec.validate_public_key(&y)?;
+        let (y_x, y_y) = y.get_xy(&ec);
+        let y = Point::new(y_x, y_y, Some(bui::one()));
+
+        Ok(Self {
+            ec,
+            y,
+            hash: PhantomData,
+        })
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.y
+    }
+}
+
+impl<D: Digest> Verifier<(bui, bui)> for VerifyingKey<D> {
+    fn verify(&self, m: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        let ec = &self.ec;
+        let n = ec.get_ref_n();
+        let (r, s) = sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
+        let h = digest_to_int(&D::digest(m), n);
+        let w = mod_inverse(r, n).ok_or_else(|| String::from("r is not invertible mod n"))?;
+        let u_1 = (&h * &w) % n;
+        let u_2 = (s * &w) % n;
+        let point = ec.multi_scalar_mul(&[(ec.get_ref_p().clone(), u_1), (self.y.clone(), u_2)]);
+        if point.is_infinity() {
+            return Err(String::from("u1 G + u2 Y is the point at infinity"));
+        }
+        let (x_1, _) = point.get_xy(ec);
+        if x_1 % n == *r {
+            Ok(())
+        } else {
+            Err(String::from("Signature does not match"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::FromHex;
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
+    use sha2::{Sha224, Sha384};
+
+    fn check_example<D: Digest>(ec: EC, x: &str, m: &[u8], k: &str, sign: &str) {
+        let key = SigningKey::<D>::from_scalar(ec, bui::from_hex(x).unwrap()).unwrap();
+        let (r, s) = key.sign_with_k(m, &bui::from_hex(k).unwrap()).unwrap();
+        let (r_hex, s_hex) = sign.split_at(sign.len() / 2);
+        assert_eq!(r, bui::from_hex(r_hex).unwrap());
+        assert_eq!(s, bui::from_hex(s_hex).unwrap());
+        key.verifying_key()
+            .verify(m, &(r.clone(), s.clone()))
+            .unwrap();
+        assert!(key.verifying_key().verify(&m[1..], &(r, s)).is_err());
+    }
+
+    // "The Digital Signature Scheme ECGDSA" (Hess, Schafheutle, Serf), 2.4.3
+    // to 2.4.5, over brainpoolP256r1 and brainpoolP384r1
+    #[test]
+    fn test_examples() {
+        let x_256 = "47B3A27862DEF03749ACF0D600E69F9B851D01EDAEFA531F4D168E787307F4D8";
+        let k_256 = "908E3099776261A4558FF7A9FA6DFFE0CA6BB3F9CB35C2E4E1DC73FD5E8C08A3";
+        check_example::<Sha224>(
+            curves::brainpool_p256r1(),
+            x_256,
+            b"Example of ECGDSA with the hash function SHA-224",
+            k_256,
+            "62CCD1D291E62F6A4FFBD966C66C85AABA990BB6AB0C087DBD54A456CCC84E4C6F029D921CBD25526EDCCF1C45E3CBF7B7A5D8D4E005F0C41C49B052DECB04EA",
+        );
+        check_example::<Sha256>(
+            curves::brainpool_p256r1(),
+            x_256,
+            b"Example of ECGDSA with the hash function SHA-256",
+            k_256,
+            "62CCD1D291E62F6A4FFBD966C66C85AABA990BB6AB0C087DBD54A456CCC84E4C1DD53F822F8BE769F601FC5826B10AB603898374B8501B53D6976BA1AAE17A45",
+        );
+        check_example::<Sha384>(
+            curves::brainpool_p384r1(),
+            "60BABEC49D0A4E36328879591B1A598F339F7971E8A1AD35788486EB081C838B5612F6DEBD6B38A0BA720BD857AB2354",
+            b"Example of ECGDSA with the hash function SHA-384",
+            "43E01A2A95EE7695955334410F32C73BD1394BBF2CD7B8A18656B447A951342C82F52E833FFB3B74612679437C13ACB5",
+            "2A2676EFF87A75EE9ECBA1FDD7A5437697294166063C8CD90F8AEBA399BF450FFA244C0EE69B3E1FFCA395CD27AFFC61733F4E370AF3F9A2DF9499F9953E091D7BD28CA8E80FB3B4AAEB1FF324CCDF6E4D7F6B4576071321D8B34C20CAF0CD01",
+        );
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = StdRng::seed_from_u64(3111);
+        let key = SigningKey::<Sha256>::random_with_rng(curves::p256(), &mut rng);
+        let vk = key.verifying_key();
+        let (r, s) = key.sign_with_rng(b"message", &mut rng).unwrap();
+        vk.verify(b"message", &(r.clone(), s.clone())).unwrap();
+        // a verifier built from Y alone accepts it too
+        let vk_y =
+            VerifyingKey::<Sha256>::from_point(curves::p256(), vk.as_point().clone()).unwrap();
+        vk_y.verify(b"message", &(r.clone(), s.clone())).unwrap();
+        assert!(vk.verify(b"massage", &(r.clone(), s.clone())).is_err());
+        assert!(vk.verify(b"message", &(s.clone(), r.clone())).is_err());
+        assert!(vk.verify(b"message", &(r, bui::zero())).is_err());
+
+        // an ECDSA key pair for the same x does not verify: Y = x^-1 G
+        let ecdsa =
+            crate::sign_ecdsa::SigningKey::from_scalar(curves::p256(), key.x.clone()).unwrap();
+        let other = VerifyingKey::<Sha256>::from_point(
+            curves::p256(),
+            ecdsa.verifying_key().as_point().clone(),
+        )
+        .unwrap();
+        let sign = key.sign_with_rng(b"message", &mut rng).unwrap();
+        assert!(other.verify(b"message", &sign).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+// EC-KCDSA (ISO/IEC 14888-3:2018, 6.7; TTAK.KO-12.0015/R2). The public key
+// is Y = x^-1 G. The message is hashed behind the certificate data
+// z = x_Y || y_Y, cut or zero-padded to the hash block size. With
+// H = h(z || M) and r = h(x(k G)), both keeping only their last
+// ceil(bits(n) / 8) bytes, w = (r xor H) mod n and s = x (k - w) mod n.
+// The verifier recomputes r from s Y + w G = k G.
+
+use std::marker::PhantomData;
+
+use crate::bigint_utils::{mod_inverse, modulo, retry_with_nonce, Wipe};
+use crate::ec::{to_fixed_be, Point, EC};
+use crate::keygen::{gen_key_pair_with_rng, KeyGenMethod};
+use crate::signature::{Signer, Verifier};
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+use sha2::digest::core_api::BlockSizeUser;
+use sha2::{Digest, Sha256};
+
+// The digest as an integer, reduced to its last ceil(bits(n) / 8) bytes
+fn truncate(digest: &[u8], n: &bui) -> bui {
+    let len = (n.bits() as usize).div_ceil(8);
+    bui::from_bytes_be(Sign::Plus, &digest[digest.len().saturating_sub(len)..])
+}
+
+// Byte length of r: the hash length, cut as in truncate
+fn r_len<D: Digest>(n: &bui) -> usize {
+    <D as Digest>::output_size().min((n.bits() as usize).div_ceil(8))
+}
+
+pub struct SigningKey<D: Digest + BlockSizeUser = Sha256> {
+    x: bui,
+    verifying_key: VerifyingKey<D>,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey<D: Digest + BlockSizeUser = Sha256> {
+    ec: EC,
+    y: Point,
+    hash: PhantomData<D>,
+}
+
+impl<D: Digest + BlockSizeUser> SigningKey<D> {
+    // Y = x^-1 G, so a key pair (d, d G) from keygen gives x = d^-1 and Y = d G
+    pub fn random_with_rng(ec: EC, rng: &mut (impl RngCore + CryptoRng)) -> SigningKey<D> {
+        let (mut d, q) = gen_key_pair_with_rng(&ec, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+        let x = mod_inverse(&d, ec.get_ref_n()).unwrap();
+        d.wipe();
+        let (y_x, y_y) = q.get_xy(&ec);
+        let y = Point::new(y_x, y_y, Some(bui::one()));
+
+        Self {
+            x,
+            verifying_key: VerifyingKey {
+                ec,
+                y,
+                hash: PhantomData,
+            },
+        }
+    }
+
+    pub fn from_scalar(ec: EC, x: bui) -> Result<SigningKey<D>, String> {
+        let n = ec.get_ref_n();
+        if x <= bui::zero() || &x >= n {
+            return Err(String::from("Private key must be in [1, n - 1]"));
+        }
+        let x_inv = mod_inverse(&x, n).ok_or_else(|| String::from("x is not invertible mod n"))?;
+        let (y_x, y_y) = ec.scalar_mul(ec.get_ref_p(), &x_inv).get_xy(&ec);
+        let y = Point::new(y_x, y_y, Some(bui::one()));
+
+        Ok(Self {
+            x,
+            verifying_key: VerifyingKey {
+                ec,
+                y,
+                hash: PhantomData,
+            },
+        })
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey<D> {
+        &self.verifying_key
+    }
+
+    pub fn sign_with_rng(
+        &self,
+        m: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        let n = self.verifying_key.ec.get_ref_n();
+        Ok(retry_with_nonce(rng, n, |k| self.sign_with_k(m, k)))
+    }
+
+    // r = H(x(k G)), s = x (k - (r xor h)) mod n; r is a hash and may be 0, so
+    // only s = 0 fails
+    pub(crate) fn sign_with_k(&self, m: &[u8], k: &bui) -> Result<(bui, bui), String> {
+        let vk = &self.verifying_key;
+        let ec = &vk.ec;
+        let n = ec.get_ref_n();
+        if *k <= bui::zero() || k >= n {
+            return Err(String::from("Nonce must be in [1, n - 1]"));
+        }
+        let h = vk.digest(m);
+
+        let (w_x, _) = ec.scalar_mul(ec.get_ref_p(), k).get_xy(ec);
+        let r = truncate(&D::digest(to_fixed_be(&w_x, ec.field_len())), n);
+        let w = (&r ^ h) % n;
+        let s = modulo(&(&self.x * (k - w)), n);
+        if s.is_zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        Ok((r, s))
+    }
+}
+
+impl<D: Digest + BlockSizeUser> Drop for SigningKey<D> {
+    fn drop(&mut self) {
+        self.x.wipe();
+    }
+}
+
+impl<D: Digest + BlockSizeUser> Signer<(bui, bui)> for SigningKey<D> {
+    fn sign(&self, m: &[u8]) -> Result<(bui, bui), String> {
+        self.sign_with_rng(m, &mut rand::thread_rng())
+    }
+}
+
+impl<D: Digest + BlockSizeUser> VerifyingKey<D> {
+    pub fn from_point(ec: EC, y: Point) -> Result<VerifyingKey<D>, String> {
+        ec.validate_public_key(&y)?;
+        let (y_x, y_y) = y.get_xy(&ec);
+        let y = Point::new(y_x, y_y, Some(bui::one()));
+
+        Ok(Self {
+            ec,
+            y,
+            hash: PhantomData,
+        })
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.y
+    }
+
+    pub fn curve(&self) -> &EC {
+        &self.ec
+    }
+
+    // z = x_Y || y_Y, cut or zero-padded to one hash block
+    pub fn cert_data(&self) -> Vec<u8> {
+        let len = self.ec.field_len();
+        let (y_x, y_y) = self.y.get_xy(&self.ec);
+        let mut z = to_fixed_be(&y_x, len);
+        z.extend(to_fixed_be(&y_y, len));
+        z.resize(D::block_size(), 0);
+        z
+    }
+
+    // H = h(z || M), truncated
+    fn digest(&self, m: &[u8]) -> bui {
+        let mut hasher = D::new();
+        hasher.update(self.cert_data());
+        hasher.update(m);
+        truncate(&hasher.finalize(), self.ec.get_ref_n())
+    }
+}
+
+impl<D: Digest + BlockSizeUser> Verifier<(bui, bui)> for VerifyingKey<D> {
+    fn verify(&self, m: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        let ec = &self.ec;
+        let n = ec.get_ref_n();
+        let (r, s) = sign;
+        if r.sign() == Sign::Minus
+            || r.bits() > 8 * r_len::<D>(n) as u64
+            || *s <= bui::zero()
+            || s >= n
+        {
+            return Err(String::from("Signature is out of range"));
+        }
+        let w = (r ^ self.digest(m)) % n;
+        let point =
+            ec.multi_scalar_mul(&[(self.y.clone(), s.clone()), (ec.get_ref_p().clone(), w)]);
+        if point.is_infinity() {
+            return Err(String::from("s Y + w G is the point at infinity"));
+        }
+        let (w_x, _) = point.get_xy(ec);
+        if truncate(&D::digest(to_fixed_be(&w_x, ec.field_len())), n) == *r {
+            Ok(())
+        } else {
+            Err(String::from("Signature does not match"))
+        }
+    }
+}
+
+// r || s, r as long as the truncated hash and s as long as n
+pub fn encode_signature<D: Digest>(ec: &EC, sign: &(bui, bui)) -> Vec<u8> {
+    let n = ec.get_ref_n();
+    let mut out = to_fixed_be(&sign.0, r_len::<D>(n));
+    out.extend(to_fixed_be(&sign.1, (n.bits() as usize).div_ceil(8)));
+    out
+}
+
+pub fn decode_signature<D: Digest>(ec: &EC, bytes: &[u8]) -> Result<(bui, bui), String> {
+    let n = ec.get_ref_n();
+    let len = r_len::<D>(n);
+    if bytes.len() != len + (n.bits() as usize).div_ceil(8) {
+        return Err(String::from("Invalid signature length"));
+    }
+    let (r, s) = bytes.split_at(len);
+    Ok((
+        bui::from_bytes_be(Sign::Plus, r),
+        bui::from_bytes_be(Sign::Plus, s),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::FromHex;
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
+    use sha2::{Sha224, Sha512};
+
+    const MSG: &[u8] = b"This is a sample message for EC-KCDSA implementation validation.";
+
+    fn check_example<D: Digest + BlockSizeUser>(ec: EC, x: &str, m: &[u8], k: &str, sign: &str) {
+        let key = SigningKey::<D>::from_scalar(ec.clone(), bui::from_hex(x).unwrap()).unwrap();
+        let result = key.sign_with_k(m, &bui::from_hex(k).unwrap()).unwrap();
+        let encoded = encode_signature::<D>(&ec, &result);
+        assert_eq!(hex::encode_upper(&encoded), sign);
+        let decoded = decode_signature::<D>(&ec, &encoded).unwrap();
+        key.verifying_key().verify(m, &decoded).unwrap();
+        assert!(key.verifying_key().verify(&m[1..], &decoded).is_err());
+    }
+
+    // ISO/IEC 14888-3:2018, F.7.1, F.7.2 and F.7.7; the last has a hash
+    // longer than n
+    #[test]
+    fn test_examples() {
+        check_example::<Sha224>(
+            curves::p224(),
+            "562A6F64E162FFCB51CD4707774AE36681B6CEF205FE5D43912956A2",
+            MSG,
+            "76A0AFC18646D1B620A079FB223865A7BCB447F3C03A35D878EA4CDA",
+            "EEA58C91E0CDCEB5799B00D2412D928FDD23122A1C2BDF43C2F8DAFAAEBAB53C7A44A8B22F35FDB9DE265F23B89F65A69A8B7BD4061911A6",
+        );
+        check_example::<Sha256>(
+            curves::p256(),
+            "9051A275AA4D98439EDDED13FA1C6CBBCCE775D8CC9433DEE69C59848B3594DF",
+            MSG,
+            "71B88F398916DA9C90F555F1B5732B7DC636B49C638150BAC11BF05CFE16596A",
+            "0EDDF680601266EE1DA83E55A6D9445FC781DAEB14C765E7E5D0CDBAF1F14A689B333457661C7CF741BDDBC0835553DFBB37EE74F53DB699E0A17780C7B6F1D0",
+        );
+        check_example::<Sha256>(
+            curves::p224(),
+            "61585827449DBC0EC161B2CF8575C9DF149F41DD0289BE4FF110773D",
+            MSG,
+            "EEC79D8D4648DF3A832A66E3775537E000CC9B957E1319C5DB9DD4F7",
+            "64B49E977E6534F877CB68A3806F6A989311CEAA8A64A0558077C04BAFF23D40B177951151BE32F6561B1B739E3E8F822CC52D4CB3909A93",
+        );
+    }
+
+    // libecc's vectors: with SHA-512 the 64-byte certificate data is padded
+    // to a 128-byte block
+    #[test]
+    fn test_libecc_examples() {
+        check_example::<Sha512>(
+            curves::p256(),
+            "DC51D3866A15BACDE33D96F992FCA99DA7E6EF0934E7097559C27F1614C88A7F",
+            b"abc",
+            "9E56F509196784D963D1C0A401510EE7ADA3DCC5DEE04B154BF61AF1D5A6DECE",
+            "70021D6D5A5E99594318A3BA8D27A539A573768073C84DA0DBCCF9249AFE7854B69728E17B9E85B030058F2854BF34D1DD1DF67A026423769EB4FD0F4C2E156B",
+        );
+        check_example::<Sha256>(
+            curves::brainpool_p256r1(),
+            "A1456788204E69BAF38488CAE77763CADEE5D0D91AA0E5E60D19B00D3A67ED48",
+            b"brainpoolP256r1",
+            "098A5B7CFA317B796AF446C40E3EB5287903422C56624978D902C59B7A92FE9B",
+            "ECC82E85669005AF077C67DEBAEA8EBB332614E661EFC7FFD1F452E1C44223CD2F53B09DDF97A82242552965E1A209278BE14DDE16FD54BC16F1F09D28E7FD11",
+        );
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = StdRng::seed_from_u64(14888);
+        let key = SigningKey::<Sha256>::random_with_rng(curves::p256(), &mut rng);
+        let vk = key.verifying_key();
+        let (r, s) = key.sign_with_rng(b"message", &mut rng).unwrap();
+        vk.verify(b"message", &(r.clone(), s.clone())).unwrap();
+        // a verifier built from Y alone accepts it too
+        let vk_y =
+            VerifyingKey::<Sha256>::from_point(curves::p256(), vk.as_point().clone()).unwrap();
+        vk_y.verify(b"message", &(r.clone(), s.clone())).unwrap();
+        assert!(vk.verify(b"massage", &(r.clone(), s.clone())).is_err());
+        assert!(vk
+            .verify(b"message", &(&r ^ bui::one(), s.clone()))
+            .is_err());
+        assert!(vk
+            .verify(b"message", &(r.clone(), vk.ec.get_ref_n() - &s))
+            .is_err());
+        assert!(vk
+            .verify(b"message", &(r + (bui::one() << 256), s))
+            .is_err());
+
+        // Y is the inverse-key point, not x G
+        let x = bui::from(2);
+        let key = SigningKey::<Sha256>::from_scalar(curves::p256(), x.clone()).unwrap();
+        let ec = key.verifying_key().curve();
+        let y = key.verifying_key().as_point();
+        assert_eq!(ec.scalar_mul(y, &x).get_xy(ec), ec.get_ref_p().get_xy(ec));
+        assert!(decode_signature::<Sha256>(ec, &[0; 63]).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+// RFC 8032 Ed25519 with its Ed25519ctx and Ed25519ph variants over
+// Edwards25519. Signing is deterministic. Verification is either cofactorless,
+// [S]B = R + [k]A, or cofactored, [8][S]B = [8]R + [8][k]A; the two disagree
+// on signatures whose R or A carries a small-order component, so every
+// verifying key is built with the equation it enforces.
+
+use crate::bigint_utils::Wipe;
+use crate::curves;
+use crate::edwards::{EdPoint, Edwards};
+use crate::signature::{Signer, Verifier};
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant<'a> {
+    Ed25519,
+    // a context of 1 to 255 bytes
+    Ed25519ctx(&'a [u8]),
+    // SHA-512 of the message is signed, with a context of up to 255 bytes
+    Ed25519ph(&'a [u8]),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verification {
+    Cofactorless,
+    Cofactored,
+}
+
+pub struct SigningKey {
+    seed: [u8; 32],
+    // the clamped scalar s and the nonce prefix, both from SHA-512(seed)
+    s: bui,
+    prefix: [u8; 32],
+    verifying_key: VerifyingKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
+    ed: Edwards,
+    a: EdPoint,
+    bytes: [u8; 32],
+    verification: Verification,
+}
+
+fn int_le(bytes: &[u8]) -> bui {
+    bui::from_bytes_le(Sign::Plus, bytes)
+}
+
+fn bytes32_le(v: &bui) -> [u8; 32] {
+    let digits = v.to_bytes_le().1;
+    let mut out = [0u8; 32];
+    out[..digits.len()].copy_from_slice(&digits);
+    out
+}
+
+impl Variant<'_> {
+    // dom2(phflag, context); empty for plain Ed25519
+    fn dom2(&self) -> Result<Vec<u8>, String> {
+        let (flag, context) = match *self {
+            Variant::Ed25519 => return Ok(Vec::new()),
+            Variant::Ed25519ctx([]) => {
+                return Err(String::from("Ed25519ctx needs a non-empty context"))
+            }
+            Variant::Ed25519ctx(context) => (0, context),
+            Variant::Ed25519ph(context) => (1, context),
+        };
+        if context.len() > 255 {
+            return Err(String::from("Context is longer than 255 bytes"));
+        }
+        let mut dom = b"SigEd25519 no Ed25519 collisions".to_vec();
+        dom.push(flag);
+        dom.push(context.len() as u8);
+        dom.extend_from_slice(context);
+        Ok(dom)
+    }
+
+    fn prehash(&self, m: &[u8]) -> Vec<u8> {
+        match self {
+            Variant::Ed25519ph(_) => Sha512::digest(m).to_vec(),
+            _ => m.to_vec(),
+        }
+    }
+}
+
+// SHA-512(parts) as a little-endian integer mod L
+fn hash_mod_l(ed: &Edwards, parts: &[&[u8]]) -> bui {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    int_le(&hasher.finalize()) % ed.get_ref_n()
+}
+
+impl SigningKey {
+    pub fn random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> SigningKey {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let key = Self::from_bytes(&seed).unwrap();
+        seed.fill(0);
+        key
+    }
+
+    // RFC 8032, 5.1.5: the 32-byte seed is the private key
+    pub fn from_bytes(bytes: &[u8]) -> Result<SigningKey, String> {
+        let seed: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| String::from("Secret key must be 32 bytes"))?;
+        let ed = curves::edwards25519();
+        let mut h: [u8; 64] = Sha512::digest(seed).into();
+        // clear the low three bits and the top bit, set bit 254
+        h[0] &= 0xf8;
+        h[31] &= 0x7f;
+        h[31] |= 0x40;
+        let s = int_le(&h[..32]);
+        let mut prefix = [0u8; 32];
+        prefix.copy_from_slice(&h[32..]);
+        h.fill(0);
+
+        let a = ed.scalar_mul(ed.get_ref_p(), &s);
+        let bytes: [u8; 32] = ed.encode(&a).try_into().unwrap();
+        Ok(Self {
+            seed,
+            s,
+            prefix,
+            verifying_key: VerifyingKey {
+                ed,
+                a,
+                bytes,
+                verification: Verification::Cofactorless,
+            },
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    // the public key, verifying cofactorless; see VerifyingKey::with_verification
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    // RFC 8032, 5.1.6
+    pub fn sign_with(&self, variant: Variant, m: &[u8]) -> Result<[u8; 64], String> {
+        let ed = &self.verifying_key.ed;
+        let n = ed.get_ref_n();
+        let dom = variant.dom2()?;
+        let m = variant.prehash(m);
+
+        let mut r = hash_mod_l(ed, &[&dom, &self.prefix, &m]);
+        let big_r = ed.encode(&ed.scalar_mul(ed.get_ref_p(), &r));
+        let k = hash_mod_l(ed, &[&dom, &big_r, &self.verifying_key.bytes, &m]);
+        let s = (&r + k * &self.s) % n;
+        r.wipe();
+
+        let mut sign = [0u8; 64];
+        sign[..32].copy_from_slice(&big_r);
+        sign[32..].copy_from_slice(&bytes32_le(&s));
+        Ok(sign)
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.seed.fill(0);
+        self.prefix.fill(0);
+        self.s.wipe();
+    }
+}
+
+impl Signer<[u8; 64]> for SigningKey {
+    fn sign(&self, m: &[u8]) -> Result<[u8; 64], String> {
+        self.sign_with(Variant::Ed25519, m)
+    }
+}
+
+impl VerifyingKey {
+    // Keys decode as in RFC 8032, 5.1.3; small-order keys are not refused
+    // here, since whether they can verify depends on the equation
+    pub fn from_bytes(bytes: &[u8], verification: Verification) -> Result<VerifyingKey, String> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| String::from("Public key must be 32 bytes"))?;
+        let ed = curves::edwards25519();
+        let a = ed.decode(&bytes)?;
+        Ok(Self {
+            ed,
+            a,
+            bytes,
+            verification,
+        })
+    }
+
+    pub fn with_verification(&self, verification: Verification) -> VerifyingKey {
+        Self {
+            verification,
+            ..self.clone()
+        }
+    }
+
+    pub fn verification(&self) -> Verification {
+        self.verification
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.bytes
+    }
+
+    pub fn as_point(&self) -> &EdPoint {
+        &self.a
+    }
+
+    // RFC 8032, 5.1.7
+    pub fn verify_with(&self, variant: Variant, m: &[u8], sign: &[u8; 64]) -> Result<(), String> {
+        let ed = &self.ed;
+        let dom = variant.dom2()?;
+        let m = variant.prehash(m);
+
+        let big_r = ed.decode(&sign[..32])?;
+        let s = int_le(&sign[32..]);
+        if &s >= ed.get_ref_n() {
+            return Err(String::from("S is out of range"));
+        }
+        let k = hash_mod_l(ed, &[&dom, &sign[..32], &self.bytes, &m]);
+
+        // [S]B - R - [k]A is the identity, or only of small order when
+        // cofactored
+        let sb = ed.scalar_mul(ed.get_ref_p(), &s);
+        let rhs = ed.add(&big_r, &ed.scalar_mul(&self.a, &k));
+        let diff = ed.add(&sb, &ed.neg(&rhs));
+        let valid = match self.verification {
+            Verification::Cofactorless => ed.is_identity(&diff),
+            Verification::Cofactored => ed.has_small_order(&diff),
+        };
+        if !valid {
+            return Err(String::from("Signature does not match"));
+        }
+        Ok(())
+    }
+}
+
+impl Verifier<[u8; 64]> for VerifyingKey {
+    fn verify(&self, m: &[u8], sign: &[u8; 64]) -> Result<(), String> {
+        self.verify_with(Variant::Ed25519, m, sign)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::RandBigInt;
+    use num_traits::One;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::fs;
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        let path = format!(
+            "{}/test_vectors/rfc8032/ed25519.csv",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+
+        let mut count = 0;
+        for line in text.lines().skip(1).filter(|l| !l.is_empty()) {
+            let fields: Vec<&str> = line.split(',').collect();
+            let [name, variant, secret, public, m, context, sign] = fields[..] else {
+                panic!("malformed line {line}");
+            };
+            let m = hex::decode(m).unwrap();
+            let context = hex::decode(context).unwrap();
+            let sign: [u8; 64] = hex::decode(sign).unwrap().try_into().unwrap();
+            let variant = match variant {
+                "Ed25519" => Variant::Ed25519,
+                "Ed25519ctx" => Variant::Ed25519ctx(&context),
+                "Ed25519ph" => Variant::Ed25519ph(&context),
+                other => panic!("unknown variant {other}"),
+            };
+
+            let key = SigningKey::from_bytes(&hex::decode(secret).unwrap()).unwrap();
+            assert_eq!(
+                hex::encode(key.verifying_key().to_bytes()),
+                public,
+                "{name}"
+            );
+            assert_eq!(key.sign_with(variant, &m).unwrap(), sign, "{name}");
+
+            for verification in [Verification::Cofactorless, Verification::Cofactored] {
+                let q_a =
+                    VerifyingKey::from_bytes(&hex::decode(public).unwrap(), verification).unwrap();
+                assert!(q_a.verify_with(variant, &m, &sign).is_ok(), "{name}");
+                // the variants are domain separated from each other
+                if variant != Variant::Ed25519 {
+                    assert!(q_a.verify(&m, &sign).is_err(), "{name}");
+                }
+            }
+            count += 1;
+        }
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let key = SigningKey::random_with_rng(&mut StdRng::seed_from_u64(38));
+        let q_a = key.verifying_key();
+        let sign = key.sign(b"ed25519").unwrap();
+        assert!(q_a.verify(b"ed25519", &sign).is_ok());
+        assert!(q_a.verify(b"ed25518", &sign).is_err());
+
+        // the seed round-trips, and A encodes to the public key bytes
+        let same = SigningKey::from_bytes(&key.to_bytes()).unwrap();
+        assert_eq!(same.verifying_key().to_bytes(), q_a.to_bytes());
+        assert_eq!(
+            curves::edwards25519().encode(q_a.as_point()),
+            q_a.to_bytes()
+        );
+
+        // S + L is refused even though it is the same scalar
+        let ed = curves::edwards25519();
+        let mut malleated = sign;
+        let s = int_le(&sign[32..]) + ed.get_ref_n();
+        malleated[32..].copy_from_slice(&bytes32_le(&s));
+        assert!(q_a.verify(b"ed25519", &malleated).is_err());
+
+        assert!(key.sign_with(Variant::Ed25519ctx(&[]), b"m").is_err());
+        assert!(key.sign_with(Variant::Ed25519ph(&[0; 256]), b"m").is_err());
+        let sign = key.sign_with(Variant::Ed25519ph(b""), b"m").unwrap();
+        assert!(q_a
+            .verify_with(Variant::Ed25519ph(b""), b"m", &sign)
+            .is_ok());
+        assert!(q_a
+            .verify_with(Variant::Ed25519ph(b"x"), b"m", &sign)
+            .is_err());
+    }
+
+    // a point of order 8: the torsion part [L] P of some curve point P
+    fn torsion_point(ed: &Edwards) -> EdPoint {
+        let mut rng = StdRng::seed_from_u64(8);
+        loop {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            bytes[31] &= 0x7f;
+            let Ok(p) = ed.decode(&bytes) else {
+                continue;
+            };
+            let t = ed.scalar_mul(&p, ed.get_ref_n());
+            if !ed.is_identity(&ed.double(&ed.double(&t))) {
+                break t;
+            }
+        }
+    }
+
+    #[test]
+    fn test_cofactored_vs_cofactorless() {
+        let ed = curves::edwards25519();
+        let n = ed.get_ref_n();
+        let key = SigningKey::random_with_rng(&mut StdRng::seed_from_u64(39));
+        let m = b"mixed-order R";
+        let t = torsion_point(&ed);
+
+        // R' = [r]B + T, S = r + k' s with k' over R': [S]B differs from
+        // R' + [k']A by -T, which only the cofactored equation ignores
+        let r = StdRng::seed_from_u64(40).gen_bigint_range(&bui::one(), n);
+        let big_r = ed.encode(&ed.add(&ed.scalar_mul(ed.get_ref_p(), &r), &t));
+        let q_a = key.verifying_key();
+        let k = hash_mod_l(&ed, &[&big_r, &q_a.to_bytes(), m]);
+        let s = (r + k * &key.s) % n;
+        let mut sign = [0u8; 64];
+        sign[..32].copy_from_slice(&big_r);
+        sign[32..].copy_from_slice(&bytes32_le(&s));
+
+        let cofactorless = q_a.with_verification(Verification::Cofactorless);
+        let cofactored = q_a.with_verification(Verification::Cofactored);
+        assert_eq!(q_a.verification(), Verification::Cofactorless);
+        assert_eq!(cofactored.verification(), Verification::Cofactored);
+        assert!(cofactorless.verify(m, &sign).is_err());
+        assert!(cofactored.verify(m, &sign).is_ok());
+
+        // a small-order public key with R = identity and S = 0 verifies any
+        // message under the cofactored equation
+        let weak = VerifyingKey::from_bytes(&ed.encode(&t), Verification::Cofactored).unwrap();
+        let mut forged = [0u8; 64];
+        forged[..32].copy_from_slice(&ed.encode(&ed.identity()));
+        assert!(weak.verify(b"anything", &forged).is_ok());
+
+        // honest signatures pass both
+        let sign = key.sign(m).unwrap();
+        assert!(cofactorless.verify(m, &sign).is_ok());
+        assert!(cofactored.verify(m, &sign).is_ok());
+    }
+}
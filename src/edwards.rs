@@ -0,0 +1,358 @@
+// Twisted Edwards curves a x^2 + y^2 = 1 + d x^2 y^2 in extended coordinates
+// (X : Y : Z : T) with x = X / Z, y = Y / Z and x y = T / Z (Hisil, Wong,
+// Carter, Dawson 2008). With a a square and d a non-square the addition law
+// is complete: the same formula handles doubling and the neutral element.
+
+use crate::bigint_utils::modulo;
+use crate::ec::{sqrt_mod, Point, EC};
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+#[derive(Clone, Debug)]
+pub struct EdPoint {
+    x: bui,
+    y: bui,
+    z: bui,
+    t: bui,
+}
+
+#[derive(Clone, Debug)]
+pub struct Edwards {
+    a: bui,
+    d: bui,
+    q: bui,
+    n: bui,     // order of the prime subgroup
+    h: u32,     // cofactor
+    p: EdPoint, // base point
+}
+
+impl Edwards {
+    pub fn new(a: bui, d: bui, q: bui, n: bui, h: u32, g_x: bui, g_y: bui) -> Result<Self, String> {
+        let mut ed = Self {
+            a: modulo(&a, &q),
+            d: modulo(&d, &q),
+            q,
+            n,
+            h,
+            p: EdPoint {
+                x: bui::zero(),
+                y: bui::one(),
+                z: bui::one(),
+                t: bui::zero(),
+            },
+        };
+        ed.p = ed.affine_point(&g_x, &g_y)?;
+        Ok(ed)
+    }
+
+    pub fn get_ref_q(&self) -> &bui {
+        &self.q
+    }
+
+    pub fn get_ref_n(&self) -> &bui {
+        &self.n
+    }
+
+    pub fn get_ref_p(&self) -> &EdPoint {
+        &self.p
+    }
+
+    pub fn cofactor(&self) -> u32 {
+        self.h
+    }
+
+    pub fn identity(&self) -> EdPoint {
+        EdPoint {
+            x: bui::zero(),
+            y: bui::one(),
+            z: bui::one(),
+            t: bui::zero(),
+        }
+    }
+
+    fn inverse(&self, v: &bui) -> bui {
+        v.modpow(&(&self.q - 2u32), &self.q)
+    }
+
+    pub fn affine_point(&self, x: &bui, y: &bui) -> Result<EdPoint, String> {
+        if x.sign() == Sign::Minus || x >= &self.q || y.sign() == Sign::Minus || y >= &self.q {
+            return Err(String::from("Coordinate is out of range"));
+        }
+        let p = EdPoint {
+            x: x.clone(),
+            y: y.clone(),
+            z: bui::one(),
+            t: (x * y) % &self.q,
+        };
+        if !self.on_curve(&p) {
+            return Err(String::from("Point is not on the curve"));
+        }
+        Ok(p)
+    }
+
+    pub fn to_affine(&self, p: &EdPoint) -> (bui, bui) {
+        let z_inv = self.inverse(&p.z);
+        ((&p.x * &z_inv) % &self.q, (&p.y * z_inv) % &self.q)
+    }
+
+    // a X^2 Z^2 + Y^2 Z^2 = Z^4 + d X^2 Y^2, and X Y = T Z
+    pub fn on_curve(&self, p: &EdPoint) -> bool {
+        let q = &self.q;
+        let (x2, y2, z2) = (p.x.pow(2), p.y.pow(2), p.z.pow(2));
+        let left = modulo(&((&self.a * &x2 + &y2) * &z2), q);
+        let right = modulo(&(z2.pow(2) + &self.d * x2 * y2), q);
+        left == right && modulo(&(&p.x * &p.y - &p.t * &p.z), q).is_zero()
+    }
+
+    pub fn is_identity(&self, p: &EdPoint) -> bool {
+        p.x.is_zero() && p.y == p.z
+    }
+
+    // projective equality: X1 Z2 = X2 Z1 and Y1 Z2 = Y2 Z1
+    pub fn eq(&self, p1: &EdPoint, p2: &EdPoint) -> bool {
+        let q = &self.q;
+        modulo(&(&p1.x * &p2.z - &p2.x * &p1.z), q).is_zero()
+            && modulo(&(&p1.y * &p2.z - &p2.y * &p1.z), q).is_zero()
+    }
+
+    pub fn neg(&self, p: &EdPoint) -> EdPoint {
+        EdPoint {
+            x: modulo(&-&p.x, &self.q),
+            y: p.y.clone(),
+            z: p.z.clone(),
+            t: modulo(&-&p.t, &self.q),
+        }
+    }
+
+    // add-2008-hwcd
+    pub fn add(&self, p1: &EdPoint, p2: &EdPoint) -> EdPoint {
+        let q = &self.q;
+        let a = &p1.x * &p2.x;
+        let b = &p1.y * &p2.y;
+        let c = &self.d * &p1.t * &p2.t;
+        let d = &p1.z * &p2.z;
+        let e = (&p1.x + &p1.y) * (&p2.x + &p2.y) - &a - &b;
+        let f = &d - &c;
+        let g = d + c;
+        let h = b - &self.a * a;
+        EdPoint {
+            x: modulo(&(&e * &f), q),
+            y: modulo(&(&g * &h), q),
+            z: modulo(&(f * g), q),
+            t: modulo(&(e * h), q),
+        }
+    }
+
+    // dbl-2008-hwcd
+    pub fn double(&self, p: &EdPoint) -> EdPoint {
+        let q = &self.q;
+        let a = p.x.pow(2);
+        let b = p.y.pow(2);
+        let c = 2u32 * p.z.pow(2);
+        let d = &self.a * &a;
+        let e = (&p.x + &p.y).pow(2) - &a - &b;
+        let g = &d + &b;
+        let f = &g - c;
+        let h = d - b;
+        EdPoint {
+            x: modulo(&(&e * &f), q),
+            y: modulo(&(&g * &h), q),
+            z: modulo(&(f * g), q),
+            t: modulo(&(e * h), q),
+        }
+    }
+
+    pub fn scalar_mul(&self, p: &EdPoint, k: &bui) -> EdPoint {
+        let mut r_0 = self.identity();
+        let mut r_1 = p.clone();
+        for i in format!("{:b}", k).chars() {
+            if i == '0' {
+                r_1 = self.add(&r_0, &r_1);
+                r_0 = self.double(&r_0);
+            } else {
+                r_0 = self.add(&r_0, &r_1);
+                r_1 = self.double(&r_1);
+            }
+        }
+
+        r_0
+    }
+
+    // the cofactor of an Edwards curve is 4 or 8: a few doublings
+    pub fn mul_by_cofactor(&self, p: &EdPoint) -> EdPoint {
+        let mut r = p.clone();
+        for _ in 0..self.h.trailing_zeros() {
+            r = self.double(&r);
+        }
+        r
+    }
+
+    // b with 2^(b - 1) > q: 256 bits for Edwards25519, 456 for Edwards448
+    pub fn encoded_len(&self) -> usize {
+        (self.q.bits() as usize + 1).div_ceil(8)
+    }
+
+    // RFC 8032, 5.1.2: y little-endian, the low bit of x in the top bit
+    pub fn encode(&self, p: &EdPoint) -> Vec<u8> {
+        let (x, y) = self.to_affine(p);
+        let mut out = y.to_bytes_le().1;
+        out.resize(self.encoded_len(), 0);
+        if x.is_odd() {
+            *out.last_mut().unwrap() |= 0x80;
+        }
+        out
+    }
+
+    // RFC 8032, 5.1.3: y >= q, an x that does not exist and x = 0 with its
+    // sign bit set are all rejected
+    pub fn decode(&self, bytes: &[u8]) -> Result<EdPoint, String> {
+        if bytes.len() != self.encoded_len() {
+            return Err(String::from("Invalid point encoding length"));
+        }
+        let mut bytes = bytes.to_vec();
+        let last = bytes.last_mut().unwrap();
+        let x_0 = *last >> 7 == 1;
+        *last &= 0x7f;
+        let y = bui::from_bytes_le(Sign::Plus, &bytes);
+        if y >= self.q {
+            return Err(String::from("Coordinate is out of range"));
+        }
+
+        // x^2 = (y^2 - 1) / (d y^2 - a)
+        let q = &self.q;
+        let y2 = y.pow(2);
+        let u = modulo(&(&y2 - 1u32), q);
+        let v = modulo(&(&self.d * y2 - &self.a), q);
+        let x2 = (u * self.inverse(&v)) % q;
+        let x = sqrt_mod(&x2, q).ok_or_else(|| String::from("Point is not on the curve"))?;
+        if x.is_zero() && x_0 {
+            return Err(String::from("Invalid sign bit for x = 0"));
+        }
+        let x = if x.is_odd() == x_0 { x } else { q - x };
+        self.affine_point(&x, &y)
+    }
+
+    // small-order (torsion) points are killed by the cofactor
+    pub fn has_small_order(&self, p: &EdPoint) -> bool {
+        self.is_identity(&self.mul_by_cofactor(p))
+    }
+
+    // s = (a - d) / 4 and t = (a + d) / 6 of the map to Weierstrass form
+    fn weierstrass_st(&self) -> (bui, bui) {
+        let q = &self.q;
+        let s = modulo(&((&self.a - &self.d) * self.inverse(&bui::from(4))), q);
+        let t = modulo(&((&self.a + &self.d) * self.inverse(&bui::from(6))), q);
+        (s, t)
+    }
+
+    // The isomorphic curve y^2 = x^3 + A x + B with A = s^2 - 3 t^2 and
+    // B = 2 t^3 - t s^2, as the GOST R 34.10-2012 twisted Edwards parameter
+    // sets are given (R 1323565.1.024-2019)
+    pub fn to_weierstrass(&self) -> EC {
+        let q = &self.q;
+        let (s, t) = self.weierstrass_st();
+        let a = modulo(&(s.pow(2) - 3u32 * t.pow(2)), q);
+        let b = modulo(&(2u32 * t.pow(3) - &t * s.pow(2)), q);
+        let g = self.map_to_weierstrass(&self.p);
+        EC::new(a, b, q.clone(), Some(self.n.clone()), Some(g))
+    }
+
+    // (u, v) -> (s (1 + v) / (1 - v) + t, s (1 + v) / ((1 - v) u)); the
+    // identity goes to infinity and (0, -1) to the 2-torsion point (t, 0)
+    pub fn map_to_weierstrass(&self, p: &EdPoint) -> Point {
+        if self.is_identity(p) {
+            return Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+        }
+        let q = &self.q;
+        let (s, t) = self.weierstrass_st();
+        let (u, v) = self.to_affine(p);
+        if u.is_zero() {
+            return Point::new(t, bui::zero(), Some(bui::one()));
+        }
+        let w = (s * (&v + 1u32) * self.inverse(&modulo(&(1 - &v), q))) % q;
+        let x = (&w + t) % q;
+        let y = (w * self.inverse(&u)) % q;
+        Point::new(x, y, Some(bui::one()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+
+    #[test]
+    fn test_edwards25519_group() {
+        let ed = curves::edwards25519();
+        let b = ed.get_ref_p();
+        let q = ed.get_ref_q();
+        // d = -121665 / 121666
+        assert_eq!(modulo(&(&ed.d * 121666u32 + 121665u32), q), bui::zero());
+        assert!(ed.on_curve(b));
+        assert!(ed.is_identity(&ed.scalar_mul(b, ed.get_ref_n())));
+        assert!(!ed.has_small_order(b));
+        assert_eq!(ed.cofactor(), 8);
+
+        // the complete formula also doubles, and B - B is the identity
+        assert!(ed.eq(&ed.add(b, b), &ed.double(b)));
+        assert!(ed.is_identity(&ed.add(b, &ed.neg(b))));
+        let b3 = ed.scalar_mul(b, &bui::from(3));
+        assert!(ed.eq(&b3, &ed.add(&ed.double(b), b)));
+        assert!(ed.on_curve(&b3));
+
+        // the base point encodes as 4/5 with an even x
+        let encoded = ed.encode(b);
+        assert_eq!(
+            hex::encode(&encoded),
+            "5866666666666666666666666666666666666666666666666666666666666666"
+        );
+        assert!(ed.eq(&ed.decode(&encoded).unwrap(), b));
+        assert!(ed.eq(&ed.decode(&ed.encode(&b3)).unwrap(), &b3));
+    }
+
+    #[test]
+    fn test_decode_rejects() {
+        let ed = curves::edwards25519();
+        // y = q is out of range
+        let mut y_q = ed.get_ref_q().to_bytes_le().1;
+        y_q.resize(32, 0);
+        assert!(ed.decode(&y_q).is_err());
+        // y = 1 has only x = 0, whose sign bit must be clear
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert!(ed.is_identity(&ed.decode(&one).unwrap()));
+        one[31] |= 0x80;
+        assert!(ed.decode(&one).is_err());
+        // y = 2 gives a non-square x^2
+        let mut two = [0u8; 32];
+        two[0] = 2;
+        assert!(ed.decode(&two).is_err());
+        assert!(ed.decode(&[0u8; 31]).is_err());
+    }
+
+    // the map to Weierstrass form respects the group law
+    #[test]
+    fn test_to_weierstrass() {
+        let ed = curves::gost3410_edwards("id-tc26-gost-3410-2012-256-paramSetA").unwrap();
+        let ec = ed.to_weierstrass();
+        let g = ed.get_ref_p();
+        let two = ed.scalar_mul(g, &bui::from(2));
+        let five = ed.scalar_mul(g, &bui::from(5));
+        let sum = ed.add(&two, &five);
+        let mapped = ec
+            .add(&ed.map_to_weierstrass(&two), &ed.map_to_weierstrass(&five))
+            .unwrap();
+        assert!(ec.on_curve(&mapped));
+        assert_eq!(ed.map_to_weierstrass(&sum).get_xy(&ec), mapped.get_xy(&ec));
+        assert!(ed.map_to_weierstrass(&ed.identity()).is_infinity());
+
+        // (0, -1) has order 2, and so has its image
+        let minus_one = ed.get_ref_q() - 1u32;
+        let t = ed.map_to_weierstrass(&ed.affine_point(&bui::zero(), &minus_one).unwrap());
+        assert!(ec.on_curve(&t));
+        assert!(ec.double(&t).unwrap().is_infinity());
+    }
+}
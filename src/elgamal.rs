@@ -0,0 +1,292 @@
+// EC ElGamal (ElGamal 1985, over E(F_q) as in Koblitz 1987). A point M is
+// encrypted to Q = d G as C = (k G, M + k Q) and recovered as C_2 - d C_1.
+// Anyone holding Q can re-randomize C to (C_1 + k' G, C_2 + k' Q), which
+// decrypts to the same M but cannot be linked to C without d.
+//
+// Integers go through Koblitz's probabilistic embedding: x = m K + j for
+// the first j < K that puts x on the curve, and m = floor(x / K) back. Each
+// try succeeds with probability about 1/2, so K = 2^8 fails about once in
+// 2^256 messages.
+
+use crate::bigint_utils::{retry_with_nonce, Wipe};
+use crate::ec::{Point, EC};
+use crate::keygen::{gen_key_pair_with_rng, KeyGenMethod};
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+// log2 K
+pub const KAPPA: u32 = 8;
+
+// m K + j stays below 2^(bits(q) - 1) <= q
+pub fn max_message_bits(ec: &EC) -> u64 {
+    ec.get_ref_q().bits() - 1 - KAPPA as u64
+}
+
+pub fn embed(ec: &EC, m: &bui) -> Result<Point, String> {
+    if m.sign() == Sign::Minus || m.bits() > max_message_bits(ec) {
+        return Err(format!(
+            "Message must be in [0, 2^{})",
+            max_message_bits(ec)
+        ));
+    }
+    let base = m << KAPPA;
+    for j in 0u32..1 << KAPPA {
+        // the parity is free; even y keeps the embedding deterministic
+        if let Some(p) = ec.lift_x(&(&base + j), false) {
+            if ec.validate_public_key(&p).is_ok() {
+                return Ok(p);
+            }
+        }
+    }
+    Err(String::from("No point found for the message"))
+}
+
+pub fn extract(ec: &EC, p: &Point) -> bui {
+    let (x, _) = p.get_xy(ec);
+    x >> KAPPA
+}
+
+#[derive(Clone, Debug)]
+pub struct Ciphertext {
+    pub c_1: Point,
+    pub c_2: Point,
+}
+
+pub struct DecryptionKey {
+    d: bui,
+    encryption_key: EncryptionKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct EncryptionKey {
+    ec: EC,
+    q: Point,
+}
+
+impl DecryptionKey {
+    pub fn random_with_rng(ec: EC, rng: &mut (impl RngCore + CryptoRng)) -> DecryptionKey {
+        let (d, q) = gen_key_pair_with_rng(&ec, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+        let (q_x, q_y) = q.get_xy(&ec);
+        let q = Point::new(q_x, q_y, Some(bui::one()));
+
+        Self {
+            d,
+            encryption_key: EncryptionKey { ec, q },
+        }
+    }
+
+    pub fn from_scalar(ec: EC, d: bui) -> Result<DecryptionKey, String> {
+        if d <= bui::zero() || &d >= ec.get_ref_n() {
+            return Err(String::from("Private key must be in [1, n - 1]"));
+        }
+        let (q_x, q_y) = ec.scalar_mul(ec.get_ref_p(), &d).get_xy(&ec);
+        let q = Point::new(q_x, q_y, Some(bui::one()));
+
+        Ok(Self {
+            d,
+            encryption_key: EncryptionKey { ec, q },
+        })
+    }
+
+    pub fn encryption_key(&self) -> &EncryptionKey {
+        &self.encryption_key
+    }
+
+    // M = C_2 - d C_1
+    pub fn decrypt_point(&self, c: &Ciphertext) -> Result<Point, String> {
+        let ec = &self.encryption_key.ec;
+        ec.validate_public_key(&c.c_1)?;
+        ec.validate_public_key(&c.c_2)?;
+        let s = ec.scalar_mul(&c.c_1, &self.d);
+        let m = ec.add(&c.c_2, &ec.neg(&s))?;
+        if m.is_infinity() {
+            return Err(String::from("Plaintext is the point at infinity"));
+        }
+        Ok(m)
+    }
+
+    pub fn decrypt(&self, c: &Ciphertext) -> Result<bui, String> {
+        let m = self.decrypt_point(c)?;
+        Ok(extract(&self.encryption_key.ec, &m))
+    }
+}
+
+impl Drop for DecryptionKey {
+    fn drop(&mut self) {
+        self.d.wipe();
+    }
+}
+
+impl EncryptionKey {
+    pub fn from_point(ec: EC, q: Point) -> Result<EncryptionKey, String> {
+        ec.validate_public_key(&q)?;
+        let (q_x, q_y) = q.get_xy(&ec);
+        let q = Point::new(q_x, q_y, Some(bui::one()));
+        Ok(Self { ec, q })
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.q
+    }
+
+    // (C_1 + k G, C_2 + k Q); with C = (O, M) this is plain encryption
+    fn shift(&self, c_1: &Point, c_2: &Point, k: &bui) -> Result<Ciphertext, String> {
+        let ec = &self.ec;
+        if *k <= bui::zero() || k >= ec.get_ref_n() {
+            return Err(String::from("Nonce must be in [1, n - 1]"));
+        }
+        let c_1 = ec.add(c_1, &ec.scalar_mul(ec.get_ref_p(), k))?;
+        let c_2 = ec.add(c_2, &ec.scalar_mul(&self.q, k))?;
+        Ok(Ciphertext { c_1, c_2 })
+    }
+
+    pub fn encrypt_point_with_rng(
+        &self,
+        m: &Point,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Ciphertext, String> {
+        self.ec.validate_public_key(m)?;
+        Ok(retry_with_nonce(rng, self.ec.get_ref_n(), |k| {
+            self.encrypt_point_with_k(m, k)
+        }))
+    }
+
+    // (k G, M + k Q); never fails for k in [1, n - 1] and M on the curve
+    pub(crate) fn encrypt_point_with_k(&self, m: &Point, k: &bui) -> Result<Ciphertext, String> {
+        self.ec.validate_public_key(m)?;
+        let o = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+        self.shift(&o, m, k)
+    }
+
+    pub fn encrypt_with_rng(
+        &self,
+        m: &bui,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Ciphertext, String> {
+        self.encrypt_point_with_rng(&embed(&self.ec, m)?, rng)
+    }
+
+    pub fn rerandomize_with_rng(
+        &self,
+        c: &Ciphertext,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Ciphertext, String> {
+        self.ec.validate_public_key(&c.c_1)?;
+        self.ec.validate_public_key(&c.c_2)?;
+        Ok(retry_with_nonce(rng, self.ec.get_ref_n(), |k| {
+            self.rerandomize_with_k(c, k)
+        }))
+    }
+
+    pub(crate) fn rerandomize_with_k(&self, c: &Ciphertext, k: &bui) -> Result<Ciphertext, String> {
+        self.ec.validate_public_key(&c.c_1)?;
+        self.ec.validate_public_key(&c.c_2)?;
+        self.shift(&c.c_1, &c.c_2, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use num_bigint::RandBigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_embedding() {
+        let mut rng = StdRng::seed_from_u64(50);
+        for ec in [curves::p224(), curves::p256(), curves::secp256k1()] {
+            let bits = max_message_bits(&ec);
+            for m in [
+                bui::zero(),
+                bui::one(),
+                rng.gen_biguint(bits).into(),
+                (bui::one() << bits) - 1,
+            ] {
+                let p = embed(&ec, &m).unwrap();
+                assert!(ec.on_curve(&p));
+                assert_eq!(extract(&ec, &p), m);
+
+                // j is the first offset that lands on the curve
+                let (x, _) = p.get_xy(&ec);
+                let j = &x - (&m << KAPPA);
+                let mut i = bui::zero();
+                while i < j {
+                    assert!(ec.lift_x(&((&m << KAPPA) + &i), false).is_none());
+                    i += 1;
+                }
+            }
+            assert!(embed(&ec, &(bui::one() << bits)).is_err());
+            assert!(embed(&ec, &bui::from(-1)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_enc_dec() {
+        let mut rng = StdRng::seed_from_u64(1985);
+        let ec = curves::p256();
+        let key = DecryptionKey::random_with_rng(ec.clone(), &mut rng);
+        let ek = key.encryption_key();
+        let m = rng.gen_biguint(max_message_bits(&ec)).into();
+
+        let c = ek.encrypt_with_rng(&m, &mut rng).unwrap();
+        assert_eq!(key.decrypt(&c).unwrap(), m);
+        // probabilistic: the same message encrypts differently
+        let c_2 = ek.encrypt_with_rng(&m, &mut rng).unwrap();
+        assert_ne!(c.c_1.get_xy(&ec), c_2.c_1.get_xy(&ec));
+        assert_eq!(key.decrypt(&c_2).unwrap(), m);
+
+        let other = DecryptionKey::random_with_rng(ec.clone(), &mut rng);
+        assert_ne!(other.decrypt(&c).ok(), Some(m));
+
+        let same = DecryptionKey::from_scalar(ec.clone(), key.d.clone()).unwrap();
+        assert_eq!(
+            same.encryption_key().as_point().get_xy(&ec),
+            ek.as_point().get_xy(&ec)
+        );
+        assert!(DecryptionKey::from_scalar(ec.clone(), bui::zero()).is_err());
+        assert!(DecryptionKey::from_scalar(ec.clone(), ec.get_ref_n().clone()).is_err());
+
+        // a point at infinity or off the curve is refused
+        let o = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+        let bad = Ciphertext {
+            c_1: o.clone(),
+            c_2: c.c_2.clone(),
+        };
+        assert!(key.decrypt(&bad).is_err());
+        assert!(ek.encrypt_point_with_rng(&o, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_rerandomize() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let ec = curves::p256();
+        let key = DecryptionKey::random_with_rng(ec.clone(), &mut rng);
+        // re-randomization needs only the public key
+        let ek =
+            EncryptionKey::from_point(ec.clone(), key.encryption_key().as_point().clone()).unwrap();
+        let m = bui::from(0xe16aa1u32);
+        let mut c = ek.encrypt_with_rng(&m, &mut rng).unwrap();
+
+        for _ in 0..3 {
+            let c_2 = ek.rerandomize_with_rng(&c, &mut rng).unwrap();
+            assert_ne!(c.c_1.get_xy(&ec), c_2.c_1.get_xy(&ec));
+            assert_ne!(c.c_2.get_xy(&ec), c_2.c_2.get_xy(&ec));
+            assert_eq!(key.decrypt(&c_2).unwrap(), m);
+            c = c_2;
+        }
+
+        // re-randomizing with k' is encrypting with k + k'
+        let point = embed(&ec, &m).unwrap();
+        let (k, k_2) = (bui::from(7), bui::from(11));
+        let c = ek.encrypt_point_with_k(&point, &k).unwrap();
+        let c_2 = ek.rerandomize_with_k(&c, &k_2).unwrap();
+        let c_3 = ek.encrypt_point_with_k(&point, &(k + k_2)).unwrap();
+        assert_eq!(c_2.c_1.get_xy(&ec), c_3.c_1.get_xy(&ec));
+        assert_eq!(c_2.c_2.get_xy(&ec), c_3.c_2.get_xy(&ec));
+    }
+}
@@ -0,0 +1,259 @@
+// GF(2^m) in polynomial basis: an element is the bui whose bit i is the
+// coefficient of t^i, reduced modulo an irreducible trinomial or pentanomial
+// f(t) = t^m + t^k3 + t^k2 + t^k1 + 1. Multiplication works on 64-bit words
+// with a carry-less word product; inversion is Itoh-Tsujii.
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use num_traits::{One, Zero};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gf2m {
+    m: usize,
+    // the middle exponents of f(t), highest first
+    ks: Vec<usize>,
+    f: bui,
+}
+
+fn words(a: &bui) -> Vec<u64> {
+    a.to_u64_digits().1
+}
+
+fn from_words(w: &[u64]) -> bui {
+    let mut bytes = Vec::with_capacity(w.len() * 8);
+    for word in w {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    bui::from_bytes_le(Sign::Plus, &bytes)
+}
+
+// the 32 bits of w at the even positions of a u64
+fn spread(w: u32) -> u64 {
+    let mut x = w as u64;
+    x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+// carry-less product of two words as (high, low), four bits of b at a time
+fn clmul(a: u64, b: u64) -> (u64, u64) {
+    let mut table = [0u128; 16];
+    for i in 1..16 {
+        table[i] = if i % 2 == 0 {
+            table[i / 2] << 1
+        } else {
+            table[i - 1] ^ a as u128
+        };
+    }
+    let mut r = 0u128;
+    for i in (0..16).rev() {
+        r = (r << 4) ^ table[((b >> (4 * i)) & 15) as usize];
+    }
+    ((r >> 64) as u64, r as u64)
+}
+
+impl Gf2m {
+    // f(t) = t^m + sum t^k + 1 for the given middle exponents
+    pub fn new(m: usize, ks: &[usize]) -> Self {
+        let mut ks = ks.to_vec();
+        ks.sort_unstable_by(|a, b| b.cmp(a));
+        let mut f = (bui::one() << m) | bui::one();
+        for &k in &ks {
+            f |= bui::one() << k;
+        }
+        Self { m, ks, f }
+    }
+
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    pub fn get_ref_f(&self) -> &bui {
+        &self.f
+    }
+
+    pub fn byte_len(&self) -> usize {
+        self.m.div_ceil(8)
+    }
+
+    pub fn contains(&self, a: &bui) -> bool {
+        a.sign() != Sign::Minus && a.bits() <= self.m as u64
+    }
+
+    // word-wise reduction: the part of each word at t^m and above is cleared
+    // and folded down through the exponents of f, top word first. Every f
+    // here has m - k > 64, so one fold never lands back in the same word
+    // except at the boundary, which is repeated until clean.
+    fn reduce_words(&self, mut c: Vec<u64>) -> bui {
+        let m = self.m;
+        for i in (m / 64..c.len()).rev() {
+            loop {
+                let w = if i == m / 64 {
+                    c[i] >> (m % 64) << (m % 64)
+                } else {
+                    c[i]
+                };
+                if w == 0 {
+                    break;
+                }
+                c[i] ^= w;
+                // bit j of w is t^(64 i + j), which becomes t^(64 i + j - m + k)
+                for k in self.ks.iter().copied().chain([0]) {
+                    let shift = 64 * i as isize + k as isize - m as isize;
+                    if shift < 0 {
+                        // the low -shift bits of w are below t^m, so zero
+                        c[0] ^= w >> -shift;
+                    } else {
+                        let (word, bit) = (shift as usize / 64, shift as usize % 64);
+                        c[word] ^= w << bit;
+                        if bit > 0 {
+                            c[word + 1] ^= w >> (64 - bit);
+                        }
+                    }
+                }
+            }
+        }
+        c.truncate(m.div_ceil(64));
+        from_words(&c)
+    }
+
+    pub fn reduce(&self, a: &bui) -> bui {
+        self.reduce_words(words(a))
+    }
+
+    pub fn mul(&self, a: &bui, b: &bui) -> bui {
+        let (a, b) = (words(a), words(b));
+        if a.is_empty() || b.is_empty() {
+            return bui::zero();
+        }
+        let mut c = vec![0u64; a.len() + b.len()];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                let (hi, lo) = clmul(x, y);
+                c[i + j] ^= lo;
+                c[i + j + 1] ^= hi;
+            }
+        }
+        self.reduce_words(c)
+    }
+
+    // squaring is linear over GF(2): the bits of a spread out with a zero
+    // between each pair, then one reduction
+    pub fn square(&self, a: &bui) -> bui {
+        let mut c = Vec::with_capacity(2 * self.m.div_ceil(64));
+        for w in words(a) {
+            c.push(spread(w as u32));
+            c.push(spread((w >> 32) as u32));
+        }
+        self.reduce_words(c)
+    }
+
+    // a^(2^k)
+    pub fn square_times(&self, a: &bui, k: usize) -> bui {
+        (0..k).fold(a.clone(), |acc, _| self.square(&acc))
+    }
+
+    // Itoh-Tsujii: a^-1 = (a^(2^(m - 1) - 1))^2, with b_k = a^(2^k - 1) built
+    // along the bits of m - 1 by b_(2k) = b_k^(2^k) b_k and b_(k + 1) = b_k^2 a
+    pub fn inv(&self, a: &bui) -> Result<bui, String> {
+        let a = self.reduce(a);
+        if a.is_zero() {
+            return Err(String::from("Zero has no inverse"));
+        }
+        let e = self.m - 1;
+        let mut b = a.clone();
+        let mut k = 1;
+        for i in (0..usize::BITS - 1 - e.leading_zeros()).rev() {
+            b = self.mul(&self.square_times(&b, k), &b);
+            k *= 2;
+            if (e >> i) & 1 == 1 {
+                b = self.mul(&self.square(&b), &a);
+                k += 1;
+            }
+        }
+        Ok(self.square(&b))
+    }
+
+    // a^(2^(m - 1)), the unique square root
+    pub fn sqrt(&self, a: &bui) -> bui {
+        self.square_times(a, self.m - 1)
+    }
+
+    // Tr(a) = a + a^2 + ... + a^(2^(m - 1)), 0 or 1
+    pub fn trace(&self, a: &bui) -> bui {
+        let mut t = a.clone();
+        let mut s = a.clone();
+        for _ in 1..self.m {
+            t = self.square(&t);
+            s ^= &t;
+        }
+        s
+    }
+
+    // For odd m, H(c) = sum c^(2^(2i)) solves z^2 + z = c when Tr(c) = 0
+    pub fn half_trace(&self, a: &bui) -> bui {
+        let mut t = a.clone();
+        let mut s = a.clone();
+        for _ in 0..(self.m - 1) / 2 {
+            t = self.square_times(&t, 2);
+            s ^= &t;
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::RandBigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // schoolbook multiply and reduce, bit by bit
+    fn mul_reference(field: &Gf2m, a: &bui, b: &bui) -> bui {
+        let mut r = bui::zero();
+        for i in 0..b.bits() {
+            if b.bit(i) {
+                r ^= a << i as usize;
+            }
+        }
+        let m = field.m() as u64;
+        while r.bits() > m {
+            r ^= field.get_ref_f() << (r.bits() - 1 - m) as usize;
+        }
+        r
+    }
+
+    #[test]
+    fn test_field_arithmetic() {
+        let mut rng = StdRng::seed_from_u64(40);
+        for field in [
+            Gf2m::new(163, &[7, 6, 3]),
+            Gf2m::new(409, &[87]),
+            Gf2m::new(431, &[5, 3, 1]),
+            Gf2m::new(571, &[10, 5, 2]),
+        ] {
+            let m = field.m() as u64;
+            for _ in 0..8 {
+                let a = bui::from(rng.gen_biguint(m));
+                let b = bui::from(rng.gen_biguint(m));
+                assert_eq!(field.mul(&a, &b), mul_reference(&field, &a, &b));
+                assert_eq!(field.square(&a), mul_reference(&field, &a, &a));
+                if !a.is_zero() {
+                    assert!(field.mul(&a, &field.inv(&a).unwrap()).is_one());
+                }
+                assert_eq!(field.square(&field.sqrt(&a)), a);
+
+                // z = H(c) solves z^2 + z = c for trace-zero c
+                let c = field.square(&a) ^ &a;
+                assert!(field.trace(&c).is_zero());
+                let z = field.half_trace(&c);
+                assert_eq!(field.square(&z) ^ &z, c);
+            }
+            assert!(field.inv(&bui::zero()).is_err());
+            // Tr(1) = m mod 2
+            assert!(field.trace(&bui::one()).is_one());
+        }
+    }
+}
@@ -0,0 +1,332 @@
+// GLV scalar multiplication on secp256k1 (Gallant, Lambert, Vanstone 2001).
+// With q = 1 mod 3 and a = 0, φ(x, y) = (β x, y) for a cube root of unity β
+// mod q acts on the group as multiplication by λ, a cube root of unity mod n.
+// k is split as k1 + k2 λ with |k1|, |k2| about 128 bits (Hankerson, Menezes,
+// Vanstone 3.74), and k1 P + k2 φ(P) is evaluated with interleaved width-5
+// NAFs, sharing half as many doublings as the plain ladder. The digits are
+// read as they come, so the running time depends on the scalar: only
+// EC::scalar_mul_vartime and EC::multi_scalar_mul take this path. For secret
+// scalars EC::scalar_mul uses scalar_mul_regular instead, which does one
+// doubling and one addition per bit of the halves whatever their value.
+// Points on secp256k1 take these paths by themselves.
+
+use crate::bigint_utils::FromHex;
+use crate::ec::{Point, EC};
+
+use num_bigint::BigInt as bui;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+use once_cell::sync::Lazy;
+
+const W: usize = 5;
+
+#[derive(Clone, Debug)]
+pub struct Glv {
+    q: bui,
+    n: bui,
+    beta: bui,
+    lambda: bui,
+    // short basis of {(x, y) : x + y λ = 0 mod n}
+    a1: bui,
+    b1: bui,
+    a2: bui,
+    b2: bui,
+}
+
+static SECP256K1: Lazy<Glv> = Lazy::new(Glv::secp256k1);
+
+// the GLV parameters for ec, when ec is secp256k1
+pub fn for_curve(ec: &EC) -> Option<&'static Glv> {
+    let glv = &*SECP256K1;
+    (ec.get_ref_q() == &glv.q && ec.get_ref_a().is_zero() && ec.get_ref_b() == &bui::from(7))
+        .then_some(glv)
+}
+
+// width-w NAF of k >= 0, least significant digit first: odd digits in
+// (-2^(w - 1), 2^(w - 1)), at most one non-zero in any w consecutive
+pub fn wnaf(k: &bui, w: usize) -> Vec<i8> {
+    let modulus = bui::one() << w;
+    let half_modulus = bui::one() << (w - 1);
+    let mut k = k.clone();
+    let mut digits = Vec::new();
+    while !k.is_zero() {
+        let mut u = 0i8;
+        if k.is_odd() {
+            let mut v = k.mod_floor(&modulus);
+            if v >= half_modulus {
+                v -= &modulus;
+            }
+            u = i8::try_from(&v).unwrap();
+            k -= v;
+        }
+        digits.push(u);
+        k >>= 1;
+    }
+    digits
+}
+
+// round(x / n) for x >= 0
+fn div_round(x: &bui, n: &bui) -> bui {
+    let x: bui = 2 * x + n;
+    x.div_floor(&(2 * n))
+}
+
+impl Glv {
+    // libsecp256k1's β, λ and lattice basis
+    pub fn secp256k1() -> Glv {
+        let hex = |s: &str| bui::from_hex(s).unwrap();
+        Glv {
+            q: hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f"),
+            n: hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"),
+            beta: hex("7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ee"),
+            lambda: hex("5363ad4cc05c30e0a5261c028812645a122e22ea20816678df02967c1b23bd72"),
+            a1: hex("3086d221a7d46bcde86c90e49284eb15"),
+            b1: -hex("e4437ed6010e88286f547fa90abfe4c3"),
+            a2: hex("114ca50f7a8e2f3f657c1108d9d44cfd8"),
+            b2: hex("3086d221a7d46bcde86c90e49284eb15"),
+        }
+    }
+
+    pub fn get_ref_lambda(&self) -> &bui {
+        &self.lambda
+    }
+
+    // k = k1 + k2 λ mod n: c1 = round(b2 k / n), c2 = round(-b1 k / n),
+    // k1 = k - c1 a1 - c2 a2, k2 = -c1 b1 - c2 b2
+    pub fn decompose(&self, k: &bui) -> (bui, bui) {
+        let k = k.mod_floor(&self.n);
+        let c1 = div_round(&(&self.b2 * &k), &self.n);
+        let c2 = div_round(&(-&self.b1 * &k), &self.n);
+        let k1 = &k - &c1 * &self.a1 - &c2 * &self.a2;
+        let k2 = -&c1 * &self.b1 - &c2 * &self.b2;
+        (k1, k2)
+    }
+
+    // φ(P) = (β x, y) = λ P
+    pub fn endomorphism(&self, ec: &EC, p: &Point) -> Point {
+        if p.is_infinity() {
+            return p.clone();
+        }
+        let (x, y) = p.get_xy(ec);
+        Point::new((&self.beta * x) % &self.q, y, Some(bui::one()))
+    }
+
+    // P, 3 P, ..., (2^(W - 1) - 1) P
+    fn odd_multiples(ec: &EC, p: &Point) -> Vec<Point> {
+        let p2 = ec.double(p).unwrap();
+        let mut table = vec![p.clone()];
+        for i in 1..1 << (W - 2) {
+            let next = ec.add(&table[i - 1], &p2).unwrap();
+            table.push(next);
+        }
+        table
+    }
+
+    // k P -> k1 P + k2 φ(P) for every term, the signs moved onto the points
+    pub fn split(&self, ec: &EC, terms: &[(Point, bui)]) -> Vec<(Point, bui)> {
+        let mut out = Vec::with_capacity(2 * terms.len());
+        for (p, k) in terms {
+            let (k1, k2) = self.decompose(k);
+            for (p, k) in [(p.clone(), k1), (self.endomorphism(ec, p), k2)] {
+                if k.is_negative() {
+                    out.push((ec.neg(&p), -k));
+                } else {
+                    out.push((p, k));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn scalar_mul(&self, ec: &EC, p: &Point, k: &bui) -> Point {
+        let (k1, k2) = self.decompose(k);
+        let p1 = if k1.is_negative() {
+            ec.neg(p)
+        } else {
+            p.clone()
+        };
+        let p2 = self.endomorphism(ec, p);
+        let p2 = if k2.is_negative() { ec.neg(&p2) } else { p2 };
+
+        let terms = [
+            (Self::odd_multiples(ec, &p1), wnaf(&k1.abs(), W)),
+            (Self::odd_multiples(ec, &p2), wnaf(&k2.abs(), W)),
+        ];
+        let len = terms.iter().map(|(_, d)| d.len()).max().unwrap_or(0);
+
+        let mut acc = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+        for i in (0..len).rev() {
+            acc = ec.double(&acc).unwrap();
+            for (table, digits) in &terms {
+                match digits.get(i) {
+                    Some(&u) if u > 0 => {
+                        acc = ec.add(&acc, &table[u as usize / 2]).unwrap();
+                    }
+                    Some(&u) if u < 0 => {
+                        acc = ec
+                            .add(&acc, &ec.neg(&table[u.unsigned_abs() as usize / 2]))
+                            .unwrap();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        acc
+    }
+
+    // k1 P + k2 φ(P) bit by bit from a table of O, P, φ(P), P + φ(P); the
+    // number of bits depends only on n, as long as the split stays within
+    // its bound
+    pub fn scalar_mul_regular(&self, ec: &EC, p: &Point, k: &bui) -> Point {
+        let (k1, k2) = self.decompose(k);
+        let p1 = if k1.is_negative() {
+            ec.neg(p)
+        } else {
+            p.clone()
+        };
+        let p2 = self.endomorphism(ec, p);
+        let p2 = if k2.is_negative() { ec.neg(&p2) } else { p2 };
+        let (k1, k2) = (k1.abs(), k2.abs());
+
+        let o = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+        let p12 = ec.add(&p1, &p2).unwrap();
+        let table = [o.clone(), p1, p2, p12];
+        let len = (self.n.bits().div_ceil(2) + 1)
+            .max(k1.bits())
+            .max(k2.bits());
+
+        let mut acc = o;
+        for i in (0..len).rev() {
+            acc = ec.double(&acc).unwrap();
+            let j = usize::from(k1.bit(i)) | (usize::from(k2.bit(i)) << 1);
+            acc = ec.add(&acc, &table[j]).unwrap();
+        }
+        acc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use num_bigint::RandBigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_parameters() {
+        let glv = Glv::secp256k1();
+        let (q, n) = (&glv.q, &glv.n);
+        assert!(glv.beta.modpow(&bui::from(3), q).is_one());
+        assert!(glv.lambda.modpow(&bui::from(3), n).is_one());
+        assert!((&glv.a1 + &glv.b1 * &glv.lambda).mod_floor(n).is_zero());
+        assert!((&glv.a2 + &glv.b2 * &glv.lambda).mod_floor(n).is_zero());
+
+        let ec = curves::secp256k1();
+        assert!(for_curve(&ec).is_some());
+        assert!(for_curve(&curves::p256()).is_none());
+        let g = ec.get_ref_p();
+        assert_eq!(
+            glv.endomorphism(&ec, g).get_xy(&ec),
+            ec.ladder(g, glv.get_ref_lambda()).get_xy(&ec)
+        );
+    }
+
+    #[test]
+    fn test_decompose() {
+        let glv = Glv::secp256k1();
+        let mut rng = StdRng::seed_from_u64(43);
+        let n = &glv.n;
+        let mut scalars = vec![
+            bui::zero(),
+            bui::one(),
+            n - 1u32,
+            glv.lambda.clone(),
+            n - &glv.lambda,
+        ];
+        scalars.extend((0..100).map(|_| rng.gen_bigint_range(&bui::zero(), n)));
+        for k in scalars {
+            let (k1, k2) = glv.decompose(&k);
+            assert_eq!((&k1 + &k2 * &glv.lambda).mod_floor(n), k);
+            assert!(k1.bits() <= 129 && k2.bits() <= 129, "{k}");
+        }
+    }
+
+    #[test]
+    fn test_wnaf() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..20 {
+            let k = bui::from(rng.gen_biguint(256));
+            let digits = wnaf(&k, W);
+            let value = digits.iter().rev().fold(bui::zero(), |acc, &u| 2 * acc + u);
+            assert_eq!(value, k);
+            assert!(digits
+                .windows(W)
+                .all(|d| d.iter().filter(|&&u| u != 0).count() <= 1));
+        }
+    }
+
+    // EC::scalar_mul, EC::scalar_mul_vartime and EC::multi_scalar_mul go
+    // through GLV on secp256k1; the ladder is the reference
+    #[test]
+    fn test_against_ladder() {
+        let ec = curves::secp256k1();
+        let n = ec.get_ref_n();
+        let mut rng = StdRng::seed_from_u64(256);
+        let p = ec.ladder(ec.get_ref_p(), &rng.gen_bigint_range(&bui::one(), n));
+        let mut scalars = vec![bui::zero(), bui::one(), bui::from(2), n - 1u32, n.clone()];
+        scalars.extend((0..10).map(|_| rng.gen_bigint_range(&bui::zero(), n)));
+        for k in &scalars {
+            let expected = ec.ladder(&p, k);
+            for result in [
+                ec.scalar_mul(&p, k),
+                ec.scalar_mul_vartime(&p, k),
+                ec.multi_scalar_mul(&[(p.clone(), k.clone())]),
+            ] {
+                assert_eq!(result.is_infinity(), expected.is_infinity(), "{k}");
+                if !expected.is_infinity() {
+                    assert_eq!(result.get_xy(&ec), expected.get_xy(&ec), "{k}");
+                }
+            }
+        }
+
+        // two terms, one of them with the same point twice
+        let (k, l) = (&scalars[5], &scalars[6]);
+        let expected = ec
+            .add(&ec.ladder(&p, k), &ec.ladder(ec.get_ref_p(), l))
+            .unwrap();
+        let result =
+            ec.multi_scalar_mul(&[(p.clone(), k.clone()), (ec.get_ref_p().clone(), l.clone())]);
+        assert_eq!(result.get_xy(&ec), expected.get_xy(&ec));
+    }
+
+    #[test]
+    fn test_benchmark() {
+        let ec = curves::secp256k1();
+        let n = ec.get_ref_n();
+        let mut rng = StdRng::seed_from_u64(1);
+        let g = ec.get_ref_p();
+        let (mut ladder, mut regular, mut glv) = (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+        let t = 10;
+        for _ in 0..t {
+            let k = rng.gen_bigint_range(&bui::one(), n);
+            let start = Instant::now();
+            let expected = ec.ladder(g, &k);
+            ladder += start.elapsed();
+            let start = Instant::now();
+            let result = ec.scalar_mul(g, &k);
+            regular += start.elapsed();
+            assert_eq!(result.get_xy(&ec), expected.get_xy(&ec));
+            let start = Instant::now();
+            let result = ec.scalar_mul_vartime(g, &k);
+            glv += start.elapsed();
+            assert_eq!(result.get_xy(&ec), expected.get_xy(&ec));
+        }
+        println!(
+            "secp256k1 ladder: {:?}, regular GLV: {:?}, GLV: {:?}",
+            ladder / t,
+            regular / t,
+            glv / t
+        );
+    }
+}
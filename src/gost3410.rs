@@ -0,0 +1,380 @@
+// GOST R 34.10-2012 signatures over the prime curves in curves::gost3410.
+// The digest is read as a little-endian integer, e = α mod q (0 becomes 1);
+// r = x(k P) mod q and s = (r d + k e) mod q. Verification checks r against
+// x(s e^-1 P - r e^-1 Q). 256-bit sets hash with Streebog-256, 512-bit sets
+// with Streebog-512. Keys travel little-endian as RFC 4491 and R 1323565.1.024
+// have them (d, and x || y for Q); the signature octets are s || r, both
+// big-endian.
+
+use crate::bigint_utils::{mod_inverse, modulo, retry_with_nonce, Wipe};
+use crate::ec::{to_fixed_be, Point, EC};
+use crate::keygen::{gen_key_pair_with_rng, KeyGenMethod};
+use crate::signature::{Signer, Verifier};
+use crate::streebog::{streebog256, streebog512};
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+pub struct SigningKey {
+    d: bui,
+    verifying_key: VerifyingKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
+    ec: EC,
+    q: Point,
+}
+
+// n as len little-endian bytes
+fn to_le(n: &bui, len: usize) -> Vec<u8> {
+    let mut out = n.to_bytes_le().1;
+    out.resize(len, 0);
+    out
+}
+
+// Streebog-256 for the 256-bit sets, Streebog-512 for the 512-bit ones
+pub fn hash(ec: &EC, m: &[u8]) -> Vec<u8> {
+    if ec.field_len() <= 32 {
+        streebog256(m)
+    } else {
+        streebog512(m)
+    }
+}
+
+// e = α mod q for the digest α read little-endian, with e = 0 replaced by 1
+pub fn digest_to_int(digest: &[u8], q: &bui) -> bui {
+    let e = bui::from_bytes_le(Sign::Plus, digest) % q;
+    if e.is_zero() {
+        bui::one()
+    } else {
+        e
+    }
+}
+
+impl SigningKey {
+    pub fn random_with_rng(ec: EC, rng: &mut (impl RngCore + CryptoRng)) -> SigningKey {
+        let (d, q) = gen_key_pair_with_rng(&ec, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+        let (q_x, q_y) = q.get_xy(&ec);
+        let q = Point::new(q_x, q_y, Some(bui::one()));
+
+        Self {
+            d,
+            verifying_key: VerifyingKey { ec, q },
+        }
+    }
+
+    pub fn from_scalar(ec: EC, d: bui) -> Result<SigningKey, String> {
+        if d <= bui::zero() || &d >= ec.get_ref_n() {
+            return Err(String::from("Private key must be in [1, q - 1]"));
+        }
+        let q = ec.scalar_mul(ec.get_ref_p(), &d);
+        let (x, y) = q.get_xy(&ec);
+        let q = Point::new(x, y, Some(bui::one()));
+
+        Ok(Self {
+            d,
+            verifying_key: VerifyingKey { ec, q },
+        })
+    }
+
+    // d as field-length little-endian bytes
+    pub fn from_bytes(ec: EC, bytes: &[u8]) -> Result<SigningKey, String> {
+        if bytes.len() != ec.field_len() {
+            return Err(String::from("Invalid private key length"));
+        }
+        Self::from_scalar(ec, bui::from_bytes_le(Sign::Plus, bytes))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_le(&self.d, self.verifying_key.ec.field_len())
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    pub fn sign_digest_with_rng(
+        &self,
+        digest: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        if digest.is_empty() {
+            return Err(String::from("Digest is empty"));
+        }
+        let n = self.verifying_key.ec.get_ref_n();
+        Ok(retry_with_nonce(rng, n, |k| {
+            self.sign_digest_with_k(digest, k)
+        }))
+    }
+
+    // r = x(k P) mod q, s = (r d + k e) mod q; fails when r = 0 or s = 0
+    pub(crate) fn sign_digest_with_k(&self, digest: &[u8], k: &bui) -> Result<(bui, bui), String> {
+        let ec = &self.verifying_key.ec;
+        let n = ec.get_ref_n();
+        if *k <= bui::zero() || k >= n {
+            return Err(String::from("Nonce must be in [1, q - 1]"));
+        }
+        let e = digest_to_int(digest, n);
+
+        let (x_c, _) = ec.scalar_mul(ec.get_ref_p(), k).get_xy(ec);
+        let r = x_c % n;
+        if r.is_zero() {
+            return Err(String::from("r = 0, another nonce is needed"));
+        }
+        let s = (&r * &self.d + k * e) % n;
+        if s.is_zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        Ok((r, s))
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.d.wipe();
+    }
+}
+
+impl Signer<(bui, bui)> for SigningKey {
+    fn sign(&self, m: &[u8]) -> Result<(bui, bui), String> {
+        let digest = hash(&self.verifying_key.ec, m);
+        self.sign_digest_with_rng(&digest, &mut rand::thread_rng())
+    }
+}
+
+impl VerifyingKey {
+    pub fn from_point(ec: EC, q: Point) -> Result<VerifyingKey, String> {
+        ec.validate_public_key(&q)?;
+        let (x, y) = q.get_xy(&ec);
+        let q = Point::new(x, y, Some(bui::one()));
+        Ok(Self { ec, q })
+    }
+
+    // x || y, each field-length little-endian
+    pub fn from_bytes(ec: EC, bytes: &[u8]) -> Result<VerifyingKey, String> {
+        let len = ec.field_len();
+        if bytes.len() != 2 * len {
+            return Err(String::from("Invalid public key length"));
+        }
+        let x = bui::from_bytes_le(Sign::Plus, &bytes[..len]);
+        let y = bui::from_bytes_le(Sign::Plus, &bytes[len..]);
+        Self::from_point(ec, Point::new(x, y, Some(bui::one())))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.ec.field_len();
+        let (x, y) = self.q.get_xy(&self.ec);
+        let mut out = to_le(&x, len);
+        out.extend(to_le(&y, len));
+        out
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.q
+    }
+
+    // v = e^-1, z1 = s v, z2 = -r v and R = x(z1 P + z2 Q) mod q must be r
+    pub fn verify_digest(&self, digest: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        let ec = &self.ec;
+        let n = ec.get_ref_n();
+        let (r, s) = sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
+
+        let e = digest_to_int(digest, n);
+        let v = mod_inverse(&e, n).ok_or_else(|| String::from("e is not invertible mod n"))?;
+        let z_1 = (s * &v) % n;
+        let z_2 = modulo(&-(r * &v), n);
+        let c = ec.multi_scalar_mul(&[(ec.get_ref_p().clone(), z_1), (self.q.clone(), z_2)]);
+        if c.is_infinity() {
+            return Err(String::from("C is the point at infinity"));
+        }
+        let (x_c, _) = c.get_xy(ec);
+        if x_c % n == *r {
+            Ok(())
+        } else {
+            Err(String::from("Signature does not match"))
+        }
+    }
+}
+
+impl Verifier<(bui, bui)> for VerifyingKey {
+    fn verify(&self, m: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        self.verify_digest(&hash(&self.ec, m), sign)
+    }
+}
+
+// s || r, each field-length big-endian
+pub fn encode_signature(ec: &EC, sign: &(bui, bui)) -> Vec<u8> {
+    let len = ec.field_len();
+    let mut out = to_fixed_be(&sign.1, len);
+    out.extend(to_fixed_be(&sign.0, len));
+    out
+}
+
+pub fn decode_signature(ec: &EC, bytes: &[u8]) -> Result<(bui, bui), String> {
+    let len = ec.field_len();
+    if bytes.len() != 2 * len {
+        return Err(String::from("Invalid signature length"));
+    }
+    let s = bui::from_bytes_be(Sign::Plus, &bytes[..len]);
+    let r = bui::from_bytes_be(Sign::Plus, &bytes[len..]);
+    Ok((r, s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::int;
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // GOST R 34.10-2012, appendix A.1; the digest bytes are α little-endian
+    #[test]
+    fn test_example_256() {
+        let ec = curves::gost3410("id-GostR3410-2001-TestParamSet").unwrap();
+        let key = SigningKey::from_scalar(
+            ec.clone(),
+            int("7A929ADE789BB9BE10ED359DD39A72C11B60961F49397EEE1D19CE9891EC3B28"),
+        )
+        .unwrap();
+        let (x, y) = key.verifying_key().as_point().get_xy(&ec);
+        assert_eq!(
+            x,
+            int("7F2B49E270DB6D90D8595BEC458B50C58585BA1D4E9B788F6689DBD8E56FD80B")
+        );
+        assert_eq!(
+            y,
+            int("26F1B489D6701DD185C8413A977B3CBBAF64D1C593D26627DFFB101A87FF77DA")
+        );
+
+        let digest =
+            hex::decode("E53E042B67E6EC678E2E02B12A0352CE1FC6EEE0529CC088119AD872B3C1FB2D")
+                .unwrap();
+        assert_eq!(
+            digest_to_int(&digest, ec.get_ref_n()),
+            int("2DFBC1B372D89A1188C09C52E0EEC61FCE52032AB1022E8E67ECE6672B043EE5")
+        );
+        let k = int("77105C9B20BCD3122823C8CF6FCC7B956DE33814E95B7FE64FED924594DCEAB3");
+        let sign = key.sign_digest_with_k(&digest, &k).unwrap();
+        assert_eq!(
+            sign.0,
+            int("41AA28D2F1AB148280CD9ED56FEDA41974053554A42767B83AD043FD39DC0493")
+        );
+        assert_eq!(
+            sign.1,
+            int("01456C64BA4642A1653C235A98A60249BCD6D3F746B631DF928014F6C5BF9C40")
+        );
+        assert_eq!(
+            hex::encode_upper(encode_signature(&ec, &sign)),
+            "01456C64BA4642A1653C235A98A60249BCD6D3F746B631DF928014F6C5BF9C40\
+             41AA28D2F1AB148280CD9ED56FEDA41974053554A42767B83AD043FD39DC0493"
+        );
+        assert!(key.verifying_key().verify_digest(&digest, &sign).is_ok());
+        let mut other = digest.clone();
+        other[0] ^= 1;
+        assert!(key.verifying_key().verify_digest(&other, &sign).is_err());
+    }
+
+    // GOST R 34.10-2012, appendix A.2
+    #[test]
+    fn test_example_512() {
+        let ec = curves::gost3410("id-tc26-gost-3410-2012-512-paramSetTest").unwrap();
+        let key = SigningKey::from_scalar(
+            ec.clone(),
+            int("0BA6048AADAE241BA40936D47756D7C93091A0E8514669700EE7508E508B102072E8123B2200A0563322DAD2827E2714A2636B7BFD18AADFC62967821FA18DD4"),
+        )
+        .unwrap();
+        let (x, y) = key.verifying_key().as_point().get_xy(&ec);
+        assert_eq!(
+            x,
+            int("115DC5BC96760C7B48598D8AB9E740D4C4A85A65BE33C1815B5C320C854621DD5A515856D13314AF69BC5B924C8B4DDFF75C45415C1D9DD9DD33612CD530EFE1")
+        );
+        assert_eq!(
+            y,
+            int("37C7C90CD40B0F5621DC3AC1B751CFA0E2634FA0503B3D52639F5D7FB72AFD61EA199441D943FFE7F0C70A2759A3CDB84C114E1F9339FDF27F35ECA93677BEEC")
+        );
+
+        let e = int("3754F3CFACC9E0615C4F4A7C4D8DAB531B09B6F9C170C533A71D147035B0C5917184EE536593F4414339976C647C5D5A407ADEDB1D560C4FC6777D2972075B8C");
+        let digest = to_le(&e, 64);
+        let k = int("0359E7F4B1410FEACC570456C6801496946312120B39D019D455986E364F365886748ED7A44B3E794434006011842286212273A6D14CF70EA3AF71BB1AE679F1");
+        let sign = key.sign_digest_with_k(&digest, &k).unwrap();
+        assert_eq!(
+            sign.0,
+            int("2F86FA60A081091A23DD795E1E3C689EE512A3C82EE0DCC2643C78EEA8FCACD35492558486B20F1C9EC197C90699850260C93BCBCD9C5C3317E19344E173AE36")
+        );
+        assert_eq!(
+            sign.1,
+            int("1081B394696FFE8E6585E7A9362D26B6325F56778AADBC081C0BFBE933D52FF5823CE288E8C4F362526080DF7F70CE406A6EEB1F56919CB92A9853BDE73E5B4A")
+        );
+        assert!(key.verifying_key().verify_digest(&digest, &sign).is_ok());
+    }
+
+    // Streebog digests end to end, with fixed k; nettle's gostdsa_verify
+    // accepts both signatures
+    #[test]
+    fn test_streebog_vectors() {
+        let vectors = [
+            ("id-tc26-gost-3410-2012-256-paramSetB",
+             "e7ad783ffa142cc43ff6779942c16cf9e7107fb4649082f66848bc5ba16e9ae1",
+             "b17bd8b73d4c48b494a8f19499f61e5decc3a658bfab5827f456661f55ab12d8",
+             "967626b74df4571bb01f36d9838712667ff1d4a7dcb57ef5c7bdccb3c0103d56",
+             "de3d4084cb59168748995b143e831e64f2e2c22695c5fe4db45b4c74eaee85ef"),
+            ("id-tc26-gost-3410-2012-512-paramSetA",
+             "cfe2530f21cefe5bfb2e63138324a10bbf2cf0c017430c2cbad3b8aebd5769d08dad6d38d86b5ee8508ee535bf29f24773f4079d177ca04083ece913e60d29c3",
+             "83a38e4b12333b560eb91f607176ffe3bfe416ffd8103fd8476967d44affd1dd06cf8a7a92f8e994624af891913f746ba7587f2d3c018d7a50c1a8bf403c556b",
+             "5e2c74e701fe6639bfb6fb7bac2c08ef55b2117cd5100b844224985aa2fa1ff3e2723c65977ded6b6a0ad54554189504fd0fb5684c85d36b03fefd69579ae7ce",
+             "3ddf71ebdc4d8139e8e0dae80945bb87bd014b15e3fbb6b4b517bd23738665e17e3d3b37efcd0882bc8e51004600e1f27e619828bc8ac0fa139ce871d2af6f48"),
+        ];
+        let m = b"message signed with GOST R 34.10-2012";
+        for (name, d, k, r, s) in vectors {
+            let ec = curves::gost3410(name).unwrap();
+            let key = SigningKey::from_scalar(ec.clone(), int(d)).unwrap();
+            let digest = hash(&ec, m);
+            let sign = key.sign_digest_with_k(&digest, &int(k)).unwrap();
+            assert_eq!(sign, (int(r), int(s)), "{name}");
+            assert!(key.verifying_key().verify(m, &sign).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = StdRng::seed_from_u64(34102012);
+        for name in curves::gost3410_names() {
+            let ec = curves::gost3410(name).unwrap();
+            let key = SigningKey::random_with_rng(ec.clone(), &mut rng);
+            let sign = key.sign(b"GOST R 34.10-2012").unwrap();
+
+            // through the little-endian encodings and back
+            let key = SigningKey::from_bytes(ec.clone(), &key.to_bytes()).unwrap();
+            let vk = VerifyingKey::from_bytes(ec.clone(), &key.verifying_key().to_bytes()).unwrap();
+            let sign = decode_signature(&ec, &encode_signature(&ec, &sign)).unwrap();
+            assert!(vk.verify(b"GOST R 34.10-2012", &sign).is_ok(), "{name}");
+            assert!(vk.verify(b"GOST R 34.10-2001", &sign).is_err(), "{name}");
+            let n = ec.get_ref_n();
+            assert!(
+                vk.verify(b"GOST R 34.10-2012", &(sign.0.clone(), n.clone()))
+                    .is_err(),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_encoding_rejects() {
+        let ec = curves::gost3410("id-tc26-gost-3410-2012-256-paramSetB").unwrap();
+        let key = SigningKey::random_with_rng(ec.clone(), &mut StdRng::seed_from_u64(1));
+        let mut bytes = key.verifying_key().to_bytes();
+        assert_eq!(bytes.len(), 64);
+        assert!(VerifyingKey::from_bytes(ec.clone(), &bytes[..63]).is_err());
+        bytes[40] ^= 1;
+        assert!(VerifyingKey::from_bytes(ec.clone(), &bytes).is_err());
+        assert!(SigningKey::from_bytes(ec.clone(), &[0u8; 32]).is_err());
+        assert!(decode_signature(&ec, &[0u8; 63]).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+// Hidden number problem audit of ECDSA signatures whose nonces leak their top
+// bits (Boneh-Venkatesan, Howgrave-Graham-Smart, Nguyen-Shparlinski).
+//
+// With l known top bits a_i of an N-bit n, k_i = a_i 2^(N-l) + e_i, 0 <= e_i < E
+// for E = 2^(N-l), and s_i k_i = h_i + r_i d (mod n) gives
+//     e_i = t_i d + u_i (mod n),  t_i = r_i / s_i,  u_i = h_i / s_i - a_i 2^(N-l)
+// d is eliminated with the first signature: for C = E / 2 and e'_i = e_i - C,
+//     e'_i = c_i e'_0 + w_i (mod n),  c_i = t_i / t_0,  w_i = u_i - c_i u_0 + (c_i - 1) C
+// and (e'_1, ..., e'_{m-1}, e'_0, C) is a short vector of the lattice spanned by
+//     n e_i (i < m - 1),  (c_1, ..., c_{m-1}, 1, 0),  (w_1, ..., w_{m-1}, 0, C)
+
+use crate::bigint_utils::{mod_inverse, modulo};
+use crate::lattice::{bkz, lll};
+use crate::sign_ecdsa::{digest_to_int, VerifyingKey};
+
+use num_bigint::BigInt as bui;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+pub struct LeakySignature {
+    pub digest: Vec<u8>,
+    pub sign: (bui, bui),
+    // the known top bits of k, zero for a nonce source with zeroed top bits
+    pub leak: bui,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    Lll,
+    Bkz(usize),
+}
+
+#[derive(Debug)]
+pub struct HnpReport {
+    pub signatures: usize,
+    pub dimension: usize,
+    // the private key when a reduced basis vector gave a key matching the
+    // public point
+    pub d_a: Option<bui>,
+}
+
+impl HnpReport {
+    pub fn recoverable(&self) -> bool {
+        self.d_a.is_some()
+    }
+}
+
+// Returns the lattice basis together with (t_0, u_0) needed to turn e_0
+// back into d
+pub fn hnp_lattice(
+    n: &bui,
+    sigs: &[LeakySignature],
+    leaked_bits: u64,
+) -> Result<(Vec<Vec<bui>>, bui, bui), String> {
+    let m = sigs.len();
+    if m < 2 {
+        return Err(String::from("At least two signatures are needed"));
+    }
+    if leaked_bits == 0 || leaked_bits >= n.bits() {
+        return Err(String::from("Number of leaked bits is out of range"));
+    }
+    let shift = n.bits() - leaked_bits;
+    let c = bui::one() << (shift - 1);
+
+    let mut t = Vec::with_capacity(m);
+    let mut u = Vec::with_capacity(m);
+    for sig in sigs {
+        let (r, s) = &sig.sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
+        let s_rev = mod_inverse(s, n).ok_or_else(|| String::from("s is not invertible mod n"))?;
+        let h = digest_to_int(&sig.digest, n);
+        t.push(modulo(&(r * &s_rev), n));
+        u.push(modulo(&(h * &s_rev - (&sig.leak << shift)), n));
+    }
+
+    let t0_rev =
+        mod_inverse(&t[0], n).ok_or_else(|| String::from("t_0 is not invertible mod n"))?;
+    let mut basis = vec![vec![bui::zero(); m + 1]; m + 1];
+    for (i, row) in basis.iter_mut().take(m - 1).enumerate() {
+        row[i] = n.clone();
+    }
+    for i in 1..m {
+        let ci = modulo(&(&t[i] * &t0_rev), n);
+        let wi = modulo(&(&u[i] - &ci * &u[0] + (&ci - 1) * &c), n);
+        basis[m - 1][i - 1] = ci;
+        basis[m][i - 1] = wi;
+    }
+    basis[m - 1][m - 1] = bui::one();
+    basis[m][m] = c;
+
+    Ok((basis, t.swap_remove(0), u.swap_remove(0)))
+}
+
+// Reduces the HNP lattice and tries every basis vector carrying the embedding
+// coordinate; a key is reported only when d G equals the public point
+pub fn audit(
+    q_a: &VerifyingKey,
+    sigs: &[LeakySignature],
+    leaked_bits: u64,
+    reduction: Reduction,
+) -> Result<HnpReport, String> {
+    let ec = q_a.curve();
+    let n = ec.get_ref_n();
+    let (mut basis, t0, u0) = hnp_lattice(n, sigs, leaked_bits)?;
+    let m = sigs.len();
+    let c = basis[m][m].clone();
+
+    let delta = BigRational::new(bui::from(99), bui::from(100));
+    match reduction {
+        Reduction::Lll => lll(&mut basis, &delta),
+        Reduction::Bkz(block) => bkz(&mut basis, block, &delta, 8),
+    };
+
+    let t0_rev = mod_inverse(&t0, n).ok_or_else(|| String::from("t_0 is not invertible mod n"))?;
+    let expected = q_a.as_point().get_xy(ec);
+    let d_a = basis.iter().find_map(|row| {
+        let sign = if row[m] == c {
+            bui::one()
+        } else if row[m] == -&c {
+            -bui::one()
+        } else {
+            return None;
+        };
+        let e0 = &row[m - 1] * sign + &c;
+        let d = modulo(&((e0 - &u0) * &t0_rev), n);
+        (!d.is_zero() && ec.scalar_mul(ec.get_ref_p(), &d).get_xy(ec) == expected).then_some(d)
+    });
+
+    Ok(HnpReport {
+        signatures: m,
+        dimension: m + 1,
+        d_a,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use crate::sign_ecdsa::{sha256, SigningKey};
+    use num_bigint::RandBigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::time::Instant;
+
+    // a nonce source whose output never sets the top `zero_bits` bits
+    fn biased_signatures(
+        key: &SigningKey,
+        count: usize,
+        zero_bits: u64,
+        rng: &mut StdRng,
+    ) -> Vec<LeakySignature> {
+        let n = key.verifying_key().curve().get_ref_n().clone();
+        (0..count)
+            .map(|i| {
+                let digest = sha256(format!("message {i}").as_bytes());
+                let k = bui::from(rng.gen_biguint(n.bits() - zero_bits));
+                let (sign, _) = key.sign_digest_with_k(&digest, &k).unwrap();
+                LeakySignature {
+                    digest,
+                    sign,
+                    leak: bui::zero(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hnp_p256_zero_top_bits() {
+        let mut rng = StdRng::seed_from_u64(36);
+        let key = SigningKey::random_with_rng(curves::p256(), &mut rng);
+        let sigs = biased_signatures(&key, 12, 32, &mut rng);
+
+        let start = Instant::now();
+        let report = audit(key.verifying_key(), &sigs, 32, Reduction::Lll).unwrap();
+        println!("HNP, 12 signatures, LLL: {:?}", start.elapsed());
+        assert_eq!(report.signatures, 12);
+        assert_eq!(report.dimension, 13);
+        let d = report.d_a.unwrap();
+        assert_eq!(
+            SigningKey::from_scalar(curves::p256(), d)
+                .unwrap()
+                .verifying_key()
+                .as_point()
+                .get_xy(&curves::p256()),
+            key.verifying_key().as_point().get_xy(&curves::p256())
+        );
+
+        // without enough leakage the key stays out of reach
+        let report = audit(key.verifying_key(), &sigs[..6], 32, Reduction::Lll).unwrap();
+        assert!(!report.recoverable());
+    }
+
+    #[test]
+    fn test_hnp_p256_known_top_bits_bkz() {
+        let mut rng = StdRng::seed_from_u64(37);
+        let ec = curves::p256();
+        let key = SigningKey::random_with_rng(ec.clone(), &mut rng);
+        let n = ec.get_ref_n();
+        let bits = 20;
+        let shift = n.bits() - bits;
+
+        // full-range nonces whose top 20 bits are leaked, e.g. by timing
+        let sigs: Vec<LeakySignature> = (0..16)
+            .map(|i| {
+                let digest = sha256(format!("leak {i}").as_bytes());
+                let k = rng.gen_bigint_range(&bui::one(), n);
+                let (sign, _) = key.sign_digest_with_k(&digest, &k).unwrap();
+                LeakySignature {
+                    digest,
+                    sign,
+                    leak: k >> shift,
+                }
+            })
+            .collect();
+
+        let start = Instant::now();
+        let report = audit(key.verifying_key(), &sigs, bits, Reduction::Bkz(10)).unwrap();
+        println!("HNP, 16 signatures, BKZ-10: {:?}", start.elapsed());
+        assert!(report.recoverable());
+    }
+
+    #[test]
+    fn test_unbiased_nonces_are_not_recoverable() {
+        let mut rng = StdRng::seed_from_u64(38);
+        let key = SigningKey::random_with_rng(curves::p256(), &mut rng);
+        let sigs = biased_signatures(&key, 12, 0, &mut rng);
+        let report = audit(key.verifying_key(), &sigs, 32, Reduction::Lll).unwrap();
+        assert!(!report.recoverable());
+    }
+}
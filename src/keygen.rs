@@ -0,0 +1,189 @@
+use crate::binary_ec::{BinPoint, BinaryCurve};
+use crate::ec::{Point, EC};
+
+use num_bigint::BigInt as bui;
+use num_bigint::RandBigInt;
+use num_traits::One;
+use rand::{CryptoRng, RngCore};
+
+// FIPS 186-5, Appendix A.2
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyGenMethod {
+    // A.2.1: d = (c mod (n - 1)) + 1 for a random c of N + 64 bits
+    #[default]
+    ExtraRandomBits,
+    // A.2.2: a random c of N bits is rejected while c > n - 2, then d = c + 1
+    TestingCandidates,
+}
+
+pub fn gen_key_pair(ec: &EC, method: KeyGenMethod) -> Result<(bui, Point), String> {
+    gen_key_pair_with_rng(ec, method, &mut rand::thread_rng())
+}
+
+pub fn gen_key_pair_with_rng(
+    ec: &EC,
+    method: KeyGenMethod,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(bui, Point), String> {
+    let d = gen_private_key(ec.get_ref_n(), method, rng);
+    let q = ec.scalar_mul(ec.get_ref_p(), &d);
+
+    pairwise_consistency_test(ec, &d, &q)?;
+    Ok((d, q))
+}
+
+// The same routines over a binary curve
+pub fn gen_binary_key_pair_with_rng(
+    curve: &BinaryCurve,
+    method: KeyGenMethod,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(bui, BinPoint), String> {
+    let d = gen_private_key(curve.get_ref_n(), method, rng);
+    let q = curve.scalar_mul(curve.get_ref_p(), &d);
+
+    binary_pairwise_consistency_test(curve, &d, &q)?;
+    Ok((d, q))
+}
+
+fn gen_private_key(n: &bui, method: KeyGenMethod, rng: &mut (impl RngCore + CryptoRng)) -> bui {
+    let len = n.bits();
+
+    match method {
+        KeyGenMethod::ExtraRandomBits => {
+            let c = bui::from(rng.gen_biguint(len + 64));
+            c % (n - 1) + 1
+        }
+        KeyGenMethod::TestingCandidates => loop {
+            let c = bui::from(rng.gen_biguint(len));
+            if c <= n - 2 {
+                break c + 1;
+            }
+        },
+    }
+}
+
+// SP 800-56A Rev. 3, 5.6.2.1.4: d in [1, n - 1], Q a valid public key and Q = dG
+pub fn pairwise_consistency_test(ec: &EC, d: &bui, q: &Point) -> Result<(), String> {
+    if *d < bui::one() || d >= ec.get_ref_n() {
+        return Err(String::from("Private key is out of range"));
+    }
+    ec.validate_public_key(q)?;
+    let expected = ec.scalar_mul(ec.get_ref_p(), d);
+    if expected.get_xy(ec) != q.get_xy(ec) {
+        return Err(String::from("Pairwise consistency test failed"));
+    }
+    Ok(())
+}
+
+pub fn binary_pairwise_consistency_test(
+    curve: &BinaryCurve,
+    d: &bui,
+    q: &BinPoint,
+) -> Result<(), String> {
+    if *d < bui::one() || d >= curve.get_ref_n() {
+        return Err(String::from("Private key is out of range"));
+    }
+    curve.validate_public_key(q)?;
+    if curve.scalar_mul(curve.get_ref_p(), d) != *q {
+        return Err(String::from("Pairwise consistency test failed"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::FromHex;
+    use crate::curves;
+    use num_traits::Zero;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_gen_key_pair() {
+        for method in [
+            KeyGenMethod::ExtraRandomBits,
+            KeyGenMethod::TestingCandidates,
+        ] {
+            let ec = curves::p256();
+            for _ in 0..10 {
+                let (d, q) = gen_key_pair(&ec, method).unwrap();
+                assert!(d >= bui::one() && &d < ec.get_ref_n());
+                assert!(pairwise_consistency_test(&ec, &d, &q).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_gen_key_pair_range() {
+        let ec = curves::toy_463();
+        for method in [
+            KeyGenMethod::ExtraRandomBits,
+            KeyGenMethod::TestingCandidates,
+        ] {
+            let mut seen = vec![false; 421];
+            for _ in 0..20000 {
+                let (d, _) = gen_key_pair(&ec, method).unwrap();
+                let d: usize = d.try_into().unwrap();
+                seen[d] = true;
+            }
+            assert!(!seen[0]);
+            assert!(seen[1..].iter().all(|&s| s), "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn test_pairwise_consistency_test() {
+        let ec = curves::p256();
+        let (d, q) = gen_key_pair(&ec, KeyGenMethod::default()).unwrap();
+        let other = ec.scalar_mul(ec.get_ref_p(), &(&d + 1));
+        assert!(pairwise_consistency_test(&ec, &d, &other).is_err());
+        assert!(pairwise_consistency_test(&ec, &bui::zero(), &q).is_err());
+        assert!(pairwise_consistency_test(&ec, ec.get_ref_n(), &q).is_err());
+    }
+
+    #[test]
+    fn test_gen_binary_key_pair() {
+        let mut rng = ChaCha20Rng::seed_from_u64(163);
+        for curve in [curves::k163(), curves::dstu4145(163).unwrap()] {
+            for method in [
+                KeyGenMethod::ExtraRandomBits,
+                KeyGenMethod::TestingCandidates,
+            ] {
+                let (d, q) = gen_binary_key_pair_with_rng(&curve, method, &mut rng).unwrap();
+                assert!(d >= bui::one() && &d < curve.get_ref_n());
+                assert_eq!(q, curve.scalar_mul(curve.get_ref_p(), &d));
+            }
+            let (d, q) =
+                gen_binary_key_pair_with_rng(&curve, KeyGenMethod::default(), &mut rng).unwrap();
+            let other = curve.scalar_mul(curve.get_ref_p(), &(&d + 1));
+            assert!(binary_pairwise_consistency_test(&curve, &d, &other).is_err());
+            assert!(binary_pairwise_consistency_test(&curve, &d, &curve.neg(&q)).is_err());
+            assert!(binary_pairwise_consistency_test(&curve, &bui::zero(), &q).is_err());
+        }
+    }
+
+    #[test]
+    fn test_gen_key_pair_seeded() {
+        let ec = curves::p256();
+        let expected = [
+            (
+                KeyGenMethod::ExtraRandomBits,
+                "487a8242ff310279bd11975358f9bd327900407550d1c9e7a5eef0b315bc04ef",
+            ),
+            (
+                KeyGenMethod::TestingCandidates,
+                "93ad8a16cb6c5f3bb6ed68bba06ee59c52fe9280fb5874ac124fcf4f53b70b8f",
+            ),
+        ];
+        for (method, d) in expected {
+            let mut rng = ChaCha20Rng::seed_from_u64(2);
+            let (d_a, q) = gen_key_pair_with_rng(&ec, method, &mut rng).unwrap();
+            assert_eq!(d_a, bui::from_hex(d).unwrap());
+            assert_eq!(
+                q.get_xy(&ec),
+                ec.scalar_mul(ec.get_ref_p(), &d_a).get_xy(&ec)
+            );
+        }
+    }
+}
@@ -0,0 +1,380 @@
+// τ-adic scalar multiplication on the Koblitz curves y^2 + x y = x^3 + a x^2 + 1,
+// a in {0, 1} (Solinas 2000; Hankerson, Menezes, Vanstone 3.4). Frobenius
+// τ(x, y) = (x^2, y^2) satisfies τ^2 = μ τ - 2 with μ = (-1)^(1 - a), so a
+// scalar rewritten in Z[τ] as a sum of u_i τ^i needs only squarings and
+// additions. The scalar is first reduced modulo δ = (τ^m - 1) / (τ - 1),
+// which kills the subgroup of order n = N(δ), so the expansion is about m
+// digits long. Digits are read as they come: this multiplier is not
+// constant-time, unlike the ladder in binary_ec.
+
+use crate::binary_ec::{BinPoint, BinaryCurve};
+
+use num_bigint::BigInt as bui;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+// r0 + r1 τ
+type Ztau = (bui, bui);
+
+#[derive(Clone, Debug)]
+pub struct Koblitz {
+    curve: BinaryCurve,
+    mu: i32,
+    delta: Ztau,
+}
+
+impl Koblitz {
+    pub fn new(curve: BinaryCurve) -> Result<Self, String> {
+        let a = curve.get_ref_a();
+        if !curve.get_ref_b().is_one() || !(a.is_zero() || a.is_one()) {
+            return Err(String::from("Not a Koblitz curve"));
+        }
+        let mu = if a.is_one() { 1 } else { -1 };
+
+        // δ = 1 + τ + ... + τ^(m - 1)
+        let mut kob = Self {
+            curve,
+            mu,
+            delta: (bui::zero(), bui::zero()),
+        };
+        let mut t = (bui::one(), bui::zero());
+        let mut delta = (bui::zero(), bui::zero());
+        for _ in 0..kob.curve.field().m() {
+            delta = (&delta.0 + &t.0, &delta.1 + &t.1);
+            t = kob.mul_tau(&t);
+        }
+        if kob.norm(&delta) != *kob.curve.get_ref_n() {
+            return Err(String::from("N(δ) is not the subgroup order"));
+        }
+        kob.delta = delta;
+        Ok(kob)
+    }
+
+    pub fn curve(&self) -> &BinaryCurve {
+        &self.curve
+    }
+
+    pub fn mu(&self) -> i32 {
+        self.mu
+    }
+
+    // (r0 + r1 τ) τ = -2 r1 + (r0 + μ r1) τ
+    fn mul_tau(&self, r: &Ztau) -> Ztau {
+        (-2 * &r.1, &r.0 + self.mu * &r.1)
+    }
+
+    // (a0 + a1 τ)(b0 + b1 τ) = a0 b0 - 2 a1 b1 + (a0 b1 + a1 b0 + μ a1 b1) τ
+    fn mul(&self, a: &Ztau, b: &Ztau) -> Ztau {
+        let a1b1 = &a.1 * &b.1;
+        (
+            &a.0 * &b.0 - 2 * &a1b1,
+            &a.0 * &b.1 + &a.1 * &b.0 + self.mu * a1b1,
+        )
+    }
+
+    // N(r0 + r1 τ) = r0^2 + μ r0 r1 + 2 r1^2
+    pub fn norm(&self, r: &Ztau) -> bui {
+        &r.0 * &r.0 + self.mu * &r.0 * &r.1 + 2 * &r.1 * &r.1
+    }
+
+    // the conjugate r0 + r1 τ', with τ' = μ - τ
+    fn conj(&self, r: &Ztau) -> Ztau {
+        (&r.0 + self.mu * &r.1, -&r.1)
+    }
+
+    // Round (λ0 + λ1 τ) to the nearest element of Z[τ] in the norm,
+    // Hankerson, Menezes, Vanstone algorithm 3.63
+    fn round(&self, l0: &BigRational, l1: &BigRational) -> Ztau {
+        let half = BigRational::new(bui::one(), bui::from(2));
+        let f0 = (l0 + &half).floor();
+        let f1 = (l1 + &half).floor();
+        let e0 = l0 - &f0;
+        let e1 = l1 - &f1;
+        let mu = BigRational::from_integer(bui::from(self.mu));
+        let int = |v: i32| BigRational::from_integer(bui::from(v));
+
+        let (mut h0, mut h1) = (0, 0);
+        let e = &e0 * int(2) + &mu * &e1;
+        if e >= int(1) {
+            if &e0 - int(3) * &mu * &e1 < int(-1) {
+                h1 = self.mu;
+            } else {
+                h0 = 1;
+            }
+        } else if &e0 + int(4) * &mu * &e1 >= int(2) {
+            h1 = self.mu;
+        }
+        if e < int(-1) {
+            if &e0 - int(3) * &mu * &e1 >= int(1) {
+                h1 = -self.mu;
+            } else {
+                h0 = -1;
+            }
+        } else if &e0 + int(4) * &mu * &e1 < int(-2) {
+            h1 = -self.mu;
+        }
+        (f0.to_integer() + h0, f1.to_integer() + h1)
+    }
+
+    // r - round(r / d) d, the division done exactly as r conj(d) / N(d)
+    fn reduce(&self, r: &Ztau, d: &Ztau) -> Ztau {
+        let num = self.mul(r, &self.conj(d));
+        let norm = self.norm(d);
+        let q = self.round(
+            &BigRational::new(num.0, norm.clone()),
+            &BigRational::new(num.1, norm),
+        );
+        let qd = self.mul(&q, d);
+        (&r.0 - qd.0, &r.1 - qd.1)
+    }
+
+    // ρ = k partmod δ: ρ P = k P on the subgroup of order n, and ρ has
+    // norm about n, so its τNAF is about m digits
+    pub fn partmod(&self, k: &bui) -> Ztau {
+        self.reduce(&(k.clone(), bui::zero()), &self.delta)
+    }
+
+    // τNAF of r0 + r1 τ, least significant digit first; digits are 0 or ±1
+    // and no two adjacent digits are non-zero
+    pub fn tnaf(&self, r: &Ztau) -> Vec<i8> {
+        let (mut r0, mut r1) = r.clone();
+        let mut digits = Vec::new();
+        while !r0.is_zero() || !r1.is_zero() {
+            let mut u = 0i8;
+            if r0.is_odd() {
+                // u = 2 - ((r0 - 2 r1) mod 4)
+                let v: bui = &r0 - 2 * &r1;
+                u = if v.mod_floor(&bui::from(4)).is_one() {
+                    1
+                } else {
+                    -1
+                };
+                r0 -= u;
+            }
+            digits.push(u);
+            // divide by τ: (r0 + r1 τ) / τ = r1 + μ r0 / 2 - (r0 / 2) τ
+            let half: bui = &r0 / 2;
+            (r0, r1) = (&r1 + self.mu * &half, -half);
+        }
+        digits
+    }
+
+    // t_w = 2 U_(w - 1) / U_w mod 2^w for the Lucas sequence U_0 = 0, U_1 = 1,
+    // U_(k + 1) = μ U_k - 2 U_(k - 1); τ maps to t_w under Z[τ]/(τ^w) = Z/2^w
+    fn t_w(&self, w: usize) -> bui {
+        let (mut u_prev, mut u) = (bui::zero(), bui::one());
+        for _ in 1..w {
+            (u_prev, u) = (u.clone(), self.mu * &u - 2 * u_prev);
+        }
+        let modulus = bui::one() << w;
+        let u_inv = u.extended_gcd(&modulus).x;
+        let t: bui = 2 * u_prev * u_inv;
+        t.mod_floor(&modulus)
+    }
+
+    // α_u = u mods τ^w for the odd u in [1, 2^(w - 1))
+    pub fn alphas(&self, w: usize) -> Vec<Ztau> {
+        let mut tau_w = (bui::one(), bui::zero());
+        for _ in 0..w {
+            tau_w = self.mul_tau(&tau_w);
+        }
+        (1..1 << (w - 1))
+            .step_by(2)
+            .map(|u| self.reduce(&(bui::from(u), bui::zero()), &tau_w))
+            .collect()
+    }
+
+    // width-w τNAF, least significant digit first: a digit u stands for α_u,
+    // or -α_(-u) when negative, and any w consecutive digits hold at most one
+    // non-zero (Hankerson, Menezes, Vanstone algorithm 3.69)
+    pub fn wtnaf(&self, r: &Ztau, w: usize) -> Result<Vec<i8>, String> {
+        if !(2..=7).contains(&w) {
+            return Err(String::from("Width must be in [2, 7]"));
+        }
+        let alphas = self.alphas(w);
+        let t_w = self.t_w(w);
+        let modulus = bui::one() << w;
+        let half_modulus = bui::one() << (w - 1);
+
+        let (mut r0, mut r1) = r.clone();
+        let mut digits = Vec::new();
+        while !r0.is_zero() || !r1.is_zero() {
+            let mut u = 0i8;
+            if r0.is_odd() {
+                // u = (r0 + r1 t_w) mods 2^w
+                let mut v = (&r0 + &r1 * &t_w).mod_floor(&modulus);
+                if v >= half_modulus {
+                    v -= &modulus;
+                }
+                u = i8::try_from(v).unwrap();
+                let alpha = &alphas[(u.unsigned_abs() / 2) as usize];
+                if u > 0 {
+                    r0 -= &alpha.0;
+                    r1 -= &alpha.1;
+                } else {
+                    r0 += &alpha.0;
+                    r1 += &alpha.1;
+                }
+            }
+            digits.push(u);
+            let half: bui = &r0 / 2;
+            (r0, r1) = (&r1 + self.mu * &half, -half);
+        }
+        Ok(digits)
+    }
+
+    // k P for P in the subgroup of order n, with a width-w τNAF: the table
+    // holds α_u P for the odd u < 2^(w - 1), then Horner's rule in τ
+    pub fn scalar_mul_w(&self, p: &BinPoint, k: &bui, w: usize) -> Result<BinPoint, String> {
+        let curve = &self.curve;
+        let k = k.mod_floor(curve.get_ref_n());
+        let digits = self.wtnaf(&self.partmod(&k), w)?;
+
+        // α_u P = β P + γ τ(P)
+        let tau_p = curve.to_affine(&curve.ld_frobenius(&curve.to_ld(p)));
+        let table: Vec<BinPoint> = self
+            .alphas(w)
+            .iter()
+            .map(|(beta, gamma)| {
+                let term = |k: &bui, q: &BinPoint| {
+                    let kq = curve.scalar_mul(q, &k.abs());
+                    if k.is_negative() {
+                        curve.neg(&kq)
+                    } else {
+                        kq
+                    }
+                };
+                curve.add(&term(beta, p), &term(gamma, &tau_p))
+            })
+            .collect();
+
+        let mut acc = curve.ld_infinity();
+        for &u in digits.iter().rev() {
+            acc = curve.ld_frobenius(&acc);
+            if u != 0 {
+                let q = &table[(u.unsigned_abs() / 2) as usize];
+                let q = if u > 0 { q.clone() } else { curve.neg(q) };
+                acc = curve.ld_add_mixed(&acc, &q);
+            }
+        }
+        Ok(curve.to_affine(&acc))
+    }
+
+    pub fn scalar_mul(&self, p: &BinPoint, k: &bui) -> BinPoint {
+        self.scalar_mul_w(p, k, 5).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use num_bigint::RandBigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::time::Instant;
+
+    // the value of a digit string in Z[τ], digit u standing for α_u
+    fn evaluate(kob: &Koblitz, digits: &[i8], alphas: &[Ztau]) -> Ztau {
+        let mut acc = (bui::zero(), bui::zero());
+        for &u in digits.iter().rev() {
+            acc = kob.mul_tau(&acc);
+            if u != 0 {
+                let (b, g) = &alphas[(u.unsigned_abs() / 2) as usize];
+                let sign = i32::from(u.signum());
+                acc = (acc.0 + sign * b, acc.1 + sign * g);
+            }
+        }
+        acc
+    }
+
+    // generic left-to-right double-and-add in affine coordinates
+    fn double_and_add(curve: &BinaryCurve, p: &BinPoint, k: &bui) -> BinPoint {
+        let mut acc = BinPoint::Infinity;
+        for bit in format!("{:b}", k).chars() {
+            acc = curve.double(&acc);
+            if bit == '1' {
+                acc = curve.add(&acc, p);
+            }
+        }
+        acc
+    }
+
+    #[test]
+    fn test_recoding() {
+        let mut rng = StdRng::seed_from_u64(42);
+        // μ = 1 for a = 1 (K-163) and -1 for a = 0 (K-233)
+        for (kob, mu) in [
+            (Koblitz::new(curves::k163()).unwrap(), 1),
+            (Koblitz::new(curves::k233()).unwrap(), -1),
+        ] {
+            assert_eq!(kob.mu(), mu);
+            let m = kob.curve().field().m();
+            for _ in 0..4 {
+                let k = rng.gen_bigint_range(&bui::one(), kob.curve().get_ref_n());
+                let rho = kob.partmod(&k);
+                // ρ ≡ k mod δ, and ρ is short
+                let diff = (&k - &rho.0, -&rho.1);
+                let q = kob.mul(&diff, &kob.conj(&kob.delta));
+                let n = kob.curve().get_ref_n();
+                assert!(q.0.is_multiple_of(n) && q.1.is_multiple_of(n));
+
+                let naf = kob.tnaf(&rho);
+                assert!(naf.len() <= m + 5, "{} digits for m = {m}", naf.len());
+                assert!(naf.windows(2).all(|d| d[0] == 0 || d[1] == 0));
+                assert_eq!(evaluate(&kob, &naf, &kob.alphas(2)), rho);
+                assert_eq!(kob.wtnaf(&rho, 2).unwrap(), naf);
+
+                for w in 3..=6 {
+                    let digits = kob.wtnaf(&rho, w).unwrap();
+                    assert!(digits
+                        .windows(w)
+                        .all(|d| d.iter().filter(|&&u| u != 0).count() <= 1));
+                    assert_eq!(evaluate(&kob, &digits, &kob.alphas(w)), rho);
+                }
+            }
+        }
+        assert!(Koblitz::new(curves::b163()).is_err());
+    }
+
+    #[test]
+    fn test_against_double_and_add() {
+        let mut rng = StdRng::seed_from_u64(163);
+        for curve in [
+            curves::k163(),
+            curves::k233(),
+            curves::k283(),
+            curves::k409(),
+            curves::k571(),
+        ] {
+            let kob = Koblitz::new(curve.clone()).unwrap();
+            let p = curve.scalar_mul(curve.get_ref_p(), &bui::from(rng.gen_biguint(64)));
+            let k = rng.gen_bigint_range(&bui::one(), curve.get_ref_n());
+            let expected = double_and_add(&curve, &p, &k);
+            assert_eq!(kob.scalar_mul(&p, &k), expected);
+            assert_eq!(kob.scalar_mul_w(&p, &k, 2).unwrap(), expected);
+            // reduction mod n first, and the edge scalars
+            let n = curve.get_ref_n();
+            assert_eq!(kob.scalar_mul(&p, &(&k + n)), expected);
+            assert!(kob.scalar_mul(&p, n).is_infinity());
+            assert_eq!(kob.scalar_mul(&p, &(n - 1u32)), curve.neg(&p));
+        }
+    }
+
+    #[test]
+    fn test_speedup() {
+        let curve = curves::k283();
+        let kob = Koblitz::new(curve.clone()).unwrap();
+        let p = curve.get_ref_p();
+        let k = curve.get_ref_n() - 12345u32;
+
+        let start = Instant::now();
+        let ladder = curve.scalar_mul(p, &k);
+        let ladder_time = start.elapsed();
+        let start = Instant::now();
+        let tnaf = kob.scalar_mul(p, &k);
+        let tnaf_time = start.elapsed();
+        assert_eq!(ladder, tnaf);
+        println!("K-283 ladder: {ladder_time:?}, width-5 τNAF: {tnaf_time:?}");
+    }
+}
@@ -0,0 +1,435 @@
+// Lattice basis reduction in exact arithmetic: LLL (Cohen, Algorithm 2.6.7)
+// and Schnorr-Euchner BKZ. Bases are lists of linearly independent integer
+// row vectors.
+
+use num_bigint::BigInt as bui;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+// Gram-Schmidt coefficients mu[i][j] = <b_i, b*_j> / B_j (j < i) and the
+// squared lengths B_i = |b*_i|^2
+pub struct Gso {
+    pub mu: Vec<Vec<BigRational>>,
+    pub b: Vec<BigRational>,
+}
+
+fn dot(u: &[bui], v: &[bui]) -> bui {
+    u.iter().zip(v).map(|(a, b)| a * b).sum()
+}
+
+fn sub_mul(u: &mut [bui], v: &[bui], q: &bui) {
+    for (a, b) in u.iter_mut().zip(v) {
+        *a -= q * b;
+    }
+}
+
+fn ratio(n: bui) -> BigRational {
+    BigRational::from_integer(n)
+}
+
+// mu[k][j] for all j < k and B_k from the Gram matrix row of b_k
+fn gso_row(basis: &[Vec<bui>], gso: &mut Gso, k: usize) {
+    for j in 0..k {
+        let mut s = ratio(dot(&basis[k], &basis[j]));
+        for i in 0..j {
+            s -= &gso.mu[j][i] * &gso.mu[k][i] * &gso.b[i];
+        }
+        gso.mu[k][j] = s / &gso.b[j];
+    }
+    let mut s = ratio(dot(&basis[k], &basis[k]));
+    for j in 0..k {
+        s -= &gso.mu[k][j] * &gso.mu[k][j] * &gso.b[j];
+    }
+    gso.b[k] = s;
+}
+
+pub fn gram_schmidt(basis: &[Vec<bui>]) -> Gso {
+    let n = basis.len();
+    let mut gso = Gso {
+        mu: vec![vec![BigRational::zero(); n]; n],
+        b: vec![BigRational::zero(); n],
+    };
+    for k in 0..n {
+        gso_row(basis, &mut gso, k);
+    }
+    gso
+}
+
+// Integral LLL state (Cohen, Algorithm 2.6.7): d[i] is the Gram determinant
+// of b_0..b_{i-1}, so B_i = d[i + 1] / d[i], and lambda[i][j] = d[j + 1] mu[i][j].
+// Every quantity stays an integer and the exact rationals are read back at
+// the end.
+struct Integral {
+    d: Vec<bui>,
+    lambda: Vec<Vec<bui>>,
+}
+
+impl Integral {
+    fn to_gso(&self) -> Gso {
+        let n = self.lambda.len();
+        Gso {
+            mu: (0..n)
+                .map(|i| {
+                    (0..n)
+                        .map(|j| {
+                            if j < i {
+                                BigRational::new(self.lambda[i][j].clone(), self.d[j + 1].clone())
+                            } else {
+                                BigRational::zero()
+                            }
+                        })
+                        .collect()
+                })
+                .collect(),
+            b: (0..n)
+                .map(|i| BigRational::new(self.d[i + 1].clone(), self.d[i].clone()))
+                .collect(),
+        }
+    }
+}
+
+// Adds b_k to the Gram-Schmidt data (step 2 of Algorithm 2.6.7)
+fn integral_row(basis: &[Vec<bui>], st: &mut Integral, k: usize) {
+    for j in 0..=k {
+        let mut u = dot(&basis[k], &basis[j]);
+        for i in 0..j {
+            u = (&st.d[i + 1] * u - &st.lambda[k][i] * &st.lambda[j][i]) / &st.d[i];
+        }
+        if j < k {
+            st.lambda[k][j] = u;
+        } else {
+            st.d[k + 1] = u;
+        }
+    }
+}
+
+// Size reduction of b_k against b_l
+fn reduce(basis: &mut [Vec<bui>], st: &mut Integral, k: usize, l: usize) {
+    let d = &st.d[l + 1];
+    if (&st.lambda[k][l] * 2u32).abs() <= *d {
+        return;
+    }
+    // nearest integer to lambda / d
+    let q = (&st.lambda[k][l] * 2u32 + d).div_floor(&(d * 2u32));
+    let (low, high) = basis.split_at_mut(k);
+    sub_mul(&mut high[0], &low[l], &q);
+
+    st.lambda[k][l] -= &q * d;
+    for i in 0..l {
+        let t = &q * &st.lambda[l][i];
+        st.lambda[k][i] -= t;
+    }
+}
+
+// Exchanges b_{k-1} and b_k, updating lambda and d for rows up to kmax
+fn swap(basis: &mut [Vec<bui>], st: &mut Integral, k: usize, kmax: usize) {
+    basis.swap(k - 1, k);
+    for j in 0..k - 1 {
+        let t = st.lambda[k][j].clone();
+        st.lambda[k][j] = st.lambda[k - 1][j].clone();
+        st.lambda[k - 1][j] = t;
+    }
+
+    let lambda = st.lambda[k][k - 1].clone();
+    let b = (&st.d[k - 1] * &st.d[k + 1] + &lambda * &lambda) / &st.d[k];
+    for i in k + 1..=kmax {
+        let t = st.lambda[i][k].clone();
+        st.lambda[i][k] = (&st.d[k + 1] * &st.lambda[i][k - 1] - &lambda * &t) / &st.d[k];
+        st.lambda[i][k - 1] = (&b * t + &lambda * &st.lambda[i][k]) / &st.d[k + 1];
+    }
+    st.d[k] = b;
+}
+
+// LLL with Lovasz parameter delta in (1/4, 1]
+pub fn lll(basis: &mut [Vec<bui>], delta: &BigRational) -> Gso {
+    let n = basis.len();
+    let mut st = Integral {
+        d: vec![bui::one(); n + 1],
+        lambda: vec![vec![bui::zero(); n]; n],
+    };
+    if n == 0 {
+        return st.to_gso();
+    }
+    integral_row(basis, &mut st, 0);
+    let (p, q) = (delta.numer(), delta.denom());
+
+    let mut k = 1;
+    let mut kmax = 0;
+    while k < n {
+        if k > kmax {
+            kmax = k;
+            integral_row(basis, &mut st, k);
+        }
+        reduce(basis, &mut st, k, k - 1);
+        // B_k < (delta - mu^2) B_{k-1} with delta = p / q, cleared of
+        // denominators
+        let lambda = &st.lambda[k][k - 1];
+        if q * &st.d[k + 1] * &st.d[k - 1] < p * &st.d[k] * &st.d[k] - q * lambda * lambda {
+            swap(basis, &mut st, k, kmax);
+            k = (k - 1).max(1);
+        } else {
+            for l in (0..k - 1).rev() {
+                reduce(basis, &mut st, k, l);
+            }
+            k += 1;
+        }
+    }
+    st.to_gso()
+}
+
+// Schnorr-Euchner enumeration of the shortest nonzero vector of the lattice
+// projected orthogonally to b_0..b_{start-1}, restricted to b_start..b_end.
+// Runs in floating point on B and mu scaled by B_start; returns the integer
+// coefficients when a vector shorter than `bound` * B_start exists.
+fn enumerate(gso: &Gso, start: usize, end: usize, bound: f64) -> Option<Vec<i64>> {
+    let s = end - start;
+    let scale = &gso.b[start];
+    let b: Vec<f64> = (start..end)
+        .map(|i| (&gso.b[i] / scale).to_f64().unwrap())
+        .collect();
+    let mu: Vec<Vec<f64>> = (start..end)
+        .map(|i| {
+            (start..end)
+                .map(|j| gso.mu[i][j].to_f64().unwrap())
+                .collect()
+        })
+        .collect();
+
+    struct Search<'a> {
+        b: &'a [f64],
+        mu: &'a [Vec<f64>],
+        x: Vec<i64>,
+        best: Option<Vec<i64>>,
+        radius: f64,
+    }
+
+    fn descend(search: &mut Search, i: usize, partial: f64, zero_above: bool) {
+        let center: f64 = -(i + 1..search.x.len())
+            .map(|t| search.x[t] as f64 * search.mu[t][i])
+            .sum::<f64>();
+        let first = center.round() as i64;
+
+        // zig-zag around the center; above the first nonzero coefficient only
+        // one sign is searched since v and -v have the same length
+        let mut step = 0i64;
+        let mut closed = [false, false];
+        while !(closed[0] && closed[1]) {
+            let side = if step % 2 == 0 { 0 } else { 1 };
+            let offset = (step + 1) / 2;
+            step += 1;
+            if closed[side] {
+                continue;
+            }
+            let x = if side == 0 {
+                first + offset
+            } else {
+                first - offset
+            };
+            if zero_above && x < 0 {
+                closed[side] = true;
+                continue;
+            }
+            let d = x as f64 - center;
+            let length = partial + d * d * search.b[i];
+            if length >= search.radius {
+                closed[side] = true;
+                continue;
+            }
+            search.x[i] = x;
+            if i == 0 {
+                if !(zero_above && x == 0) {
+                    search.radius = length;
+                    search.best = Some(search.x.clone());
+                }
+            } else {
+                descend(search, i - 1, length, zero_above && x == 0);
+            }
+        }
+        search.x[i] = 0;
+    }
+
+    let mut search = Search {
+        b: &b,
+        mu: &mu,
+        x: vec![0; s],
+        best: None,
+        radius: bound,
+    };
+    descend(&mut search, s - 1, 0.0, true);
+    search.best
+}
+
+// Rewrites b_start..b_end by a unimodular transform so that b_start becomes
+// sum x_i b_i; x must be primitive
+fn insert(basis: &mut [Vec<bui>], start: usize, x: &[i64]) {
+    let mut x: Vec<bui> = x.iter().map(|&c| bui::from(c)).collect();
+    for i in (1..x.len()).rev() {
+        if x[i].is_zero() {
+            continue;
+        }
+        let (a, b) = (x[i - 1].clone(), x[i].clone());
+        let e = a.extended_gcd(&b);
+        let (i0, i1) = (start + i - 1, start + i);
+        let c1: Vec<bui> = basis[i0]
+            .iter()
+            .zip(&basis[i1])
+            .map(|(u, v)| (&a / &e.gcd) * u + (&b / &e.gcd) * v)
+            .collect();
+        let c2: Vec<bui> = basis[i0]
+            .iter()
+            .zip(&basis[i1])
+            .map(|(u, v)| -&e.y * u + &e.x * v)
+            .collect();
+        basis[i0] = c1;
+        basis[i1] = c2;
+        x[i - 1] = e.gcd;
+        x[i] = bui::zero();
+    }
+    if x[0].is_negative() {
+        for c in basis[start].iter_mut() {
+            *c = -&*c;
+        }
+    }
+}
+
+// BKZ with the given block size; stops after a full tour without changes or
+// after `max_tours` tours
+pub fn bkz(basis: &mut [Vec<bui>], block: usize, delta: &BigRational, max_tours: usize) -> Gso {
+    let n = basis.len();
+    let mut gso = lll(basis, delta);
+    let bound = delta.to_f64().unwrap();
+
+    for _ in 0..max_tours {
+        let mut changed = false;
+        for k in 0..n.saturating_sub(1) {
+            let end = (k + block).min(n);
+            let Some(mut x) = enumerate(&gso, k, end, bound) else {
+                continue;
+            };
+            let g = x.iter().fold(0i64, |g, &c| g.gcd(&c));
+            x.iter_mut().for_each(|c| *c /= g);
+
+            insert(basis, k, &x);
+            gso = lll(basis, delta);
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    gso
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn three_quarters() -> BigRational {
+        BigRational::new(bui::from(3), bui::from(4))
+    }
+
+    fn basis(rows: &[&[i64]]) -> Vec<Vec<bui>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&c| bui::from(c)).collect())
+            .collect()
+    }
+
+    fn det(basis: &[Vec<bui>]) -> BigRational {
+        gram_schmidt(basis)
+            .b
+            .iter()
+            .fold(BigRational::one(), |acc, b| acc * b)
+    }
+
+    fn is_lll_reduced(basis: &[Vec<bui>], delta: &BigRational) -> bool {
+        let gso = gram_schmidt(basis);
+        let half = BigRational::new(bui::one(), bui::from(2));
+        let size_reduced = (0..basis.len()).all(|i| (0..i).all(|j| gso.mu[i][j].abs() <= half));
+        let lovasz = (1..basis.len())
+            .all(|k| gso.b[k] >= (delta - &gso.mu[k][k - 1] * &gso.mu[k][k - 1]) * &gso.b[k - 1]);
+        size_reduced && lovasz
+    }
+
+    #[test]
+    fn test_lll_textbook() {
+        let mut b = basis(&[&[1, 1, 1], &[-1, 0, 2], &[3, 5, 6]]);
+        let before = det(&b);
+        let delta = three_quarters();
+        let gso = lll(&mut b, &delta);
+        assert!(is_lll_reduced(&b, &delta));
+        assert_eq!(det(&b), before);
+        let reference = gram_schmidt(&b);
+        assert_eq!(gso.b, reference.b);
+        assert_eq!(gso.mu, reference.mu);
+    }
+
+    #[test]
+    fn test_lll_random() {
+        let mut rng = StdRng::seed_from_u64(34);
+        let delta = BigRational::new(bui::from(99), bui::from(100));
+        for _ in 0..5 {
+            let mut b: Vec<Vec<bui>> = (0..8)
+                .map(|_| {
+                    (0..8)
+                        .map(|_| bui::from(rng.gen_range(-1000..1000)))
+                        .collect()
+                })
+                .collect();
+            let before = det(&b);
+            lll(&mut b, &delta);
+            assert!(is_lll_reduced(&b, &delta));
+            assert_eq!(det(&b), before);
+        }
+    }
+
+    #[test]
+    fn test_bkz_finds_planted_short_vector() {
+        // mod-q knapsack with a planted short solution: sum s_i a_i = t mod q
+        // gives the lattice vector (s, -1, 0). BKZ with a full block is an
+        // exact SVP solver
+        let mut rng = StdRng::seed_from_u64(35);
+        let n = 10;
+        let q: i64 = 1_000_003;
+        let secret: Vec<i64> = (0..n).map(|_| rng.gen_range(-2..=2)).collect();
+        let a: Vec<i64> = (0..n).map(|_| rng.gen_range(0..q)).collect();
+        let target = secret
+            .iter()
+            .zip(&a)
+            .map(|(s, a)| s * a)
+            .sum::<i64>()
+            .rem_euclid(q);
+
+        let weighted = |v: i64| bui::from(v) * 1000;
+        let mut b = Vec::new();
+        for i in 0..n {
+            let mut row = vec![bui::zero(); n + 2];
+            row[i] = bui::one();
+            row[n + 1] = weighted(a[i]);
+            b.push(row);
+        }
+        let mut row = vec![bui::zero(); n + 2];
+        row[n] = bui::one();
+        row[n + 1] = weighted(target);
+        b.push(row);
+        let mut row = vec![bui::zero(); n + 2];
+        row[n + 1] = weighted(q);
+        b.push(row);
+
+        let before = det(&b);
+        let delta = BigRational::new(bui::from(99), bui::from(100));
+        let gso = bkz(&mut b, n + 2, &delta, 10);
+        assert!(is_lll_reduced(&b, &delta));
+        assert_eq!(det(&b), before);
+
+        // the returned Gram-Schmidt data describes the reduced basis
+        let fresh = gram_schmidt(&b);
+        assert_eq!(gso.b, fresh.b);
+        assert_eq!(gso.mu, fresh.mu);
+        assert_eq!(gso.b[0], ratio(dot(&b[0], &b[0])));
+
+        let planted_norm: i64 = secret.iter().map(|s| s * s).sum::<i64>() + 1;
+        assert!(dot(&b[0], &b[0]) <= bui::from(planted_norm));
+    }
+}
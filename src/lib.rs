@@ -0,0 +1,33 @@
+pub mod bigint_utils;
+pub mod binary_ec;
+pub mod cavp;
+pub mod curves;
+pub mod der;
+pub mod dh_exchange;
+pub mod dstu4145;
+pub mod ec;
+pub mod ecgdsa;
+pub mod eckcdsa;
+pub mod ed25519;
+pub mod edwards;
+pub mod elgamal;
+pub mod gf2m;
+pub mod glv;
+pub mod gost3410;
+pub mod hnp_audit;
+pub mod keygen;
+pub mod koblitz;
+pub mod lattice;
+pub mod montgomery;
+pub mod nonce_audit;
+pub mod nonce_pool;
+pub mod schnorr;
+pub mod sign_ecdsa;
+pub mod sign_ecdsa_binary;
+pub mod signature;
+pub mod sm2;
+pub mod sm3;
+pub mod streaming;
+pub mod streebog;
+pub mod trg_enc;
+pub mod wycheproof;
@@ -1,15 +1,6 @@
-mod bigint_utils;
-mod dh_exchange;
-mod ec;
-mod sign_ecdsa;
-mod trg_enc;
 use std::char;
-use std::result;
 
-use bigint_utils::FromHex;
-use ec::EC;
 use num_bigint::BigInt as bui;
-use num_bigint::BigUint;
 use num_bigint::Sign;
 use sha2::{Digest, Sha256};
 
@@ -0,0 +1,244 @@
+// Montgomery curves v^2 = u^3 + A u^2 + u with x-only (u, with Z) ladder
+// arithmetic, and the RFC 7748 X25519 and X448 functions. Scalars and
+// u-coordinates are little-endian byte strings.
+
+use crate::bigint_utils::modulo;
+use crate::curves;
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use num_traits::{One, Zero};
+
+#[derive(Clone, Debug)]
+pub struct Montgomery {
+    q: bui,
+    a24: bui,    // (A - 2) / 4
+    bits: usize, // scalar and u-coordinate size in bits
+    h_bits: u32, // log2 of the cofactor, cleared by clamping
+    u: bui,      // base point
+}
+
+impl Montgomery {
+    pub fn new(q: bui, a24: u32, bits: usize, h_bits: u32, u: u32) -> Self {
+        Self {
+            q,
+            a24: bui::from(a24),
+            bits,
+            h_bits,
+            u: bui::from(u),
+        }
+    }
+
+    pub fn get_ref_q(&self) -> &bui {
+        &self.q
+    }
+
+    pub fn byte_len(&self) -> usize {
+        self.bits.div_ceil(8)
+    }
+
+    // RFC 7748, 5: clear the cofactor bits, set the top bit
+    pub fn clamp(&self, k: &[u8]) -> Result<bui, String> {
+        if k.len() != self.byte_len() {
+            return Err(format!("Scalar must be {} bytes", self.byte_len()));
+        }
+        let mut k = bui::from_bytes_le(Sign::Plus, k);
+        k &= (bui::one() << self.bits) - (bui::one() << self.h_bits);
+        k |= bui::one() << (self.bits - 1);
+        Ok(k)
+    }
+
+    // Unused top bits are masked; non-canonical values, q and up, are
+    // accepted and reduced
+    pub fn decode_u(&self, u: &[u8]) -> Result<bui, String> {
+        if u.len() != self.byte_len() {
+            return Err(format!("u-coordinate must be {} bytes", self.byte_len()));
+        }
+        let u = bui::from_bytes_le(Sign::Plus, u) & ((bui::one() << self.bits) - 1);
+        Ok(u % &self.q)
+    }
+
+    pub fn encode_u(&self, u: &bui) -> Vec<u8> {
+        let mut out = u.to_bytes_le().1;
+        out.resize(self.byte_len(), 0);
+        out
+    }
+
+    pub fn base_u(&self) -> Vec<u8> {
+        self.encode_u(&self.u)
+    }
+
+    // RFC 7748, 5: the ladder over (x_2 : z_2) = [j] u and (x_3 : z_3) =
+    // [j + 1] u, swapped by the scalar bits
+    pub fn ladder(&self, k: &bui, u: &bui) -> bui {
+        let q = &self.q;
+        let x_1 = u.clone();
+        let (mut x_2, mut z_2) = (bui::one(), bui::zero());
+        let (mut x_3, mut z_3) = (u.clone(), bui::one());
+        let mut swap = false;
+
+        for t in (0..self.bits).rev() {
+            let k_t = k.bit(t as u64);
+            if swap != k_t {
+                std::mem::swap(&mut x_2, &mut x_3);
+                std::mem::swap(&mut z_2, &mut z_3);
+            }
+            swap = k_t;
+
+            let a = &x_2 + &z_2;
+            let aa = a.pow(2);
+            let b = &x_2 - &z_2;
+            let bb = b.pow(2);
+            let e = &aa - &bb;
+            let c = &x_3 + &z_3;
+            let d = &x_3 - &z_3;
+            let da = d * a;
+            let cb = c * b;
+            x_3 = modulo(&(&da + &cb).pow(2), q);
+            z_3 = modulo(&(&x_1 * (da - cb).pow(2)), q);
+            z_2 = modulo(&(&e * (&aa + &self.a24 * &e)), q);
+            x_2 = modulo(&(aa * bb), q);
+        }
+        if swap {
+            std::mem::swap(&mut x_2, &mut x_3);
+            std::mem::swap(&mut z_2, &mut z_3);
+        }
+
+        (x_2 * z_2.modpow(&(q - 2u32), q)) % q
+    }
+
+    // X25519(k, u) / X448(k, u); an all-zero output is returned as is
+    pub fn scalar_mul(&self, k: &[u8], u: &[u8]) -> Result<Vec<u8>, String> {
+        let k = self.clamp(k)?;
+        let u = self.decode_u(u)?;
+        Ok(self.encode_u(&self.ladder(&k, &u)))
+    }
+}
+
+pub fn x25519(k: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    curves::curve25519()
+        .scalar_mul(k, u)
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+pub fn x448(k: &[u8; 56], u: &[u8; 56]) -> [u8; 56] {
+    curves::curve448()
+        .scalar_mul(k, u)
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes<const N: usize>(s: &str) -> [u8; N] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_curve_params() {
+        // RFC 7748, 4.1 and 4.2
+        let p_25519 = (bui::one() << 255) - 19;
+        let p_448 = (bui::one() << 448) - (bui::one() << 224) - 1;
+        assert_eq!(curves::curve25519().get_ref_q(), &p_25519);
+        assert_eq!(curves::curve448().get_ref_q(), &p_448);
+    }
+
+    #[test]
+    fn test_rfc7748_vectors() {
+        // RFC 7748, 5.2
+        assert_eq!(
+            x25519(
+                &bytes("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                &bytes("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c"),
+            ),
+            bytes::<32>("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552")
+        );
+        // the top bit of u is masked
+        assert_eq!(
+            x25519(
+                &bytes("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d"),
+                &bytes("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493"),
+            ),
+            bytes::<32>("95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957")
+        );
+        assert_eq!(
+            x448(
+                &bytes("3d262fddf9ec8e88495266fea19a34d28882acef045104d0d1aae121700a779c984c24f8cdd78fbff44943eba368f54b29259a4f1c600ad3"),
+                &bytes("06fce640fa3487bfda5f6cf2d5263f8aad88334cbd07437f020f08f9814dc031ddbdc38c19c6da2583fa5429db94ada18aa7a7fb4ef8a086"),
+            ),
+            bytes::<56>("ce3e4ff95a60dc6697da1db1d85e6afbdf79b50a2412d7546d5f239fe14fbaadeb445fc66a01b0779d98223961111e21766282f73dd96b6f")
+        );
+        assert_eq!(
+            x448(
+                &bytes("203d494428b8399352665ddca42f9de8fef600908e0d461cb021f8c538345dd77c3e4806e25f46d3315c44e0a5b4371282dd2c8d5be3095f"),
+                &bytes("0fbcc2f993cd56d3305b0b7d9e55d4c1a8fb5dbb52f8e9a1e9b6201b165d015894e56c4d3570bee52fe205e28a78b91cdfbde71ce8d157db"),
+            ),
+            bytes::<56>("884a02576239ff7a2f2f63b2db6a9ff37047ac13568e1e30fe63c4a7ad1b3ee3a5700df34321d62077e63633c575c1c954514e99da7c179d")
+        );
+    }
+
+    // RFC 7748, 5.2: k = u = base, then k, u = X(k, u), k
+    fn iterate(curve: &Montgomery, rounds: &[(usize, &str)]) {
+        let mut k = curve.base_u();
+        let mut u = curve.base_u();
+        let mut done = 0;
+        for (count, expected) in rounds {
+            while done < *count {
+                let next = curve.scalar_mul(&k, &u).unwrap();
+                u = std::mem::replace(&mut k, next);
+                done += 1;
+            }
+            assert_eq!(hex::encode(&k), *expected, "after {count} iterations");
+        }
+    }
+
+    #[test]
+    fn test_x25519_iterated() {
+        iterate(
+            &curves::curve25519(),
+            &[
+                (
+                    1,
+                    "422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079",
+                ),
+                (
+                    1000,
+                    "684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_x448_iterated() {
+        iterate(
+            &curves::curve448(),
+            &[
+                (1, "3f482c8a9f19b01e6c46ee9711d9dc14fd4bf67af30765c2ae2b846a4d23a8cd0db897086239492caf350b51f833868b9bc2b3bca9cf4113"),
+                (1000, "aa3b4749d55b9daf1e5b00288826c467274ce3ebbdd5c17b975e09d4af6c67cf10d087202db88286e2b79fceea3ec353ef54faa26e219f38"),
+            ],
+        );
+    }
+
+    // a million ladders each, hours at this speed: cargo test -- --ignored
+    #[test]
+    #[ignore]
+    fn test_iterated_million() {
+        iterate(
+            &curves::curve25519(),
+            &[(
+                1_000_000,
+                "7c3911e0ab2586fd864497297e575e6f3bc601c0883c30df5f4dd2d24f665424",
+            )],
+        );
+        iterate(
+            &curves::curve448(),
+            &[(1_000_000, "077f453681caca3693198420bbe515cae0002472519b3e67661a7e89cab94695c8f4bcd66e61b9b9c946da8d524de3d69bd9d9d66b997e37")],
+        );
+    }
+}
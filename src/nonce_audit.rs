@@ -0,0 +1,214 @@
+// Audit of ECDSA signature logs for nonce reuse. Two signatures with the same
+// r under one key were made with k or -k, so
+//     s1 - s2 = k^-1 (h1 - h2)  or  s1 + s2 = k^-1 (h1 - h2)
+// gives k, and then d = r^-1 (s1 k - h1). Every candidate key is checked
+// against the public point before it is reported.
+
+use std::collections::HashMap;
+
+use crate::bigint_utils::{mod_inverse, modulo};
+use crate::sign_ecdsa::{digest_to_int, sha256, VerifyingKey};
+
+use num_bigint::BigInt as bui;
+use num_traits::Zero;
+
+pub struct LogEntry<'a> {
+    pub m: &'a [u8],
+    pub sign: (bui, bui),
+}
+
+#[derive(Debug)]
+pub struct Recovery {
+    // indices of the pair the key was recovered from
+    pub pair: (usize, usize),
+    // nonce of the first signature of the pair
+    pub k: bui,
+    pub d_a: bui,
+}
+
+#[derive(Debug)]
+pub struct Finding {
+    pub r: bui,
+    pub indices: Vec<usize>,
+    // None when no pair in the group gives a key matching the public point,
+    // e.g. the same signature logged twice
+    pub recovery: Option<Recovery>,
+}
+
+// Groups of entries sharing r, in order of first appearance
+pub fn find_repeated_r(entries: &[LogEntry]) -> Vec<(bui, Vec<usize>)> {
+    let mut groups: Vec<(bui, Vec<usize>)> = Vec::new();
+    let mut seen: HashMap<&bui, usize> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        match seen.get(&entry.sign.0) {
+            Some(&g) => groups[g].1.push(i),
+            None => {
+                seen.insert(&entry.sign.0, groups.len());
+                groups.push((entry.sign.0.clone(), vec![i]));
+            }
+        }
+    }
+    groups.retain(|(_, indices)| indices.len() > 1);
+    groups
+}
+
+// Recovers (k, d) from two signatures sharing r; the key is only returned
+// when d G equals the public point
+pub fn recover_from_reuse(
+    q_a: &VerifyingKey,
+    first: &LogEntry,
+    second: &LogEntry,
+) -> Result<(bui, bui), String> {
+    let ec = q_a.curve();
+    let n = ec.get_ref_n();
+    let (r, s1) = &first.sign;
+    let (r2, s2) = &second.sign;
+    if r != r2 {
+        return Err(String::from("Signatures do not share r"));
+    }
+    if *r <= bui::zero() || r >= n {
+        return Err(String::from("Signature is out of range"));
+    }
+    let h1 = digest_to_int(&sha256(first.m), n);
+    let h2 = digest_to_int(&sha256(second.m), n);
+    let r_rev = mod_inverse(r, n).ok_or("r is not invertible")?;
+    let expected = q_a.as_point().get_xy(ec);
+
+    // same nonce, then opposite nonces
+    let dh = &h1 - &h2;
+    for ds in [s1 - s2, s1 + s2] {
+        let Some(ds_rev) = mod_inverse(&modulo(&ds, n), n) else {
+            continue;
+        };
+        let k = modulo(&(&dh * ds_rev), n);
+        if k.is_zero() {
+            continue;
+        }
+        let d = modulo(&((s1 * &k - &h1) * &r_rev), n);
+        if !d.is_zero() && ec.scalar_mul(ec.get_ref_p(), &d).get_xy(ec) == expected {
+            return Ok((k, d));
+        }
+    }
+    Err(String::from("No candidate key matches the public point"))
+}
+
+// Flags every repeated r and tries each pair of its group until one gives
+// the private key
+pub fn audit(q_a: &VerifyingKey, entries: &[LogEntry]) -> Vec<Finding> {
+    find_repeated_r(entries)
+        .into_iter()
+        .map(|(r, indices)| {
+            let recovery = indices
+                .iter()
+                .enumerate()
+                .flat_map(|(a, &i)| indices[a + 1..].iter().map(move |&j| (i, j)))
+                .find_map(|(i, j)| {
+                    recover_from_reuse(q_a, &entries[i], &entries[j])
+                        .ok()
+                        .map(|(k, d_a)| Recovery {
+                            pair: (i, j),
+                            k,
+                            d_a,
+                        })
+                });
+            Finding {
+                r,
+                indices,
+                recovery,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use crate::sign_ecdsa::SigningKey;
+    use crate::signature::{Signer, Verifier};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn sign_with_k(key: &SigningKey, m: &[u8], k: &bui) -> (bui, bui) {
+        key.sign_digest_with_k(&sha256(m), k).unwrap().0
+    }
+
+    #[test]
+    fn test_recover_reused_nonce() {
+        let mut rng = StdRng::seed_from_u64(33);
+        for ec in [curves::p256(), curves::secp256k1()] {
+            let key = SigningKey::random_with_rng(ec.clone(), &mut rng);
+            let q_a = key.verifying_key();
+            let n = ec.get_ref_n();
+            let k = bui::from(0x5eed_u32) << 200;
+
+            let first = LogEntry {
+                m: b"first",
+                sign: sign_with_k(&key, b"first", &k),
+            };
+            let second = LogEntry {
+                m: b"second",
+                sign: sign_with_k(&key, b"second", &k),
+            };
+            let (k_rec, d) = recover_from_reuse(q_a, &first, &second).unwrap();
+            assert_eq!(k_rec, k);
+
+            // the recovered key signs for the audited public key
+            let forged = SigningKey::from_scalar(ec.clone(), d).unwrap();
+            let sign = forged.sign(b"forged").unwrap();
+            assert!(q_a.verify(b"forged", &sign).is_ok());
+
+            // n - k gives the same r: recovery still works
+            let negated = LogEntry {
+                m: b"third",
+                sign: sign_with_k(&key, b"third", &(n - &k)),
+            };
+            let (k_rec, _) = recover_from_reuse(q_a, &first, &negated).unwrap();
+            assert_eq!(k_rec, k);
+        }
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut rng = StdRng::seed_from_u64(34);
+        let ec = curves::p256();
+        let key = SigningKey::random_with_rng(ec.clone(), &mut rng);
+        let other = SigningKey::random_with_rng(ec.clone(), &mut rng);
+        let k = bui::from(123456789);
+
+        let messages: Vec<Vec<u8>> = (0..8).map(|i| format!("log {i}").into_bytes()).collect();
+        let mut entries: Vec<LogEntry> = messages
+            .iter()
+            .map(|m| LogEntry {
+                m,
+                sign: key.sign_with_rng(m, &mut rng).unwrap(),
+            })
+            .collect();
+        // a duplicated log line and a reused nonce
+        entries[5].sign = entries[1].sign.clone();
+        entries[6].sign = sign_with_k(&key, &messages[6], &k);
+        entries[2].sign = sign_with_k(&key, &messages[2], &k);
+
+        let findings = audit(key.verifying_key(), &entries);
+        assert_eq!(findings.len(), 2);
+
+        assert_eq!(findings[0].r, entries[1].sign.0);
+        assert_eq!(findings[0].indices, vec![1, 5]);
+        assert!(findings[0].recovery.is_none());
+
+        assert_eq!(findings[1].r, entries[2].sign.0);
+        assert_eq!(findings[1].indices, vec![2, 6]);
+        let recovery = findings[1].recovery.as_ref().unwrap();
+        assert_eq!(recovery.pair, (2, 6));
+        assert_eq!(recovery.k, k);
+        assert_eq!(
+            ec.scalar_mul(ec.get_ref_p(), &recovery.d_a).get_xy(&ec),
+            key.verifying_key().as_point().get_xy(&ec)
+        );
+
+        // the same reuse audited against another key is flagged but not
+        // confirmed
+        let findings = audit(other.verifying_key(), &entries);
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().all(|f| f.recovery.is_none()));
+    }
+}
@@ -0,0 +1,342 @@
+// Offline/online ECDSA: the k G multiplication is done ahead of time and the
+// resulting (k, k^-1, r) tuples wait in a bounded pool. Signing then costs two
+// multiplications mod n. A tuple is moved out of the pool by `take` and
+// consumed by value when signing, so it can never sign twice; its scalars are
+// wiped when it is dropped.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::bigint_utils::{mod_inverse, Wipe};
+use crate::ec::EC;
+use crate::sign_ecdsa::{sha256, SigningKey};
+
+use num_bigint::BigInt as bui;
+use num_bigint::RandBigInt;
+use num_integer::Integer;
+use num_traits::Zero;
+use rand::{CryptoRng, RngCore};
+
+pub struct Presig {
+    pub(crate) k: bui,
+    pub(crate) k_inv: bui,
+    pub(crate) r: bui,
+    pub(crate) recid: u8,
+    // order of the curve the tuple was made for
+    pub(crate) n: bui,
+}
+
+impl Presig {
+    pub fn generate(ec: &EC) -> Presig {
+        Self::generate_with_rng(ec, &mut rand::thread_rng())
+    }
+
+    pub fn generate_with_rng(ec: &EC, rng: &mut (impl RngCore + CryptoRng)) -> Presig {
+        let n = ec.get_ref_n();
+        loop {
+            let k = rng.gen_bigint_range(&bui::from(2), n);
+            let (x_1, y_1) = ec.scalar_mul(ec.get_ref_p(), &k).get_xy(ec);
+            let r = &x_1 % n;
+            if r.is_zero() {
+                continue;
+            }
+            let Some(k_inv) = mod_inverse(&k, n) else {
+                continue;
+            };
+            let recid = u8::from(y_1.is_odd()) | (u8::from(x_1 >= *n) << 1);
+            break Presig {
+                k,
+                k_inv,
+                r,
+                recid,
+                n: n.clone(),
+            };
+        }
+    }
+
+    pub fn r(&self) -> &bui {
+        &self.r
+    }
+}
+
+impl Drop for Presig {
+    fn drop(&mut self) {
+        self.k.wipe();
+        self.k_inv.wipe();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnEmpty {
+    // signing fails until the pool is refilled
+    Fail,
+    // a tuple is computed in the request path, at the usual signing cost
+    ComputeInline,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RefillPolicy {
+    // the pool never holds more tuples than this
+    pub capacity: usize,
+    // a refill is requested once the pool is down to this many tuples
+    pub low_watermark: usize,
+    pub on_empty: OnEmpty,
+}
+
+impl Default for RefillPolicy {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            low_watermark: 16,
+            on_empty: OnEmpty::ComputeInline,
+        }
+    }
+}
+
+pub struct NoncePool {
+    ec: EC,
+    policy: RefillPolicy,
+    presigs: Mutex<VecDeque<Presig>>,
+    low: Condvar,
+}
+
+impl NoncePool {
+    pub fn new(ec: EC, policy: RefillPolicy) -> Result<NoncePool, String> {
+        if policy.capacity == 0 || policy.low_watermark >= policy.capacity {
+            return Err(String::from(
+                "Low watermark must be below a nonzero capacity",
+            ));
+        }
+        Ok(Self {
+            ec,
+            policy,
+            presigs: Mutex::new(VecDeque::with_capacity(policy.capacity)),
+            low: Condvar::new(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.presigs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn needs_refill(&self) -> bool {
+        self.len() <= self.policy.low_watermark
+    }
+
+    // Tops the pool up to capacity and returns the number of tuples added.
+    // The scalar multiplications run without holding the lock.
+    pub fn refill(&self) -> usize {
+        self.refill_with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn refill_with_rng(&self, rng: &mut (impl RngCore + CryptoRng)) -> usize {
+        let missing = self.policy.capacity - self.len();
+        let fresh: Vec<Presig> = (0..missing)
+            .map(|_| Presig::generate_with_rng(&self.ec, rng))
+            .collect();
+
+        let mut presigs = self.presigs.lock().unwrap();
+        let room = self.policy.capacity - presigs.len();
+        let added = room.min(fresh.len());
+        // tuples that no longer fit are dropped, and so wiped
+        presigs.extend(fresh.into_iter().take(added));
+        added
+    }
+
+    // Moves one tuple out of the pool, waking the refiller when the pool
+    // reaches the low watermark
+    pub fn take(&self) -> Option<Presig> {
+        let mut presigs = self.presigs.lock().unwrap();
+        let presig = presigs.pop_front();
+        if presigs.len() <= self.policy.low_watermark {
+            self.low.notify_all();
+        }
+        presig
+    }
+
+    pub fn sign(&self, key: &SigningKey, m: &[u8]) -> Result<(bui, bui), String> {
+        if key.verifying_key().curve().get_ref_n() != self.ec.get_ref_n() {
+            return Err(String::from("Signing key is on another curve"));
+        }
+        let digest = sha256(m);
+        loop {
+            let presig = match (self.take(), self.policy.on_empty) {
+                (Some(presig), _) => presig,
+                (None, OnEmpty::ComputeInline) => Presig::generate(&self.ec),
+                (None, OnEmpty::Fail) => return Err(String::from("Nonce pool is empty")),
+            };
+            // s = 0 burns the tuple; the next one is tried
+            if let Ok((sign, _)) = key.sign_digest_with_presig(&digest, presig) {
+                break Ok(sign);
+            }
+        }
+    }
+
+    // Starts a thread that refills the pool whenever it reaches the low
+    // watermark; the thread stops when the returned handle is dropped
+    pub fn spawn_refiller(self: &Arc<Self>) -> Refiller {
+        let stop = Arc::new(AtomicBool::new(false));
+        let pool = Arc::clone(self);
+        let flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || loop {
+            {
+                let presigs = pool.presigs.lock().unwrap();
+                let _presigs = pool
+                    .low
+                    .wait_while(presigs, |p| {
+                        p.len() > pool.policy.low_watermark && !flag.load(Ordering::Acquire)
+                    })
+                    .unwrap();
+            }
+            if flag.load(Ordering::Acquire) {
+                break;
+            }
+            pool.refill();
+        });
+
+        Refiller {
+            pool: Arc::clone(self),
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+pub struct Refiller {
+    pool: Arc<NoncePool>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Refiller {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        {
+            // notify under the lock so the wakeup cannot slip in between the
+            // refiller's check and its wait
+            let _presigs = self.pool.presigs.lock().unwrap();
+            self.pool.low.notify_all();
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use crate::signature::{Signer, Verifier};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::HashSet;
+    use std::time::{Duration, Instant};
+
+    fn policy(capacity: usize, low_watermark: usize, on_empty: OnEmpty) -> RefillPolicy {
+        RefillPolicy {
+            capacity,
+            low_watermark,
+            on_empty,
+        }
+    }
+
+    #[test]
+    fn test_pool_signatures_verify() {
+        let mut rng = StdRng::seed_from_u64(35);
+        let ec = curves::p256();
+        let key = SigningKey::random_with_rng(ec.clone(), &mut rng);
+        let pool = NoncePool::new(ec, policy(8, 2, OnEmpty::Fail)).unwrap();
+        assert_eq!(pool.refill_with_rng(&mut rng), 8);
+        assert_eq!(pool.refill_with_rng(&mut rng), 0);
+
+        let mut seen = HashSet::new();
+        for i in 0..8 {
+            let m = format!("request {i}");
+            let sign = pool.sign(&key, m.as_bytes()).unwrap();
+            assert!(key.verifying_key().verify(m.as_bytes(), &sign).is_ok());
+            // every tuple is used once: no r repeats
+            assert!(seen.insert(sign.0));
+        }
+        assert!(pool.is_empty());
+        assert!(pool.sign(&key, b"one more").is_err());
+    }
+
+    #[test]
+    fn test_presig_matches_k() {
+        let mut rng = StdRng::seed_from_u64(36);
+        let ec = curves::secp256k1();
+        let key = SigningKey::random_with_rng(ec.clone(), &mut rng);
+        let presig = Presig::generate_with_rng(&ec, &mut rng);
+        let digest = sha256(b"offline/online");
+        let expected = key.sign_digest_with_k(&digest, &presig.k).unwrap();
+        let ((r, _), _) = &expected;
+        assert_eq!(presig.r(), r);
+        assert_eq!(
+            key.sign_digest_with_presig(&digest, presig).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pool_policy() {
+        let ec = curves::p256();
+        assert!(NoncePool::new(ec.clone(), policy(4, 4, OnEmpty::Fail)).is_err());
+        assert!(NoncePool::new(ec.clone(), policy(0, 0, OnEmpty::Fail)).is_err());
+
+        let pool = NoncePool::new(ec.clone(), policy(4, 1, OnEmpty::ComputeInline)).unwrap();
+        assert!(pool.needs_refill());
+        pool.refill();
+        assert!(!pool.needs_refill());
+        pool.take();
+        pool.take();
+        assert!(!pool.needs_refill());
+        pool.take();
+        assert!(pool.needs_refill());
+
+        // an empty pool still signs inline, and keys on other curves are refused
+        let key = SigningKey::random(ec);
+        pool.take();
+        assert!(pool.is_empty());
+        assert!(pool.sign(&key, b"inline").is_ok());
+        assert!(pool
+            .sign(&SigningKey::random(curves::p384()), b"p384")
+            .is_err());
+    }
+
+    #[test]
+    fn test_background_refill() {
+        let ec = curves::p256();
+        let key = SigningKey::random(ec.clone());
+        let pool = Arc::new(NoncePool::new(ec, policy(16, 4, OnEmpty::Fail)).unwrap());
+        let refiller = pool.spawn_refiller();
+
+        let wait_full = |pool: &NoncePool| {
+            let deadline = Instant::now() + Duration::from_secs(60);
+            while pool.len() < 16 {
+                assert!(Instant::now() < deadline, "refill timed out");
+                thread::sleep(Duration::from_millis(5));
+            }
+        };
+        wait_full(&pool);
+
+        let start = Instant::now();
+        for i in 0..12 {
+            pool.sign(&key, format!("online {i}").as_bytes()).unwrap();
+        }
+        let online = start.elapsed() / 12;
+        let start = Instant::now();
+        key.sign(b"offline").unwrap();
+        println!("online: {online:?}, full signature: {:?}", start.elapsed());
+
+        // 12 tuples taken leaves 4, the low watermark: the refiller tops up
+        wait_full(&pool);
+        drop(refiller);
+        assert_eq!(pool.len(), 16);
+    }
+}
@@ -0,0 +1,346 @@
+// BIP-340 Schnorr signatures over secp256k1 with x-only public keys, and the
+// BIP-341 Taproot key tweak
+
+use crate::bigint_utils::{modulo, Wipe};
+use crate::curves;
+use crate::ec::{to_fixed_be, Point, EC};
+use crate::signature::{Signer, Verifier};
+
+use num_bigint::BigInt as bui;
+use num_bigint::Sign;
+use num_integer::Integer;
+use num_traits::Zero;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+pub struct SigningKey {
+    // the secret as given; signing negates it when d G has an odd y
+    d: bui,
+    verifying_key: VerifyingKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
+    ec: EC,
+    // the even-y point with the given x coordinate
+    p: Point,
+}
+
+// hash_tag(x) = SHA256(SHA256(tag) || SHA256(tag) || x)
+pub fn tagged_hash(tag: &str, parts: &[&[u8]]) -> [u8; 32] {
+    let tag = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(tag);
+    hasher.update(tag);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn int(bytes: &[u8]) -> bui {
+    bui::from_bytes_be(Sign::Plus, bytes)
+}
+
+fn bytes32(v: &bui) -> [u8; 32] {
+    to_fixed_be(v, 32).try_into().unwrap()
+}
+
+fn has_even_y(ec: &EC, p: &Point) -> bool {
+    p.get_xy(ec).1.is_even()
+}
+
+// e = int(hash_BIP0340/challenge(bytes(R) || bytes(P) || m)) mod n
+fn challenge(ec: &EC, r: &[u8], p: &[u8; 32], m: &[u8]) -> bui {
+    int(&tagged_hash("BIP0340/challenge", &[r, p, m])) % ec.get_ref_n()
+}
+
+impl SigningKey {
+    pub fn random() -> SigningKey {
+        Self::random_with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> SigningKey {
+        loop {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            if let Ok(key) = Self::from_bytes(&bytes) {
+                break key;
+            }
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<SigningKey, String> {
+        if bytes.len() != 32 {
+            return Err(String::from("Secret key must be 32 bytes"));
+        }
+        let ec = curves::secp256k1();
+        let d = int(bytes);
+        if d.is_zero() || &d >= ec.get_ref_n() {
+            return Err(String::from("Secret key must be in [1, n - 1]"));
+        }
+        let p = ec.scalar_mul(ec.get_ref_p(), &d);
+        let p = if has_even_y(&ec, &p) { p } else { ec.neg(&p) };
+        let p = ec.convert(&ec.convert(&p)?)?;
+
+        Ok(Self {
+            d,
+            verifying_key: VerifyingKey { ec, p },
+        })
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    // d or n - d, whichever makes d G have an even y
+    fn even_secret(&self) -> bui {
+        let ec = &self.verifying_key.ec;
+        let p = ec.scalar_mul(ec.get_ref_p(), &self.d);
+        if has_even_y(ec, &p) {
+            self.d.clone()
+        } else {
+            ec.get_ref_n() - &self.d
+        }
+    }
+
+    // BIP-340 default signing with 32 bytes of auxiliary randomness
+    pub fn sign_with_aux(&self, m: &[u8], aux: &[u8; 32]) -> Result<[u8; 64], String> {
+        let ec = &self.verifying_key.ec;
+        let n = ec.get_ref_n();
+        let mut d = self.even_secret();
+        let p = self.verifying_key.to_bytes();
+
+        let mut t = bytes32(&d);
+        for (t, a) in t.iter_mut().zip(tagged_hash("BIP0340/aux", &[aux])) {
+            *t ^= a;
+        }
+        let rand = tagged_hash("BIP0340/nonce", &[&t, &p, m]);
+        t.fill(0);
+        let mut k = int(&rand) % n;
+        if k.is_zero() {
+            d.wipe();
+            return Err(String::from("Nonce is zero"));
+        }
+        let big_r = ec.scalar_mul(ec.get_ref_p(), &k);
+        if !has_even_y(ec, &big_r) {
+            k = n - &k;
+        }
+        let r = bytes32(&big_r.get_xy(ec).0);
+        let e = challenge(ec, &r, &p, m);
+        let s = (&k + e * &d) % n;
+        k.wipe();
+        d.wipe();
+
+        let mut sign = [0u8; 64];
+        sign[..32].copy_from_slice(&r);
+        sign[32..].copy_from_slice(&bytes32(&s));
+        self.verifying_key
+            .verify(m, &sign)
+            .map_err(|_| String::from("Produced signature does not verify"))?;
+        Ok(sign)
+    }
+
+    pub fn sign_with_rng(
+        &self,
+        m: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<[u8; 64], String> {
+        let mut aux = [0u8; 32];
+        rng.fill_bytes(&mut aux);
+        self.sign_with_aux(m, &aux)
+    }
+
+    // BIP-341: the secret for the output key P + t G, negated first when
+    // d G has an odd y
+    pub fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<SigningKey, String> {
+        let ec = &self.verifying_key.ec;
+        let t = self.verifying_key.tap_tweak_scalar(merkle_root)?;
+        let mut d = self.even_secret();
+        let tweaked = (&d + t) % ec.get_ref_n();
+        d.wipe();
+        Self::from_bytes(&bytes32(&tweaked))
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.d.wipe();
+    }
+}
+
+impl Signer<[u8; 64]> for SigningKey {
+    fn sign(&self, m: &[u8]) -> Result<[u8; 64], String> {
+        self.sign_with_rng(m, &mut rand::thread_rng())
+    }
+}
+
+impl VerifyingKey {
+    // lift_x: fails for x >= p or when x^3 + 7 is not a square
+    pub fn from_bytes(bytes: &[u8]) -> Result<VerifyingKey, String> {
+        if bytes.len() != 32 {
+            return Err(String::from("Public key must be 32 bytes"));
+        }
+        let ec = curves::secp256k1();
+        let p = ec
+            .lift_x(&int(bytes), false)
+            .ok_or_else(|| String::from("Public key is not a valid x coordinate"))?;
+        Ok(Self { ec, p })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        bytes32(&self.p.get_xy(&self.ec).0)
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.p
+    }
+
+    // t = int(hash_TapTweak(bytes(P) || merkle_root)), rejected when >= n
+    fn tap_tweak_scalar(&self, merkle_root: Option<&[u8; 32]>) -> Result<bui, String> {
+        let p = self.to_bytes();
+        let root: &[u8] = merkle_root.map_or(&[], |root| root);
+        let t = int(&tagged_hash("TapTweak", &[&p, root]));
+        if &t >= self.ec.get_ref_n() {
+            return Err(String::from("Tweak is out of range"));
+        }
+        Ok(t)
+    }
+
+    // BIP-341 output key Q = P + t G, with the parity of its y coordinate
+    // that goes into the control block
+    pub fn tap_tweak(
+        &self,
+        merkle_root: Option<&[u8; 32]>,
+    ) -> Result<(VerifyingKey, bool), String> {
+        let ec = &self.ec;
+        let t = self.tap_tweak_scalar(merkle_root)?;
+        let q = ec.add(&self.p, &ec.scalar_mul_vartime(ec.get_ref_p(), &t))?;
+        if q.is_infinity() {
+            return Err(String::from("Tweaked key is the point at infinity"));
+        }
+        let odd = !has_even_y(ec, &q);
+        let key = Self::from_bytes(&bytes32(&q.get_xy(ec).0))?;
+        Ok((key, odd))
+    }
+}
+
+impl Verifier<[u8; 64]> for VerifyingKey {
+    fn verify(&self, m: &[u8], sign: &[u8; 64]) -> Result<(), String> {
+        let ec = &self.ec;
+        let n = ec.get_ref_n();
+        let r = int(&sign[..32]);
+        let s = int(&sign[32..]);
+        if &r >= ec.get_ref_q() || &s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
+        let e = challenge(ec, &sign[..32], &self.to_bytes(), m);
+
+        // R = s G - e P
+        let big_r = ec.multi_scalar_mul(&[
+            (ec.get_ref_p().clone(), s),
+            (self.p.clone(), modulo(&-e, n)),
+        ]);
+        if big_r.is_infinity() {
+            return Err(String::from("R is the point at infinity"));
+        }
+        let (x, y) = big_r.get_xy(ec);
+        if y.is_odd() || x != r {
+            return Err(String::from("Signature does not match"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_bip340_vectors() {
+        let path = format!(
+            "{}/test_vectors/bip340/test-vectors.csv",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+
+        let mut count = 0;
+        for line in text.lines().skip(1).filter(|l| !l.is_empty()) {
+            let fields: Vec<&str> = line.splitn(8, ',').collect();
+            let [index, secret, public, aux, m, sign, result, comment] = fields[..] else {
+                panic!("malformed line {line}");
+            };
+            let m = hex::decode(m).unwrap();
+            let sign: [u8; 64] = hex::decode(sign).unwrap().try_into().unwrap();
+            let expected = result == "TRUE";
+
+            if !secret.is_empty() {
+                let key = SigningKey::from_bytes(&hex::decode(secret).unwrap()).unwrap();
+                assert_eq!(
+                    hex::encode_upper(key.verifying_key().to_bytes()),
+                    public,
+                    "#{index}"
+                );
+                let aux: [u8; 32] = hex::decode(aux).unwrap().try_into().unwrap();
+                assert_eq!(key.sign_with_aux(&m, &aux).unwrap(), sign, "#{index}");
+            }
+
+            let verified = VerifyingKey::from_bytes(&hex::decode(public).unwrap())
+                .and_then(|key| key.verify(&m, &sign))
+                .is_ok();
+            assert_eq!(verified, expected, "#{index} {comment}");
+            count += 1;
+        }
+        assert_eq!(count, 19);
+    }
+
+    #[test]
+    fn test_sign_random() {
+        let key = SigningKey::random();
+        let sign = key.sign(b"schnorr").unwrap();
+        assert!(key.verifying_key().verify(b"schnorr", &sign).is_ok());
+        assert!(key.verifying_key().verify(b"schnorR", &sign).is_err());
+    }
+
+    #[test]
+    fn test_tap_tweak_vector() {
+        // BIP-341 wallet-test-vectors.json, scriptPubKey[0]: key path only
+        let internal = VerifyingKey::from_bytes(
+            &hex::decode("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d")
+                .unwrap(),
+        )
+        .unwrap();
+        // an x-only key stands for the point with even y
+        let (_, y) = internal.as_point().get_xy(&curves::secp256k1());
+        assert!(!y.bit(0));
+        let (output_key, _) = internal.tap_tweak(None).unwrap();
+        assert_eq!(
+            hex::encode(output_key.to_bytes()),
+            "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+        );
+    }
+
+    #[test]
+    fn test_tap_tweak() {
+        let key = SigningKey::from_bytes(
+            &hex::decode("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef")
+                .unwrap(),
+        )
+        .unwrap();
+        let root = [0x42u8; 32];
+        for merkle_root in [None, Some(&root)] {
+            let (output_key, _) = key.verifying_key().tap_tweak(merkle_root).unwrap();
+            let tweaked = key.tap_tweak(merkle_root).unwrap();
+            assert_eq!(tweaked.verifying_key().to_bytes(), output_key.to_bytes());
+
+            // a key-path spend signs with the tweaked secret for the output key
+            let sign = tweaked.sign(b"key path spend").unwrap();
+            assert!(output_key.verify(b"key path spend", &sign).is_ok());
+            assert!(key
+                .verifying_key()
+                .verify(b"key path spend", &sign)
+                .is_err());
+        }
+    }
+}
@@ -2,7 +2,11 @@
 
 use std::str::FromStr;
 
+use crate::bigint_utils::{mod_inverse, modulo, retry_with_nonce, Wipe};
 use crate::ec::{Point, EC};
+use crate::keygen::{gen_key_pair_with_rng, KeyGenMethod};
+use crate::nonce_pool::Presig;
+use crate::signature::{Signer, Verifier};
 
 use num_bigint::BigInt as bui;
 use num_bigint::RandBigInt;
@@ -10,97 +14,370 @@ use num_bigint::ToBigInt;
 use num_traits::Zero;
 
 use num_integer::Integer;
+use rand::{CryptoRng, RngCore};
 
-struct Ecdsa {
+pub struct SigningKey {
+    d_a: bui,
+    verifying_key: VerifyingKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
     ec: EC,
-    q_a: Option<Point>,
-    d_a: Option<bui>,
+    q_a: Point,
 }
 
 use num_bigint::Sign;
 use sha2::{Digest, Sha256};
 
-fn sha256(m: &[u8]) -> bui {
+pub struct BatchItem<'a> {
+    pub m: &'a [u8],
+    pub sign: &'a (bui, bui),
+    pub recid: u8,
+    pub q_a: &'a VerifyingKey,
+}
+
+pub fn sha256(m: &[u8]) -> Vec<u8> {
     let mut hasher = Sha256::new();
     hasher.update(m);
-    bui::from_bytes_be(Sign::Plus, &hasher.finalize())
+    hasher.finalize().to_vec()
 }
 
-impl Ecdsa {
-    fn get_pk(&self) -> Result<&Point, String> {
-        match &self.q_a {
-            Some(point) => Ok(point),
-            None => Err(String::from("You have not created public key")),
-        }
+// Leftmost min(N, hashlen) bits of the digest, N = bitlen(n) (FIPS 186-5, 6.4.1)
+pub fn digest_to_int(digest: &[u8], n: &bui) -> bui {
+    let e = bui::from_bytes_be(Sign::Plus, digest);
+    let len = digest.len() as u64 * 8;
+    if len > n.bits() {
+        e >> (len - n.bits())
+    } else {
+        e
     }
+}
 
-    fn init(ec: EC) -> Ecdsa {
-        let mut rng = rand::thread_rng();
+impl SigningKey {
+    pub fn random(ec: EC) -> SigningKey {
+        Self::random_with_rng(ec, &mut rand::thread_rng())
+    }
 
-        let d_a = rng.gen_bigint_range(
-            &(2).to_bigint().unwrap(),
-            &(ec.get_ref_q() - &1.to_bigint().unwrap()),
-        );
+    pub fn random_with_rng(ec: EC, rng: &mut (impl RngCore + CryptoRng)) -> SigningKey {
+        let (d_a, q_a) = gen_key_pair_with_rng(&ec, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+        let signing_key = Self {
+            d_a,
+            verifying_key: VerifyingKey { ec, q_a },
+        };
+
+        // signature flavour of the pairwise consistency test (FIPS 140-3, 10.3.A)
+        let sign = signing_key
+            .sign_with_rng(b"pairwise consistency test", rng)
+            .unwrap();
+        signing_key
+            .verifying_key
+            .verify(b"pairwise consistency test", &sign)
+            .expect("pairwise consistency test failed");
+        signing_key
+    }
+
+    pub fn from_scalar(ec: EC, d_a: bui) -> Result<SigningKey, String> {
+        if d_a <= bui::zero() || &d_a >= ec.get_ref_n() {
+            return Err(String::from("Private key must be in [1, n - 1]"));
+        }
 
         let q_a = ec.scalar_mul(ec.get_ref_p(), &d_a);
 
-        Self {
-            ec,
-            q_a: Some(q_a),
-            d_a: Some(d_a),
-        }
-    }
-
-    fn sign(&mut self, m: &[u8]) -> Result<(bui, bui), String> {
-        match &self.d_a {
-            Some(d_a) => {
-                let mut rng = rand::thread_rng();
-                let n: &bui = self.ec.get_ref_n();
-                let h: bui = sha256(m);
-                loop {
-                    let k = rng.gen_bigint_range(&(2).to_bigint().unwrap(), n);
-
-                    let kp = self.ec.scalar_mul(self.ec.get_ref_p(), &k);
-                    let (x_1, _) = kp.get_xy(&self.ec);
-                    let r = x_1 % n;
-                    if r != bui::zero() {
-                        let k_rev = k.extended_gcd(n).x;
-                        let k_rev = modulo(&k_rev, n);
-                        let s = (k_rev * (h + d_a * &r)) % n;
-                        break Ok((r, s));
-                    }
-                }
-            }
-            None => Err(String::from("You have not created public key")),
+        Ok(Self {
+            d_a,
+            verifying_key: VerifyingKey { ec, q_a },
+        })
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    // Also returns the recovery id: bit 0 is the parity of y(kP), bit 1 is set
+    // when x(kP) >= n and r had to be reduced
+    pub fn sign_recoverable(&self, m: &[u8]) -> Result<((bui, bui), u8), String> {
+        self.sign_recoverable_with_rng(m, &mut rand::thread_rng())
+    }
+
+    pub fn sign_with_rng(
+        &self,
+        m: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        self.sign_recoverable_with_rng(m, rng).map(|(sign, _)| sign)
+    }
+
+    pub fn sign_recoverable_with_rng(
+        &self,
+        m: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<((bui, bui), u8), String> {
+        self.sign_prehash_recoverable_with_rng(&sha256(m), rng)
+    }
+
+    // For callers that already hold the digest, from any hash function;
+    // digests longer than n are truncated as in FIPS 186-5, 6.4.1
+    pub fn sign_prehash(&self, digest: &[u8]) -> Result<(bui, bui), String> {
+        self.sign_prehash_with_rng(digest, &mut rand::thread_rng())
+    }
+
+    pub fn sign_prehash_with_rng(
+        &self,
+        digest: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        self.sign_prehash_recoverable_with_rng(digest, rng)
+            .map(|(sign, _)| sign)
+    }
+
+    pub fn sign_prehash_recoverable_with_rng(
+        &self,
+        digest: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<((bui, bui), u8), String> {
+        if digest.is_empty() {
+            return Err(String::from("Digest is empty"));
         }
+        let n: &bui = self.verifying_key.ec.get_ref_n();
+        Ok(retry_with_nonce(rng, n, |k| {
+            self.sign_digest_with_k(digest, k)
+        }))
     }
 
-    fn verify(&self, m: &[u8], sign: &(bui, bui), q_a: &Point) -> bool {
-        let h = sha256(m);
+    // r = x(k P) mod n, s = k^-1 (h + d r) mod n; fails when r = 0 or s = 0
+    pub(crate) fn sign_digest_with_k(
+        &self,
+        digest: &[u8],
+        k: &bui,
+    ) -> Result<((bui, bui), u8), String> {
+        let ec = &self.verifying_key.ec;
+        let n: &bui = ec.get_ref_n();
+        if *k <= bui::zero() || k >= n {
+            return Err(String::from("Nonce must be in [1, n - 1]"));
+        }
+        let h = digest_to_int(digest, n);
+
+        let kp = ec.scalar_mul(ec.get_ref_p(), k);
+        let (x_1, y_1) = kp.get_xy(ec);
+        let r = &x_1 % n;
+        if r == bui::zero() {
+            return Err(String::from("r = 0, another nonce is needed"));
+        }
+        let k_rev = mod_inverse(k, n).ok_or_else(|| String::from("k is not invertible mod n"))?;
+        let s = (k_rev * (h + &self.d_a * &r)) % n;
+        if s == bui::zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        let recid = u8::from(y_1.is_odd()) | (u8::from(x_1 >= *n) << 1);
+        Ok(((r, s), recid))
+    }
+
+    // Online half of offline/online signing: the tuple is consumed whether or
+    // not it produces a signature
+    pub fn sign_digest_with_presig(
+        &self,
+        digest: &[u8],
+        presig: Presig,
+    ) -> Result<((bui, bui), u8), String> {
+        let n: &bui = self.verifying_key.ec.get_ref_n();
+        if presig.n != *n {
+            return Err(String::from("Presignature was made for another curve"));
+        }
+        let h = digest_to_int(digest, n);
+        let s = (&presig.k_inv * (h + &self.d_a * &presig.r)) % n;
+        if s == bui::zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        Ok(((presig.r.clone(), s), presig.recid))
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.d_a.wipe();
+    }
+}
+
+impl Signer<(bui, bui)> for SigningKey {
+    fn sign(&self, m: &[u8]) -> Result<(bui, bui), String> {
+        self.sign_recoverable(m).map(|(sign, _)| sign)
+    }
+}
+
+impl VerifyingKey {
+    pub fn from_point(ec: EC, q_a: Point) -> Result<VerifyingKey, String> {
+        ec.validate_public_key(&q_a)?;
+        let (x, y) = q_a.get_xy(&ec);
+        let q_a = Point::new(x, y, Some(1.to_bigint().unwrap()));
+
+        Ok(Self { ec, q_a })
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.q_a
+    }
+
+    pub fn curve(&self) -> &EC {
+        &self.ec
+    }
+
+    pub fn verify_digest(&self, digest: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        if digest.is_empty() {
+            return Err(String::from("Digest is empty"));
+        }
         let n = self.ec.get_ref_n();
+        let h = digest_to_int(digest, n);
+        let (r, s) = sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
 
-        let s_rev = sign.1.extended_gcd(n).x;
-        let s_rev = modulo(&s_rev, n);
+        let s_rev = mod_inverse(s, n).ok_or_else(|| String::from("s is not invertible mod n"))?;
         let u1 = (&s_rev * h) % n;
-        let u2 = (&s_rev * &sign.0) % n;
-        let u1p = self.ec.scalar_mul(self.ec.get_ref_p(), &u1);
-        let u2q_a = self.ec.scalar_mul(q_a, &u2);
-        let (x_0, _) = self.ec.add(&u1p, &u2q_a).unwrap().get_xy(&self.ec);
+        let u2 = (&s_rev * r) % n;
+        let u1p = self.ec.scalar_mul_vartime(self.ec.get_ref_p(), &u1);
+        let u2q_a = self.ec.scalar_mul_vartime(&self.q_a, &u2);
+        let (x_0, _) = self.ec.add(&u1p, &u2q_a)?.get_xy(&self.ec);
         let v = x_0 % n;
-        v == sign.0
+        if v == *r {
+            Ok(())
+        } else {
+            Err(String::from("Signature does not match"))
+        }
+    }
+}
+
+impl Verifier<(bui, bui)> for VerifyingKey {
+    fn verify(&self, m: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        self.verify_digest(&sha256(m), sign)
+    }
+}
+
+// Checks sum z_i (s_i^-1 h_i P + s_i^-1 r_i Q_i - R_i) = O for random 128-bit
+// z_i, with R_i lifted from r_i through the recovery id. On failure falls back
+// to one-by-one verification and returns the indices that do not verify;
+// items whose key is on another curve than ec are rejected up front.
+pub fn verify_batch(ec: &EC, items: &[BatchItem]) -> Result<(), Vec<usize>> {
+    verify_batch_with_rng(ec, items, &mut rand::thread_rng())
+}
+
+pub fn verify_batch_with_rng(
+    ec: &EC,
+    items: &[BatchItem],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), Vec<usize>> {
+    let foreign: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !item.q_a.curve().same_curve(ec))
+        .map(|(i, _)| i)
+        .collect();
+    if !foreign.is_empty() {
+        return Err(foreign);
+    }
+    if verify_batch_combined(ec, items, rng).unwrap_or(false) {
+        return Ok(());
+    }
+
+    let failed: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.q_a.verify(item.m, item.sign).is_err())
+        .map(|(i, _)| i)
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
     }
 }
 
-fn modulo(a: &bui, n: &bui) -> bui {
-    ((a % n) + n) % n
+fn verify_batch_combined(
+    ec: &EC,
+    items: &[BatchItem],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<bool, String> {
+    let n = ec.get_ref_n();
+
+    let mut u_p = bui::zero();
+    let mut keys: Vec<((bui, bui), Point, bui)> = Vec::new();
+    let mut terms: Vec<(Point, bui)> = Vec::with_capacity(items.len() + 1);
+    for item in items {
+        let (r, s) = item.sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n || item.recid > 3 {
+            return Ok(false);
+        }
+
+        let x = r + n * u32::from(item.recid >> 1);
+        let big_r = ec
+            .lift_x(&x, item.recid & 1 == 1)
+            .ok_or_else(|| String::from("r does not correspond to a curve point"))?;
+        let z = bui::from(rng.gen_biguint(128)) + 1;
+        let Some(s_rev) = mod_inverse(s, n) else {
+            return Ok(false);
+        };
+        let zs_rev = (&z * s_rev) % n;
+
+        u_p += &zs_rev * digest_to_int(&sha256(item.m), n);
+        let u_q = (&zs_rev * r) % n;
+        let xy = item.q_a.q_a.get_xy(ec);
+        match keys.iter_mut().find(|(key, _, _)| *key == xy) {
+            Some((_, _, k)) => *k += u_q,
+            None => keys.push((xy, item.q_a.q_a.clone(), u_q)),
+        }
+        terms.push((ec.neg(&big_r), z));
+    }
+
+    terms.push((ec.get_ref_p().clone(), u_p % n));
+    terms.extend(keys.into_iter().map(|(_, q_a, k)| (q_a, k % n)));
+    Ok(ec.multi_scalar_mul(&terms).is_infinity())
+}
+
+// Q_A = r^-1 (sR - hP), where R is the point with x = r + (recid >> 1) n
+// and the y parity given by recid & 1
+pub fn recover_public_key(
+    ec: &EC,
+    m: &[u8],
+    sign: &(bui, bui),
+    recid: u8,
+) -> Result<Point, String> {
+    if recid > 3 {
+        return Err(String::from("Recovery id must be in 0..=3"));
+    }
+    let n = ec.get_ref_n();
+    let (r, s) = sign;
+    if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+        return Err(String::from("Signature is out of range"));
+    }
+
+    let x = r + n * u32::from(recid >> 1);
+    let big_r = ec
+        .lift_x(&x, recid & 1 == 1)
+        .ok_or_else(|| String::from("r does not correspond to a curve point"))?;
+
+    let h = digest_to_int(&sha256(m), n);
+    let r_rev = mod_inverse(r, n).ok_or_else(|| String::from("r is not invertible mod n"))?;
+    let u1 = modulo(&(-h * &r_rev), n);
+    let u2 = (s * &r_rev) % n;
+    let u1p = ec.scalar_mul_vartime(ec.get_ref_p(), &u1);
+    let u2r = ec.scalar_mul_vartime(&big_r, &u2);
+    let q_a = ec.add(&u1p, &u2r)?;
+    if q_a.is_infinity() {
+        return Err(String::from("Recovered point at infinity"));
+    }
+    Ok(q_a)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::bigint_utils::FromHex;
+    use crate::curves;
     use num_traits::One;
-    use rand::Rng;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
 
     use std::time::{Duration, Instant};
     #[test]
@@ -126,7 +403,7 @@ mod tests {
         let mut total_verf = Duration::ZERO;
         let t = 100;
         for _ in 0..t {
-            let mut ecdsa = Ecdsa::init(ec_p256.clone());
+            let ecdsa = SigningKey::random(ec_p256.clone());
 
             let mut rng = rand::thread_rng();
             let message: [u8; 32] = rng.gen();
@@ -143,20 +420,215 @@ mod tests {
 
             let (r, s) = signature.unwrap();
 
+            let pk = ecdsa.verifying_key();
+
             let start = Instant::now();
 
-            let pk = ecdsa.get_pk().unwrap();
+            let is_valid = pk.verify(&message, &(r, s));
 
             let elapsed = start.elapsed();
 
             total_verf += elapsed;
 
-            let is_valid = ecdsa.verify(&message, &(r, s), pk);
-
-            assert!(is_valid, "Підпис не пройшов перевірку");
+            assert!(is_valid.is_ok(), "Підпис не пройшов перевірку");
         }
 
         println!("time sign: {:?}", total_sign / t);
         println!("time verf: {:?}", total_verf / t);
     }
+
+    fn check_recovery(ec: &EC, t: usize) {
+        for _ in 0..t {
+            let ecdsa = SigningKey::random(ec.clone());
+
+            let mut rng = rand::thread_rng();
+            let message: [u8; 32] = rng.gen();
+
+            let (signature, recid) = ecdsa.sign_recoverable(&message).unwrap();
+            assert!(recid < 4);
+
+            let q_a = recover_public_key(ec, &message, &signature, recid).unwrap();
+            assert_eq!(q_a.get_xy(ec), ecdsa.verifying_key().as_point().get_xy(ec));
+
+            let other = recover_public_key(ec, &message, &signature, recid ^ 1).unwrap();
+            assert_ne!(other.get_xy(ec), q_a.get_xy(ec));
+        }
+    }
+
+    #[test]
+    fn test_recover_public_key_p256() {
+        check_recovery(&curves::p256(), 20);
+    }
+
+    #[test]
+    fn test_recover_public_key_secp256k1() {
+        check_recovery(&curves::secp256k1(), 20);
+    }
+
+    #[test]
+    fn test_recover_public_key_x_overflow() {
+        // n = 421 < q = 463, so x(kP) >= n for roughly one nonce in eleven
+        let ec = curves::toy_463();
+
+        let mut overflowed = 0;
+        for i in 0..300u32 {
+            let ecdsa = SigningKey::random(ec.clone());
+            let message = i.to_be_bytes();
+            let (signature, recid) = ecdsa.sign_recoverable(&message).unwrap();
+            if recid & 2 != 0 {
+                overflowed += 1;
+            }
+
+            let q_a = recover_public_key(&ec, &message, &signature, recid).unwrap();
+            assert_eq!(
+                q_a.get_xy(&ec),
+                ecdsa.verifying_key().as_point().get_xy(&ec)
+            );
+        }
+        assert!(overflowed > 0);
+    }
+
+    #[test]
+    fn test_recover_public_key_bad_recid() {
+        let ec = curves::p256();
+        let ecdsa = SigningKey::random(ec.clone());
+        let (signature, _) = ecdsa.sign_recoverable(b"message").unwrap();
+        assert!(recover_public_key(&ec, b"message", &signature, 4).is_err());
+        assert!(recover_public_key(&ec, b"message", &(bui::zero(), signature.1), 0).is_err());
+    }
+
+    fn batch_items<'a>(
+        messages: &'a [[u8; 32]],
+        signatures: &'a [((bui, bui), u8)],
+        signers: &'a [SigningKey],
+    ) -> Vec<BatchItem<'a>> {
+        (0..messages.len())
+            .map(|i| BatchItem {
+                m: &messages[i],
+                sign: &signatures[i].0,
+                recid: signatures[i].1,
+                q_a: signers[i % signers.len()].verifying_key(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let ec = curves::p256();
+        let mut rng = rand::thread_rng();
+
+        let signers: Vec<SigningKey> = (0..4).map(|_| SigningKey::random(ec.clone())).collect();
+        let mut messages = Vec::new();
+        let mut signatures = Vec::new();
+        for i in 0..32 {
+            let message: [u8; 32] = rng.gen();
+            signatures.push(signers[i % 4].sign_recoverable(&message).unwrap());
+            messages.push(message);
+        }
+        let items = |signatures| batch_items(&messages, signatures, &signers);
+
+        let start = Instant::now();
+        assert_eq!(verify_batch(&ec, &items(&signatures)), Ok(()));
+        println!("time batch: {:?}", start.elapsed());
+
+        let start = Instant::now();
+        for item in items(&signatures) {
+            assert!(item.q_a.verify(item.m, item.sign).is_ok());
+        }
+        println!("time one by one: {:?}", start.elapsed());
+
+        let mut tampered = signatures.clone();
+        tampered[5].0 .1 += 1;
+        tampered[17].0 .0 = tampered[18].0 .0.clone();
+        assert_eq!(verify_batch(&ec, &items(&tampered)), Err(vec![5, 17]));
+
+        // a wrong recovery id only costs the fast path
+        let mut wrong_recid = signatures.clone();
+        wrong_recid[3].1 ^= 1;
+        assert!(!verify_batch_combined(&ec, &items(&wrong_recid), &mut rng).unwrap());
+        assert_eq!(verify_batch(&ec, &items(&wrong_recid)), Ok(()));
+
+        // a key on another curve is rejected before the combined check
+        let other = SigningKey::random(curves::secp256k1());
+        let sign = other.sign_recoverable(&messages[0]).unwrap();
+        let mut mixed = items(&signatures);
+        mixed[2] = BatchItem {
+            m: &messages[0],
+            sign: &sign.0,
+            recid: sign.1,
+            q_a: other.verifying_key(),
+        };
+        assert_eq!(verify_batch(&ec, &mixed), Err(vec![2]));
+        assert_eq!(
+            verify_batch(&curves::secp256k1(), &items(&signatures)),
+            Err((0..32).collect())
+        );
+    }
+
+    #[test]
+    fn test_key_validation() {
+        let ec = curves::p256();
+        let n = ec.get_ref_n().clone();
+        assert!(SigningKey::from_scalar(ec.clone(), bui::zero()).is_err());
+        assert!(SigningKey::from_scalar(ec.clone(), n.clone()).is_err());
+
+        let d_a = bui::from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")
+            .unwrap();
+        let signing_key = SigningKey::from_scalar(ec.clone(), d_a).unwrap();
+        let q_a = signing_key.verifying_key().as_point().clone();
+        let (x, y) = q_a.get_xy(&ec);
+        assert_eq!(
+            x,
+            bui::from_hex("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6")
+                .unwrap()
+        );
+        assert_eq!(
+            y,
+            bui::from_hex("7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299")
+                .unwrap()
+        );
+
+        let verifying_key = VerifyingKey::from_point(ec.clone(), q_a).unwrap();
+        let signature = signing_key.sign(b"sample").unwrap();
+        assert!(verifying_key.verify(b"sample", &signature).is_ok());
+        assert!(verifying_key.verify(b"other", &signature).is_err());
+        assert!(verifying_key
+            .verify(b"sample", &(signature.0.clone(), n.clone()))
+            .is_err());
+
+        let off_curve = Point::new(x.clone(), &y + 1, Some(bui::one()));
+        assert!(VerifyingKey::from_point(ec.clone(), off_curve).is_err());
+        let out_of_range = Point::new(x + ec.get_ref_q(), y, Some(bui::one()));
+        assert!(VerifyingKey::from_point(ec.clone(), out_of_range).is_err());
+        let o_e = Point::new(bui::zero(), bui::one(), Some(bui::zero()));
+        assert!(VerifyingKey::from_point(ec, o_e).is_err());
+    }
+
+    #[test]
+    fn test_sign_seeded() {
+        let ec = curves::p256();
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let ecdsa = SigningKey::random_with_rng(ec.clone(), &mut rng);
+        let signature = ecdsa.sign_with_rng(b"message", &mut rng).unwrap();
+
+        assert_eq!(
+            ecdsa.verifying_key().as_point().get_xy(&ec),
+            (
+                bui::from_hex("407fbcc8e07c7a4ff64d4ef4b6a5993988afbb87b2afc2f8bc746e4634d59810")
+                    .unwrap(),
+                bui::from_hex("925a2b76b5f4b2da6a7ad10bd8e218940b95c73235a8921c28a73913cfc8a550")
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            signature,
+            (
+                bui::from_hex("9d7ae338cb2da62ce3635723a534663b429238a6faac89faa7b2fbafd3808068")
+                    .unwrap(),
+                bui::from_hex("800853ca273985330c4e45a87818cb27c5225fa69d21c785be52e15791fb5b5a")
+                    .unwrap()
+            )
+        );
+        assert!(ecdsa.verifying_key().verify(b"message", &signature).is_ok());
+    }
 }
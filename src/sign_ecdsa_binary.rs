@@ -0,0 +1,214 @@
+// ECDSA over the binary curves (X9.62, FIPS 186-4): as in sign_ecdsa, with
+// r = x(k P) mod n where the field element x is read as an integer
+
+use crate::bigint_utils::{mod_inverse, retry_with_nonce, Wipe};
+use crate::binary_ec::{BinPoint, BinaryCurve};
+use crate::keygen::{gen_binary_key_pair_with_rng, KeyGenMethod};
+use crate::sign_ecdsa::{digest_to_int, sha256};
+use crate::signature::{Signer, Verifier};
+
+use num_bigint::BigInt as bui;
+use num_traits::Zero;
+use rand::{CryptoRng, RngCore};
+
+pub struct SigningKey {
+    d_a: bui,
+    verifying_key: VerifyingKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
+    curve: BinaryCurve,
+    q_a: BinPoint,
+}
+
+impl SigningKey {
+    pub fn random_with_rng(curve: BinaryCurve, rng: &mut (impl RngCore + CryptoRng)) -> SigningKey {
+        let (d_a, q_a) = gen_binary_key_pair_with_rng(&curve, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
+
+        Self {
+            d_a,
+            verifying_key: VerifyingKey { curve, q_a },
+        }
+    }
+
+    pub fn from_scalar(curve: BinaryCurve, d_a: bui) -> Result<SigningKey, String> {
+        if d_a <= bui::zero() || &d_a >= curve.get_ref_n() {
+            return Err(String::from("Private key must be in [1, n - 1]"));
+        }
+        let q_a = curve.scalar_mul(curve.get_ref_p(), &d_a);
+
+        Ok(Self {
+            d_a,
+            verifying_key: VerifyingKey { curve, q_a },
+        })
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    pub fn sign_with_rng(
+        &self,
+        m: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        self.sign_prehash_with_rng(&sha256(m), rng)
+    }
+
+    pub fn sign_prehash_with_rng(
+        &self,
+        digest: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        if digest.is_empty() {
+            return Err(String::from("Digest is empty"));
+        }
+        let n = self.verifying_key.curve.get_ref_n();
+        Ok(retry_with_nonce(rng, n, |k| {
+            self.sign_digest_with_k(digest, k)
+        }))
+    }
+
+    // r = x(k P) mod n with x(k P) read as an integer, s = k^-1 (h + d r) mod n;
+    // fails when k P = O, r = 0 or s = 0
+    pub(crate) fn sign_digest_with_k(&self, digest: &[u8], k: &bui) -> Result<(bui, bui), String> {
+        let curve = &self.verifying_key.curve;
+        let n = curve.get_ref_n();
+        if *k <= bui::zero() || k >= n {
+            return Err(String::from("Nonce must be in [1, n - 1]"));
+        }
+        let h = digest_to_int(digest, n);
+
+        let BinPoint::Affine(x_1, _) = curve.scalar_mul(curve.get_ref_p(), k) else {
+            return Err(String::from("k P is the point at infinity"));
+        };
+        let r = x_1 % n;
+        if r.is_zero() {
+            return Err(String::from("r = 0, another nonce is needed"));
+        }
+        let k_inv = mod_inverse(k, n).ok_or_else(|| String::from("k is not invertible mod n"))?;
+        let s = (k_inv * (h + &self.d_a * &r)) % n;
+        if s.is_zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        Ok((r, s))
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.d_a.wipe();
+    }
+}
+
+impl Signer<(bui, bui)> for SigningKey {
+    fn sign(&self, m: &[u8]) -> Result<(bui, bui), String> {
+        self.sign_with_rng(m, &mut rand::thread_rng())
+    }
+}
+
+impl VerifyingKey {
+    pub fn from_point(curve: BinaryCurve, q_a: BinPoint) -> Result<VerifyingKey, String> {
+        curve.validate_public_key(&q_a)?;
+        Ok(Self { curve, q_a })
+    }
+
+    pub fn as_point(&self) -> &BinPoint {
+        &self.q_a
+    }
+
+    pub fn curve(&self) -> &BinaryCurve {
+        &self.curve
+    }
+
+    pub fn verify_digest(&self, digest: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        let curve = &self.curve;
+        let n = curve.get_ref_n();
+        let h = digest_to_int(digest, n);
+        let (r, s) = sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
+
+        let s_inv = mod_inverse(s, n).ok_or_else(|| String::from("s is not invertible mod n"))?;
+        let u1 = (&s_inv * h) % n;
+        let u2 = (&s_inv * r) % n;
+        let big_r =
+            curve.multi_scalar_mul(&[(curve.get_ref_p().clone(), u1), (self.q_a.clone(), u2)]);
+        let BinPoint::Affine(x_0, _) = big_r else {
+            return Err(String::from("R is the point at infinity"));
+        };
+        if x_0 % n == *r {
+            Ok(())
+        } else {
+            Err(String::from("Signature does not match"))
+        }
+    }
+}
+
+impl Verifier<(bui, bui)> for VerifyingKey {
+    fn verify(&self, m: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        self.verify_digest(&sha256(m), sign)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::int;
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // test_vectors/openssl/binary_curves.csv: the keys and signatures come
+    // from OpenSSL, see the README there
+    #[test]
+    fn test_openssl_vectors() {
+        let path = format!(
+            "{}/test_vectors/openssl/binary_curves.csv",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+        let mut count = 0;
+        for line in text.lines().skip(1) {
+            let fields: Vec<&str> = line.split(',').collect();
+            let [name, d, q_x, q_y, m, r, s, _, _] = fields[..] else {
+                panic!("malformed line {line}");
+            };
+            let curve = curves::nist_binary(name).unwrap();
+            let key = SigningKey::from_scalar(curve.clone(), int(d)).unwrap();
+            let q_a = BinPoint::Affine(int(q_x), int(q_y));
+            assert_eq!(*key.verifying_key().as_point(), q_a, "{name}");
+
+            let vk = VerifyingKey::from_point(curve, q_a).unwrap();
+            let m = hex::decode(m).unwrap();
+            let sign = (int(r), int(s));
+            assert!(vk.verify(&m, &sign).is_ok(), "{name}");
+            assert!(vk.verify(b"another message", &sign).is_err(), "{name}");
+            let own = key.sign(&m).unwrap();
+            assert!(vk.verify(&m, &own).is_ok(), "{name}");
+            count += 1;
+        }
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_sign_with_k() {
+        let mut rng = StdRng::seed_from_u64(163);
+        let key = SigningKey::random_with_rng(curves::k163(), &mut rng);
+        let n = key.verifying_key().curve().get_ref_n().clone();
+        let digest = sha256(b"koblitz");
+        assert!(key.sign_digest_with_k(&digest, &bui::zero()).is_err());
+        assert!(key.sign_digest_with_k(&digest, &n).is_err());
+        // the same k gives the same signature; s + 1 does not verify
+        let k = bui::from(0x1234_5678u32);
+        let sign = key.sign_digest_with_k(&digest, &k).unwrap();
+        assert_eq!(sign, key.sign_digest_with_k(&digest, &k).unwrap());
+        assert!(key.verifying_key().verify_digest(&digest, &sign).is_ok());
+        assert!(key
+            .verifying_key()
+            .verify_digest(&digest, &(sign.0.clone(), &sign.1 + 1u32))
+            .is_err());
+    }
+}
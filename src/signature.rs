@@ -0,0 +1,7 @@
+pub trait Signer<S> {
+    fn sign(&self, m: &[u8]) -> Result<S, String>;
+}
+
+pub trait Verifier<S> {
+    fn verify(&self, m: &[u8], sign: &S) -> Result<(), String>;
+}
@@ -0,0 +1,631 @@
+// SM2 (GB/T 32918-2016) over a prime curve, normally curves::sm2p256v1:
+// signatures, public-key encryption and authenticated key exchange, all
+// hashing with SM3. Every party has an identity ID and
+// Z = SM3(ENTL || ID || a || b || x_G || y_G || x_P || y_P) binds it and its
+// public key to the curve; Z prefixes the signed message and enters the
+// exchanged key. Field elements are big-endian and field-length.
+
+use crate::bigint_utils::{mod_inverse, modulo, retry_with_nonce, Wipe};
+use crate::ec::{to_fixed_be, Point, EC};
+use crate::signature::{Signer, Verifier};
+use crate::sm3::{sm3, Sm3};
+
+use num_bigint::BigInt as bui;
+use num_bigint::RandBigInt;
+use num_bigint::Sign;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+// GM/T 0009-2012: the ID when the parties agree on none
+pub const DEFAULT_ID: &[u8] = b"1234567812345678";
+
+// Z = SM3(ENTL || ID || a || b || x_G || y_G || x_P || y_P), ENTL the bit
+// length of ID in two bytes
+pub fn z_value(ec: &EC, id: &[u8], p: &Point) -> Result<Vec<u8>, String> {
+    let entl = u16::try_from(id.len() * 8).map_err(|_| String::from("ID is too long"))?;
+    let len = ec.field_len();
+    let (x_g, y_g) = ec.get_ref_p().get_xy(ec);
+    let (x_p, y_p) = p.get_xy(ec);
+    let mut hasher = Sm3::new();
+    hasher.update(&entl.to_be_bytes());
+    hasher.update(id);
+    for v in [ec.get_ref_a(), ec.get_ref_b(), &x_g, &y_g, &x_p, &y_p] {
+        hasher.update(&to_fixed_be(v, len));
+    }
+    Ok(hasher.finalize())
+}
+
+// KDF(Z, klen): SM3(Z || ct) for ct = 1, 2, ... as 32-bit big-endian
+// counters, concatenated and cut to klen bytes
+pub fn kdf(z: &[u8], klen: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(klen + 32);
+    let mut ct: u32 = 1;
+    while out.len() < klen {
+        let mut hasher = Sm3::new();
+        hasher.update(z);
+        hasher.update(&ct.to_be_bytes());
+        out.extend(hasher.finalize());
+        ct += 1;
+    }
+    out.truncate(klen);
+    out
+}
+
+pub struct SigningKey {
+    d: bui,
+    verifying_key: VerifyingKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
+    ec: EC,
+    p: Point,
+    // Z of the key's owner
+    z: Vec<u8>,
+}
+
+impl SigningKey {
+    pub fn random_with_rng(
+        ec: EC,
+        id: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<SigningKey, String> {
+        let d = rng.gen_bigint_range(&bui::one(), &(ec.get_ref_n() - 1u32));
+        Self::from_scalar(ec, d, id)
+    }
+
+    // d is in [1, n - 2], so that 1 + d can be inverted
+    pub fn from_scalar(ec: EC, d: bui, id: &[u8]) -> Result<SigningKey, String> {
+        if d <= bui::zero() || d >= ec.get_ref_n() - 1u32 {
+            return Err(String::from("Private key must be in [1, n - 2]"));
+        }
+        let (x, y) = ec.scalar_mul(ec.get_ref_p(), &d).get_xy(&ec);
+        let p = Point::new(x, y, Some(bui::one()));
+        let z = z_value(&ec, id, &p)?;
+
+        Ok(Self {
+            d,
+            verifying_key: VerifyingKey { ec, p, z },
+        })
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    pub fn sign_with_rng(
+        &self,
+        m: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        let n = self.verifying_key.ec.get_ref_n();
+        Ok(retry_with_nonce(rng, n, |k| self.sign_with_k(m, k)))
+    }
+
+    // e = SM3(Z || M), r = (e + x(k G)) mod n, s = (1 + d)^-1 (k - r d) mod n;
+    // fails when r = 0, r + k = n or s = 0
+    pub(crate) fn sign_with_k(&self, m: &[u8], k: &bui) -> Result<(bui, bui), String> {
+        let vk = &self.verifying_key;
+        let ec = &vk.ec;
+        let n = ec.get_ref_n();
+        if *k <= bui::zero() || k >= n {
+            return Err(String::from("Nonce must be in [1, n - 1]"));
+        }
+        let e = vk.digest(m);
+
+        let (x_1, _) = ec.scalar_mul(ec.get_ref_p(), k).get_xy(ec);
+        let r = (e + x_1) % n;
+        if r.is_zero() || &(&r + k) == n {
+            return Err(String::from("r = 0 or r + k = n, another nonce is needed"));
+        }
+        let d_1_inv = mod_inverse(&(&self.d + 1u32), n)
+            .ok_or_else(|| String::from("1 + d is not invertible mod n"))?;
+        let s = modulo(&(d_1_inv * (k - &r * &self.d)), n);
+        if s.is_zero() {
+            return Err(String::from("s = 0, another nonce is needed"));
+        }
+        Ok((r, s))
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.d.wipe();
+    }
+}
+
+impl Signer<(bui, bui)> for SigningKey {
+    fn sign(&self, m: &[u8]) -> Result<(bui, bui), String> {
+        self.sign_with_rng(m, &mut rand::thread_rng())
+    }
+}
+
+impl VerifyingKey {
+    pub fn from_point(ec: EC, p: Point, id: &[u8]) -> Result<VerifyingKey, String> {
+        ec.validate_public_key(&p)?;
+        let (x, y) = p.get_xy(&ec);
+        let p = Point::new(x, y, Some(bui::one()));
+        let z = z_value(&ec, id, &p)?;
+        Ok(Self { ec, p, z })
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.p
+    }
+
+    pub fn z(&self) -> &[u8] {
+        &self.z
+    }
+
+    // e = SM3(Z || M) as an integer
+    fn digest(&self, m: &[u8]) -> bui {
+        let mut hasher = Sm3::new();
+        hasher.update(&self.z);
+        hasher.update(m);
+        bui::from_bytes_be(Sign::Plus, &hasher.finalize())
+    }
+}
+
+impl Verifier<(bui, bui)> for VerifyingKey {
+    // t = r + s, and r must equal e + x(s G + t P) mod n
+    fn verify(&self, m: &[u8], sign: &(bui, bui)) -> Result<(), String> {
+        let ec = &self.ec;
+        let n = ec.get_ref_n();
+        let (r, s) = sign;
+        if *r <= bui::zero() || r >= n || *s <= bui::zero() || s >= n {
+            return Err(String::from("Signature is out of range"));
+        }
+        let t = (r + s) % n;
+        if t.is_zero() {
+            return Err(String::from("t = 0"));
+        }
+        let e = self.digest(m);
+        let point =
+            ec.multi_scalar_mul(&[(ec.get_ref_p().clone(), s.clone()), (self.p.clone(), t)]);
+        if point.is_infinity() {
+            return Err(String::from("s G + t P is the point at infinity"));
+        }
+        let (x_1, _) = point.get_xy(ec);
+        if (e + x_1) % n == *r {
+            Ok(())
+        } else {
+            Err(String::from("Signature does not match"))
+        }
+    }
+}
+
+// C = C1 || C3 || C2 with C1 = k G uncompressed, t = KDF(x2 || y2, |M|) for
+// (x2, y2) = k P_B, C2 = M xor t and C3 = SM3(x2 || M || y2)
+pub fn encrypt_with_rng(
+    ec: &EC,
+    p_b: &Point,
+    m: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, String> {
+    ec.validate_public_key(p_b)?;
+    if m.is_empty() {
+        return Err(String::from("Message is empty"));
+    }
+    Ok(retry_with_nonce(rng, ec.get_ref_n(), |k| {
+        encrypt_with_k(ec, p_b, m, k)
+    }))
+}
+
+// Fails when the KDF output for k P_B is all zero
+pub(crate) fn encrypt_with_k(ec: &EC, p_b: &Point, m: &[u8], k: &bui) -> Result<Vec<u8>, String> {
+    if m.is_empty() {
+        return Err(String::from("Message is empty"));
+    }
+    let len = ec.field_len();
+    let c_1 = ec.scalar_mul(ec.get_ref_p(), k);
+    let (x_2, y_2) = ec.scalar_mul(p_b, k).get_xy(ec);
+    let (x_2, y_2) = (to_fixed_be(&x_2, len), to_fixed_be(&y_2, len));
+
+    let t = kdf(&[x_2.as_slice(), &y_2].concat(), m.len());
+    if t.iter().all(|&b| b == 0) {
+        return Err(String::from("KDF output is zero, another k is needed"));
+    }
+    let c_2: Vec<u8> = m.iter().zip(&t).map(|(m, t)| m ^ t).collect();
+    let c_3 = sm3(&[x_2.as_slice(), m, &y_2].concat());
+
+    let mut out = ec.encode_point(&c_1, false);
+    out.extend(c_3);
+    out.extend(c_2);
+    Ok(out)
+}
+
+pub fn decrypt(ec: &EC, d_b: &bui, c: &[u8]) -> Result<Vec<u8>, String> {
+    let len = ec.field_len();
+    if c.len() <= 1 + 2 * len + 32 {
+        return Err(String::from("Ciphertext is too short"));
+    }
+    let (c_1, rest) = c.split_at(1 + 2 * len);
+    let (c_3, c_2) = rest.split_at(32);
+    let c_1 = ec.decode_point(c_1)?;
+
+    let (x_2, y_2) = ec.scalar_mul(&c_1, d_b).get_xy(ec);
+    let (x_2, y_2) = (to_fixed_be(&x_2, len), to_fixed_be(&y_2, len));
+    let t = kdf(&[x_2.as_slice(), &y_2].concat(), c_2.len());
+    if t.iter().all(|&b| b == 0) {
+        return Err(String::from("KDF output is zero"));
+    }
+    let m: Vec<u8> = c_2.iter().zip(&t).map(|(c, t)| c ^ t).collect();
+    let u = sm3(&[x_2.as_slice(), &m, &y_2].concat());
+    if u.iter().zip(c_3).fold(0, |acc, (a, b)| acc | (a ^ b)) != 0 {
+        return Err(String::from("C3 does not match"));
+    }
+    Ok(m)
+}
+
+// One side of the SM2 key exchange (GB/T 32918.3). Each side sends R = r G,
+// then computes t = (d + x̄ r) mod n with x̄ = 2^w + (x(R) mod 2^w),
+// w = ceil(ceil(log2 n) / 2) - 1, and the shared point
+// U = t (P_peer + x̄_peer R_peer). K = KDF(x_U || y_U || Z_A || Z_B, klen),
+// A being the initiator; the optional confirmations are
+// SM3(0x02 or 0x03 || y_U || SM3(x_U || Z_A || Z_B || x_RA || y_RA || x_RB || y_RB)).
+pub struct KeyExchange {
+    ec: EC,
+    d: bui,
+    z: Vec<u8>,
+    r: bui,
+    big_r: Point,
+    initiator: bool,
+}
+
+pub struct Agreement {
+    pub key: Vec<u8>,
+    // S_A from the initiator, S_B from the responder
+    pub confirmation: Vec<u8>,
+    // the peer's confirmation, for check
+    expected: Vec<u8>,
+}
+
+impl Agreement {
+    pub fn check(&self, confirmation: &[u8]) -> Result<(), String> {
+        if confirmation.len() == self.expected.len()
+            && confirmation
+                .iter()
+                .zip(&self.expected)
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0
+        {
+            Ok(())
+        } else {
+            Err(String::from("Key confirmation does not match"))
+        }
+    }
+}
+
+impl KeyExchange {
+    pub fn init_with_rng(
+        ec: EC,
+        d: bui,
+        id: &[u8],
+        initiator: bool,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<KeyExchange, String> {
+        let r = rng.gen_bigint_range(&bui::one(), ec.get_ref_n());
+        Self::from_scalars(ec, d, id, r, initiator)
+    }
+
+    pub fn from_scalars(
+        ec: EC,
+        d: bui,
+        id: &[u8],
+        r: bui,
+        initiator: bool,
+    ) -> Result<KeyExchange, String> {
+        let n = ec.get_ref_n();
+        if d <= bui::zero() || &d >= n || r <= bui::zero() || &r >= n {
+            return Err(String::from("Scalars must be in [1, n - 1]"));
+        }
+        let p = ec.scalar_mul(ec.get_ref_p(), &d);
+        let z = z_value(&ec, id, &p)?;
+        let big_r = ec.scalar_mul(ec.get_ref_p(), &r);
+        Ok(Self {
+            ec,
+            d,
+            z,
+            r,
+            big_r,
+            initiator,
+        })
+    }
+
+    pub fn send(&self) -> Point {
+        self.big_r.clone()
+    }
+
+    // x̄ = 2^w + (x mod 2^w)
+    fn x_bar(&self, p: &Point) -> bui {
+        let w = self.ec.get_ref_n().bits().div_ceil(2) - 1;
+        let (x, _) = p.get_xy(&self.ec);
+        let low: bui = x & ((bui::one() << w) - 1);
+        (bui::one() << w) + low
+    }
+
+    pub fn recieve(
+        &self,
+        p_peer: &Point,
+        id_peer: &[u8],
+        r_peer: &Point,
+        klen: usize,
+    ) -> Result<Agreement, String> {
+        let ec = &self.ec;
+        let n = ec.get_ref_n();
+        ec.validate_public_key(p_peer)?;
+        ec.validate_public_key(r_peer)?;
+        let z_peer = z_value(ec, id_peer, p_peer)?;
+
+        let mut t = (&self.d + self.x_bar(&self.big_r) * &self.r) % n;
+        let sum = ec.add(p_peer, &ec.scalar_mul(r_peer, &self.x_bar(r_peer)))?;
+        let u = ec.scalar_mul(&sum, &t);
+        t.wipe();
+        if u.is_infinity() {
+            return Err(String::from("Shared point is the point at infinity"));
+        }
+
+        let len = ec.field_len();
+        let (x_u, y_u) = u.get_xy(ec);
+        let (x_u, y_u) = (to_fixed_be(&x_u, len), to_fixed_be(&y_u, len));
+        let (r_a, r_b) = if self.initiator {
+            (&self.big_r, r_peer)
+        } else {
+            (r_peer, &self.big_r)
+        };
+        let (z_a, z_b) = if self.initiator {
+            (&self.z, &z_peer)
+        } else {
+            (&z_peer, &self.z)
+        };
+
+        let key = kdf(&[x_u.as_slice(), &y_u, z_a, z_b].concat(), klen);
+        let mut inner = Sm3::new();
+        inner.update(&x_u);
+        inner.update(z_a);
+        inner.update(z_b);
+        for p in [r_a, r_b] {
+            let (x, y) = p.get_xy(ec);
+            inner.update(&to_fixed_be(&x, len));
+            inner.update(&to_fixed_be(&y, len));
+        }
+        let inner = inner.finalize();
+        let confirm = |tag: u8| sm3(&[&[tag][..], &y_u, &inner].concat());
+        let (s_b, s_a) = (confirm(0x02), confirm(0x03));
+
+        Ok(if self.initiator {
+            Agreement {
+                key,
+                confirmation: s_a,
+                expected: s_b,
+            }
+        } else {
+            Agreement {
+                key,
+                confirmation: s_b,
+                expected: s_a,
+            }
+        })
+    }
+}
+
+impl Drop for KeyExchange {
+    fn drop(&mut self) {
+        self.d.wipe();
+        self.r.wipe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_utils::int;
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // the 256-bit prime test curve of the GB/T 32918 appendices
+    fn example_curve() -> EC {
+        let g = Point::new(
+            int("421DEBD61B62EAB6746434EBC3CC315E32220B3BADD50BDC4C4E6C147FEDD43D"),
+            int("0680512BCBB42C07D47349D2153B70C4E5D7FDFCBFA36EA1A85841B9E46E09A2"),
+            Some(bui::one()),
+        );
+        EC::new(
+            int("787968B4FA32C3FD2417842E73BBFEFF2F3C848B6831D7E0EC65228B3937E498"),
+            int("63E4C6D3B23B0C849CF84241484BFE48F61D59A5B16BA06E6E12D1DA27C5249A"),
+            int("8542D69E4C044F18E8B92435BF6FF7DE457283915C45517D722EDB8B08F1DFC3"),
+            Some(int(
+                "8542D69E4C044F18E8B92435BF6FF7DD297720630485628D5AE74EE7C32E79B7",
+            )),
+            Some(g),
+        )
+    }
+
+    #[test]
+    fn test_sm2p256v1() {
+        let ec = curves::sm2p256v1();
+        let g = ec.get_ref_p();
+        assert!(ec.on_curve(g));
+        assert!(ec.ladder(g, ec.get_ref_n()).is_infinity());
+    }
+
+    // GB/T 32918.2, appendix A.1
+    #[test]
+    fn test_example_signature() {
+        let ec = example_curve();
+        let key = SigningKey::from_scalar(
+            ec.clone(),
+            int("128B2FA8BD433C6C068C8D803DFF79792A519A55171B1B650C23661D15897263"),
+            b"ALICE123@YAHOO.COM",
+        )
+        .unwrap();
+        let vk = key.verifying_key();
+        assert_eq!(
+            vk.as_point().get_xy(&ec),
+            (
+                int("0AE4C7798AA0F119471BEE11825BE46202BB79E2A5844495E97C04FF4DF2548A"),
+                int("7C0240F88F1CD4E16352A73C17B7F16F07353E53A176D684A9FE0C6BB798E857")
+            )
+        );
+        assert_eq!(
+            hex::encode(vk.z()),
+            "f4a38489e32b45b6f876e3ac2168ca392362dc8f23459c1d1146fc3dbfb7bc9a"
+        );
+        assert_eq!(
+            vk.digest(b"message digest"),
+            int("B524F552CD82B8B028476E005C377FB19A87E6FC682D48BB5D42E3D9B9EFFE76")
+        );
+
+        let k = int("6CB28D99385C175C94F94E934817663FC176D925DD72B727260DBAAE1FB2F96F");
+        let sign = key.sign_with_k(b"message digest", &k).unwrap();
+        assert_eq!(
+            sign,
+            (
+                int("40F1EC59F793D9F49E09DCEF49130D4194F79FB1EED2CAA55BACDB49C4E755D1"),
+                int("6FC6DAC32C5D5CF10C77DFB20F7C2EB667A457872FB09EC56327A67EC7DEEBE7")
+            )
+        );
+        vk.verify(b"message digest", &sign).unwrap();
+        assert!(vk.verify(b"message digesT", &sign).is_err());
+
+        // the same key under another ID has another Z
+        let other =
+            VerifyingKey::from_point(ec, vk.as_point().clone(), b"BILL456@YAHOO.COM").unwrap();
+        assert!(other.verify(b"message digest", &sign).is_err());
+    }
+
+    // GB/T 32918.4, appendix A.2
+    #[test]
+    fn test_example_encryption() {
+        let ec = example_curve();
+        let d_b = int("1649AB77A00637BD5E2EFE283FBF353534AA7F7CB89463F208DDBC2920BB0DA0");
+        let p_b = ec.scalar_mul(ec.get_ref_p(), &d_b);
+        assert_eq!(
+            p_b.get_xy(&ec),
+            (
+                int("435B39CCA8F3B508C1488AFC67BE491A0F7BA07E581A0E4849A5CF70628A7E0A"),
+                int("75DDBA78F15FEECB4C7895E2C1CDF5FE01DEBB2CDBADF45399CCF77BBA076A42")
+            )
+        );
+        let k = int("4C62EEFD6ECFC2B95B92FD6C3D9575148AFA17425546D49018E5388D49DD7B4F");
+        let c = encrypt_with_k(&ec, &p_b, b"encryption standard", &k).unwrap();
+        assert_eq!(
+            hex::encode(&c),
+            [
+                "04",
+                "245c26fb68b1ddddb12c4b6bf9f2b6d5fe60a383b0d18d1c4144abf17f6252e7",
+                "76cb9264c2a7e88e52b19903fdc47378f605e36811f5c07423a24b84400f01b8",
+                "9c3d7360c30156fab7c80a0276712da9d8094a634b766d3a285e07480653426d",
+                "650053a89b41c418b0c3aad00d886c00286467",
+            ]
+            .concat()
+        );
+        assert_eq!(decrypt(&ec, &d_b, &c).unwrap(), b"encryption standard");
+    }
+
+    // GB/T 32918.3, appendix A.2; A initiates
+    #[test]
+    fn test_example_key_exchange() {
+        let ec = example_curve();
+        let (id_a, id_b) = (b"ALICE123@YAHOO.COM", b"BILL456@YAHOO.COM");
+        let d_a = int("6FCBA2EF9AE0AB902BC3BDE3FF915D44BA4CC78F88E2F8E7F8996D3B8CCEEDEE");
+        let d_b = int("5E35D7D3F3C54DBAC72E61819E730B019A84208CA3A35E4C2E353DFCCB2A3B53");
+        let p_a = ec.scalar_mul(ec.get_ref_p(), &d_a);
+        let p_b = ec.scalar_mul(ec.get_ref_p(), &d_b);
+        assert_eq!(
+            hex::encode(z_value(&ec, id_a, &p_a).unwrap()),
+            "e4d1d0c3ca4c7f11bc8ff8cb3f4c02a78f108fa098e51a668487240f75e20f31"
+        );
+        assert_eq!(
+            hex::encode(z_value(&ec, id_b, &p_b).unwrap()),
+            "6b4b6d0e276691bd4a11bf72f4fb501ae309fdacb72fa6cc336e6656119abd67"
+        );
+
+        let alice = KeyExchange::from_scalars(
+            ec.clone(),
+            d_a,
+            id_a,
+            int("83A2C9C8B96E5AF70BD480B472409A9A327257F1EBB73F5B073354B248668563"),
+            true,
+        )
+        .unwrap();
+        let bob = KeyExchange::from_scalars(
+            ec.clone(),
+            d_b,
+            id_b,
+            int("33FE21940342161C55619C4A0C060293D543C80AF19748CE176D83477DE71C80"),
+            false,
+        )
+        .unwrap();
+        let (r_a, r_b) = (alice.send(), bob.send());
+        assert_eq!(
+            r_a.get_xy(&ec).0,
+            int("6CB5633816F4DD560B1DEC458310CBCC6856C09505324A6D23150C408F162BF0")
+        );
+        assert_eq!(
+            r_b.get_xy(&ec).0,
+            int("1799B2A2C778295300D9A2325C686129B8F2B5337B3DCF4514E8BBC19D900EE5")
+        );
+
+        let at_b = bob.recieve(&p_a, id_a, &r_a, 16).unwrap();
+        let at_a = alice.recieve(&p_b, id_b, &r_b, 16).unwrap();
+        assert_eq!(hex::encode(&at_b.key), "55b0ac62a6b927ba23703832c853ded4");
+        assert_eq!(at_a.key, at_b.key);
+        assert_eq!(
+            hex::encode(&at_b.confirmation),
+            "284c8f198f141b502e81250f1581c7e9eeb4ca6990f9e02df388b45471f5bc5c"
+        );
+        assert_eq!(
+            hex::encode(&at_a.confirmation),
+            "23444daf8ed7534366cb901c84b3bdbb63504f4065c1116c91a4c00697e6cf7a"
+        );
+        at_a.check(&at_b.confirmation).unwrap();
+        at_b.check(&at_a.confirmation).unwrap();
+        assert!(at_a.check(&at_a.confirmation).is_err());
+
+        // a wrong peer ID gives another key
+        let wrong = bob.recieve(&p_a, id_b, &r_a, 16).unwrap();
+        assert_ne!(wrong.key, at_a.key);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let ec = curves::sm2p256v1();
+        let mut rng = StdRng::seed_from_u64(32918);
+
+        let key = SigningKey::random_with_rng(ec.clone(), DEFAULT_ID, &mut rng).unwrap();
+        let vk = key.verifying_key();
+        let sign = key.sign_with_rng(b"message", &mut rng).unwrap();
+        vk.verify(b"message", &sign).unwrap();
+        assert!(vk.verify(b"massage", &sign).is_err());
+        let (r, s) = sign;
+        assert!(vk.verify(b"message", &(s.clone(), r.clone())).is_err());
+        assert!(vk.verify(b"message", &(r, ec.get_ref_n() - s)).is_err());
+        assert!(SigningKey::from_scalar(ec.clone(), ec.get_ref_n() - 1u32, DEFAULT_ID).is_err());
+
+        let d_b = rng.gen_bigint_range(&bui::one(), ec.get_ref_n());
+        let p_b = ec.scalar_mul(ec.get_ref_p(), &d_b);
+        for m in [&b"x"[..], &[7u8; 33], &[0u8; 100]] {
+            let c = encrypt_with_rng(&ec, &p_b, m, &mut rng).unwrap();
+            assert_eq!(c.len(), 1 + 64 + 32 + m.len());
+            assert_eq!(decrypt(&ec, &d_b, &c).unwrap(), m);
+            for i in [0, 1, 70, c.len() - 1] {
+                let mut tampered = c.clone();
+                tampered[i] ^= 1;
+                assert!(decrypt(&ec, &d_b, &tampered).is_err(), "{i}");
+            }
+            assert!(decrypt(&ec, &d_b, &c[..97]).is_err());
+        }
+        assert!(encrypt_with_rng(&ec, &p_b, b"", &mut rng).is_err());
+
+        let d_a = rng.gen_bigint_range(&bui::one(), ec.get_ref_n());
+        let p_a = ec.scalar_mul(ec.get_ref_p(), &d_a);
+        let a = KeyExchange::init_with_rng(ec.clone(), d_a, DEFAULT_ID, true, &mut rng).unwrap();
+        let b = KeyExchange::init_with_rng(ec, d_b, DEFAULT_ID, false, &mut rng).unwrap();
+        let at_a = a.recieve(&p_b, DEFAULT_ID, &b.send(), 48).unwrap();
+        let at_b = b.recieve(&p_a, DEFAULT_ID, &a.send(), 48).unwrap();
+        assert_eq!(at_a.key.len(), 48);
+        assert_eq!(at_a.key, at_b.key);
+        at_a.check(&at_b.confirmation).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+// Incremental ECDSA over messages too large to hold in memory: the message is
+// fed through any `Digest` in chunks and the signature is made or checked on
+// the final digest. Both sides implement io::Write, so io::copy from a file
+// works as well.
+
+use std::io;
+
+use crate::sign_ecdsa::{SigningKey, VerifyingKey};
+
+use num_bigint::BigInt as bui;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+pub struct StreamingSigner<'a, D: Digest = Sha256> {
+    key: &'a SigningKey,
+    hasher: D,
+}
+
+pub struct StreamingVerifier<'a, D: Digest = Sha256> {
+    key: &'a VerifyingKey,
+    hasher: D,
+}
+
+impl<'a, D: Digest> StreamingSigner<'a, D> {
+    pub fn new(key: &'a SigningKey) -> Self {
+        Self {
+            key,
+            hasher: D::new(),
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    pub fn finalize(self) -> Result<(bui, bui), String> {
+        self.finalize_with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn finalize_with_rng(
+        self,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(bui, bui), String> {
+        self.key.sign_prehash_with_rng(&self.hasher.finalize(), rng)
+    }
+}
+
+impl<'a, D: Digest> StreamingVerifier<'a, D> {
+    pub fn new(key: &'a VerifyingKey) -> Self {
+        Self {
+            key,
+            hasher: D::new(),
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    pub fn finalize(self, sign: &(bui, bui)) -> Result<(), String> {
+        self.key.verify_digest(&self.hasher.finalize(), sign)
+    }
+}
+
+impl<D: Digest> io::Write for StreamingSigner<'_, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<D: Digest> io::Write for StreamingVerifier<'_, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves;
+    use crate::signature::Verifier;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use sha2::{Sha224, Sha384, Sha512};
+
+    fn message(len: usize) -> Vec<u8> {
+        let mut rng = StdRng::seed_from_u64(36);
+        (0..len).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let m = message(100_000);
+        let key = SigningKey::random(curves::p256());
+
+        let mut signer = StreamingSigner::<Sha256>::new(&key);
+        for chunk in m.chunks(4093) {
+            signer.update(chunk);
+        }
+        let sign = signer
+            .finalize_with_rng(&mut StdRng::seed_from_u64(7))
+            .unwrap();
+        assert_eq!(
+            sign,
+            key.sign_with_rng(&m, &mut StdRng::seed_from_u64(7))
+                .unwrap()
+        );
+        assert!(key.verifying_key().verify(&m, &sign).is_ok());
+
+        let mut verifier = StreamingVerifier::<Sha256>::new(key.verifying_key());
+        for chunk in m.chunks(1000) {
+            verifier.update(chunk);
+        }
+        assert!(verifier.finalize(&sign).is_ok());
+
+        let mut verifier = StreamingVerifier::<Sha256>::new(key.verifying_key());
+        verifier.update(&m[1..]);
+        assert!(verifier.finalize(&sign).is_err());
+    }
+
+    #[test]
+    fn test_streaming_other_digests() {
+        let m = message(10_000);
+        let key = SigningKey::random(curves::p384());
+
+        let mut signer = StreamingSigner::<Sha384>::new(&key);
+        io::copy(&mut io::Cursor::new(&m), &mut signer).unwrap();
+        let sign = signer.finalize().unwrap();
+        assert!(key
+            .verifying_key()
+            .verify_digest(&Sha384::digest(&m), &sign)
+            .is_ok());
+
+        let mut verifier = StreamingVerifier::<Sha384>::new(key.verifying_key());
+        io::copy(&mut io::Cursor::new(&m), &mut verifier).unwrap();
+        assert!(verifier.finalize(&sign).is_ok());
+
+        // the same bytes under another hash do not verify
+        let mut verifier = StreamingVerifier::<Sha512>::new(key.verifying_key());
+        verifier.update(&m);
+        assert!(verifier.finalize(&sign).is_err());
+
+        // SHA-512 on P-256 is truncated to the leftmost 256 bits, SHA-224 is
+        // used as is
+        let key = SigningKey::random(curves::p256());
+        for digest in [Sha512::digest(&m).to_vec(), Sha224::digest(&m).to_vec()] {
+            let sign = key.sign_prehash(&digest).unwrap();
+            assert!(key.verifying_key().verify_digest(&digest, &sign).is_ok());
+        }
+        assert!(key.sign_prehash(&[]).is_err());
+        let sign = key.sign_prehash(&[0; 32]).unwrap();
+        assert_eq!(
+            key.verifying_key().verify_digest(&[], &sign),
+            Err(String::from("Digest is empty"))
+        );
+    }
+}
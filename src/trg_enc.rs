@@ -1,62 +1,324 @@
 #![allow(unused)]
-use crate::ec::{Point, EC};
 
+// ECIES (SEC 1, 5.1; ISO/IEC 18033-2, 10.2). The sender picks an ephemeral
+// r, sends R = r G and derives K with the ANSI X9.63 KDF over SHA-256. The
+// KEM decides what goes into the KDF:
+//
+//   EciesX963Sha256:        K = KDF(x(r Q_B), R)
+//   UnifiedModelX963Sha256: K = KDF(x(r Q_B) || x(d_A Q_B), R || Q_A || Q_B)
+//
+// The second is the one-pass Unified Model of NIST SP 800-56A, 6.2.1.2. The
+// DEM is AES-256-GCM under K; R makes the key fresh for every message, so
+// the nonce is fixed at zero.
+//
+// Ciphertexts travel in a versioned container, all lengths big-endian:
+//
+//   "TRGE" | version | len | curve OID | KEM | DEM | len (2 bytes) | R | C || T
+//
+// with R uncompressed and T the 16-byte GCM tag. Everything before C is the
+// GCM associated data, so a change to any header byte fails authentication.
+
+use crate::bigint_utils::Wipe;
+use crate::curves::CurveId;
+use crate::ec::{to_fixed_be, Point, EC};
+use crate::keygen::{gen_key_pair_with_rng, KeyGenMethod};
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
 use num_bigint::BigInt as bui;
 use num_bigint::RandBigInt;
-use num_bigint::ToBigInt;
+use num_traits::One;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+const MAGIC: &[u8; 4] = b"TRGE";
+const VERSION: u8 = 1;
+const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+
+// ANSI X9.63 KDF: h(Z || ct || SharedInfo) for ct = 1, 2, ... as 32-bit
+// big-endian counters, concatenated and cut to klen bytes
+pub fn kdf_x963<D: Digest>(z: &[u8], shared_info: &[u8], klen: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(klen + <D as Digest>::output_size());
+    let mut ct: u32 = 1;
+    while out.len() < klen {
+        let mut hasher = D::new();
+        hasher.update(z);
+        hasher.update(ct.to_be_bytes());
+        hasher.update(shared_info);
+        out.extend(hasher.finalize());
+        ct += 1;
+    }
+    out.truncate(klen);
+    out
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kem {
+    // Anonymous ephemeral-static. Only the holder of d_B can read the
+    // message and any change is detected, but anyone can encrypt: the
+    // recipient learns nothing about the sender. Leaking d_B opens every
+    // past message.
+    EciesX963Sha256 = 1,
+    // Static-static plus ephemeral. On top of the above the recipient knows
+    // the message came from the holder of d_A. It is not a signature: the
+    // recipient can compute the same key and forge messages "from" A to
+    // itself, and a leaked d_B lets anyone impersonate A to B. Replays are
+    // not detected in either mode.
+    UnifiedModelX963Sha256 = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dem {
+    Aes256Gcm = 1,
+}
+
+impl Kem {
+    fn from_u8(b: u8) -> Result<Kem, String> {
+        match b {
+            1 => Ok(Kem::EciesX963Sha256),
+            2 => Ok(Kem::UnifiedModelX963Sha256),
+            _ => Err(format!("Unknown KEM {b}")),
+        }
+    }
+}
+
+impl Dem {
+    fn from_u8(b: u8) -> Result<Dem, String> {
+        match b {
+            1 => Ok(Dem::Aes256Gcm),
+            _ => Err(format!("Unknown DEM {b}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Container {
+    pub curve: CurveId,
+    pub kem: Kem,
+    pub dem: Dem,
+    pub ephemeral: Vec<u8>,
+    pub body: Vec<u8>,
+}
+
+impl Container {
+    // Everything up to the DEM output, also the GCM associated data
+    fn header(&self) -> Vec<u8> {
+        let oid = self.curve.oid();
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        out.push(oid.len() as u8);
+        out.extend_from_slice(oid);
+        out.push(self.kem as u8);
+        out.push(self.dem as u8);
+        out.extend((self.ephemeral.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.ephemeral);
+        out
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.header();
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    // Checks the layout only; R is checked against the curve on decryption
+    pub fn from_bytes(bytes: &[u8]) -> Result<Container, String> {
+        let mut rest = bytes;
+        let mut take = |len: usize| -> Result<&[u8], String> {
+            if rest.len() < len {
+                return Err(String::from("Container is truncated"));
+            }
+            let (head, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(head)
+        };
+
+        if take(MAGIC.len())? != MAGIC {
+            return Err(String::from("Not a TrgEnc container"));
+        }
+        let version = take(1)?[0];
+        if version != VERSION {
+            return Err(format!("Unsupported container version {version}"));
+        }
+        let oid_len = take(1)?[0] as usize;
+        let curve = CurveId::from_oid(take(oid_len)?).ok_or("Unknown curve")?;
+        let kem = Kem::from_u8(take(1)?[0])?;
+        let dem = Dem::from_u8(take(1)?[0])?;
+        let r_len = take(2)?;
+        let r_len = u16::from_be_bytes([r_len[0], r_len[1]]) as usize;
+        if r_len != 1 + 2 * curve.ec().field_len() {
+            return Err(String::from("Invalid ephemeral key length"));
+        }
+        let ephemeral = take(r_len)?.to_vec();
+        if rest.len() < TAG_LEN {
+            return Err(String::from("Container is truncated"));
+        }
+
+        Ok(Self {
+            curve,
+            kem,
+            dem,
+            ephemeral,
+            body: rest.to_vec(),
+        })
+    }
+}
 
 struct TrgEnc {
+    curve: CurveId,
     ec: EC,
     q_a: Option<Point>,
     e_a: Option<bui>,
 }
 
-fn wrap_dec(x: &bui, k: &bui) -> bui {
-    x ^ k
-}
-
 impl TrgEnc {
     fn get_ref_q_a(&self) -> &Point {
         self.q_a.as_ref().expect("Dont have q_a")
     }
 
-    fn init(ec: EC) -> TrgEnc {
-        let mut rng = rand::thread_rng();
-
-        let e_a = rng.gen_bigint_range(&(2).to_bigint().unwrap(), ec.get_ref_n());
+    fn init(curve: CurveId) -> TrgEnc {
+        Self::init_with_rng(curve, &mut rand::thread_rng())
+    }
 
-        let q_a = ec.scalar_mul(ec.get_ref_p(), &e_a);
+    fn init_with_rng(curve: CurveId, rng: &mut (impl RngCore + CryptoRng)) -> TrgEnc {
+        let ec = curve.ec();
+        let (e_a, q_a) = gen_key_pair_with_rng(&ec, KeyGenMethod::default(), rng)
+            .expect("key generation failed");
 
         Self {
+            curve,
             ec,
             q_a: Some(q_a),
             e_a: Some(e_a),
         }
     }
 
-    fn enc(&self, m: &bui, q_b: &Point) -> (bui, bui) {
-        let ec = &self.ec;
-        let p = ec.get_ref_p();
-        let e_a = self.e_a.as_ref().unwrap();
-        let mut rng = rand::thread_rng();
+    // x(Z) for the shared point Z
+    fn shared_x(&self, z: &Point) -> Result<Vec<u8>, String> {
+        if z.is_infinity() {
+            return Err(String::from("Shared point is the point at infinity"));
+        }
+        let (z_x, _) = z.get_xy(&self.ec);
+        Ok(to_fixed_be(&z_x, self.ec.field_len()))
+    }
 
-        let k = rng.gen_bigint(256);
-        let c_m = wrap_dec(m, &k);
+    // K for the container's KEM. z_e is the ephemeral-static shared point;
+    // the Unified Model also takes the static-static one and both keys.
+    fn dem_key(
+        &self,
+        container: &Container,
+        z_e: &Point,
+        z_s: Option<&Point>,
+        q_a: Option<&Point>,
+        q_b: &Point,
+    ) -> Result<Vec<u8>, String> {
+        let ec = &self.ec;
+        let mut z = self.shared_x(z_e)?;
+        let mut shared_info = container.ephemeral.clone();
+        if container.kem == Kem::UnifiedModelX963Sha256 {
+            let (z_s, q_a) = z_s
+                .zip(q_a)
+                .ok_or("The Unified Model needs the sender key")?;
+            z.extend(self.shared_x(z_s)?);
+            shared_info.extend(ec.encode_point(q_a, false));
+            shared_info.extend(ec.encode_point(q_b, false));
+        }
+        let k = kdf_x963::<Sha256>(&z, &shared_info, KEY_LEN);
+        z.iter_mut().for_each(|b| *b = 0);
+        Ok(k)
+    }
 
-        let (s_x, _) = ec.scalar_mul(q_b, e_a).get_xy(ec);
-        let c_k = wrap_dec(&k, &s_x);
+    fn enc(&self, m: &[u8], q_b: &Point, kem: Kem) -> Result<Vec<u8>, String> {
+        self.enc_with_rng(m, q_b, kem, &mut rand::thread_rng())
+    }
 
-        (c_k, c_m)
+    fn enc_with_rng(
+        &self,
+        m: &[u8],
+        q_b: &Point,
+        kem: Kem,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, String> {
+        let mut r = rng.gen_bigint_range(&bui::one(), self.ec.get_ref_n());
+        let c = self.enc_with_k(m, q_b, kem, &r);
+        r.wipe();
+        c
     }
 
-    fn dec(&self, q_a: &Point, c_k: &bui, c_m: &bui) -> bui {
-        let d_b = self.e_a.as_ref().unwrap();
+    // Deterministic core of encryption for the ephemeral scalar r. In the
+    // Unified Model the sender's static key is this instance's key pair.
+    fn enc_with_k(&self, m: &[u8], q_b: &Point, kem: Kem, r: &bui) -> Result<Vec<u8>, String> {
         let ec = &self.ec;
+        ec.validate_public_key(q_b)?;
+        let mut container = Container {
+            curve: self.curve,
+            kem,
+            dem: Dem::Aes256Gcm,
+            ephemeral: ec.encode_point(&ec.scalar_mul(ec.get_ref_p(), r), false),
+            body: Vec::new(),
+        };
+        let z_e = ec.scalar_mul(q_b, r);
+        let mut k = match kem {
+            Kem::EciesX963Sha256 => self.dem_key(&container, &z_e, None, None, q_b)?,
+            Kem::UnifiedModelX963Sha256 => {
+                let d_a = self.e_a.as_ref().ok_or("Sender has no static key")?;
+                let z_s = ec.scalar_mul(q_b, d_a);
+                self.dem_key(&container, &z_e, Some(&z_s), self.q_a.as_ref(), q_b)?
+            }
+        };
 
-        let (s_x, _) = ec.scalar_mul(q_a, d_b).get_xy(ec);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&k));
+        k.iter_mut().for_each(|b| *b = 0);
+        let aad = container.header();
+        container.body = cipher
+            .encrypt(Nonce::from_slice(&[0; 12]), Payload { msg: m, aad: &aad })
+            .map_err(|_| String::from("Encryption failed"))?;
+        Ok(container.to_bytes())
+    }
 
-        let k = wrap_dec(c_k, &s_x);
-        wrap_dec(c_m, &k)
+    // `sender` is the static key the caller expects the message to come
+    // from. It must be given exactly when the container is authenticated, so
+    // an anonymous container cannot pass for an authenticated one.
+    fn dec(&self, c: &[u8], sender: Option<&Point>) -> Result<Vec<u8>, String> {
+        let ec = &self.ec;
+        let d_b = self.e_a.as_ref().unwrap();
+        let container = Container::from_bytes(c)?;
+        if container.curve != self.curve {
+            return Err(String::from("Container is for another curve"));
+        }
+        let r = ec.decode_point(&container.ephemeral)?;
+        ec.validate_public_key(&r)?;
+        let z_e = ec.scalar_mul(&r, d_b);
+        let mut k = match (container.kem, sender) {
+            (Kem::EciesX963Sha256, None) => {
+                self.dem_key(&container, &z_e, None, None, self.get_ref_q_a())?
+            }
+            (Kem::UnifiedModelX963Sha256, Some(q_a)) => {
+                ec.validate_public_key(q_a)?;
+                let z_s = ec.scalar_mul(q_a, d_b);
+                self.dem_key(&container, &z_e, Some(&z_s), Some(q_a), self.get_ref_q_a())?
+            }
+            (Kem::EciesX963Sha256, Some(_)) => {
+                return Err(String::from("Container is not sender-authenticated"))
+            }
+            (Kem::UnifiedModelX963Sha256, None) => {
+                return Err(String::from("Container needs the sender's static key"))
+            }
+        };
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&k));
+        k.iter_mut().for_each(|b| *b = 0);
+        let aad = container.header();
+        cipher
+            .decrypt(
+                Nonce::from_slice(&[0; 12]),
+                Payload {
+                    msg: &container.body,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| String::from("Authentication failed"))
     }
 }
 
@@ -64,52 +326,34 @@ impl TrgEnc {
 mod tests {
     use super::*;
     use crate::bigint_utils::FromHex;
-    use num_bigint::ToBigInt;
-    use num_traits::{One, Zero};
+    use crate::curves;
+    use rand::{rngs::StdRng, SeedableRng};
     use std::time::{Duration, Instant};
 
     #[test]
     fn test_trg_enc_dec() {
-        let p = Point::new(
-            bui::from_hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296")
-                .unwrap(),
-            bui::from_hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5")
-                .unwrap(),
-            Some(bui::one()),
-        );
-        let a = bui::from_hex("ffffffff00000001000000000000000000000000fffffffffffffffffffffffc")
-            .unwrap();
-        let b = bui::from_hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b")
-            .unwrap();
-        let q = bui::from_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff")
-            .unwrap();
-        let n = bui::from_hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551")
-            .unwrap();
-        let ec_p256 = EC::new(a, b, q, Some(n), Some(p.clone()));
-
         let mut total_enc = Duration::ZERO;
         let mut total_dec = Duration::ZERO;
         let t = 100;
 
         for _ in 0..t {
-            let Bob = TrgEnc::init(ec_p256.clone());
-            let Alice = TrgEnc::init(ec_p256.clone());
+            let bob = TrgEnc::init(CurveId::P256);
+            let alice = TrgEnc::init(CurveId::P256);
 
             let mut rng = rand::thread_rng();
-            let message = rng.gen_bigint(256);
+            let mut message = vec![0u8; 32];
+            rng.fill_bytes(&mut message);
 
-            let q_b = Alice.get_ref_q_a();
+            let q_b = alice.get_ref_q_a();
 
             let start = Instant::now();
-            let (c_k, c_m) = Bob.enc(&message, q_b);
+            let c = bob.enc(&message, q_b, Kem::EciesX963Sha256).unwrap();
 
             total_enc += start.elapsed();
 
-            let q_a = Bob.get_ref_q_a();
-
             let start = Instant::now();
 
-            let decrypted = Alice.dec(q_a, &c_k, &c_m);
+            let decrypted = alice.dec(&c, None).unwrap();
 
             total_dec += start.elapsed();
 
@@ -119,4 +363,279 @@ mod tests {
         println!("time enc: {:?}", total_enc / t);
         println!("time dec: {:?}", total_dec / t);
     }
+
+    // NIST CAVS X9.63 KDF, SHA-256 with empty SharedInfo
+    #[test]
+    fn test_kdf_x963() {
+        let z = hex::decode("96c05619d56c328ab95fe84b18264b08725b85e33fd34f08").unwrap();
+        assert_eq!(
+            hex::encode(kdf_x963::<Sha256>(&z, &[], 16)),
+            "443024c3dae66b95e6f5670601558f71"
+        );
+    }
+
+    // Cross-checked against pyca/cryptography's X963KDF and AESGCM
+    #[test]
+    fn test_trg_enc_known_answer() {
+        let ec = curves::p256();
+        let d_b = bui::from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")
+            .unwrap();
+        let q_b = ec.scalar_mul(ec.get_ref_p(), &d_b);
+        assert_eq!(
+            hex::encode(ec.encode_point(&q_b, false)),
+            "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+        );
+        let bob = TrgEnc {
+            curve: CurveId::P256,
+            ec,
+            q_a: Some(q_b.clone()),
+            e_a: Some(d_b),
+        };
+        let r = bui::from_hex("6b3c5ea8f9b2bcd2c1e0bd5ae8b6c41c8b7f6f4d0f8c3a2e1d5b9c7a3e2f1d0c")
+            .unwrap();
+        let message = b"Attack at dawn";
+
+        let c = bob
+            .enc_with_k(message, &q_b, Kem::EciesX963Sha256, &r)
+            .unwrap();
+        assert_eq!(
+            hex::encode(&c),
+            "5452474501082a8648ce3d03010701010041041ee100a1be7ea8d2c9da2cfaffdc9132edb3cbb7fad3a5df2f43db83b09e3cf246ec796e0a4ea08f7cdf509b4698695eea1e99a7b839f8a50216a62bbc5c1bc5736ced7af251d120ec62760ad9eead484188d8512c1b125a4781c285c638"
+        );
+        assert_eq!(bob.dec(&c, None).unwrap(), message);
+    }
+
+    #[test]
+    fn test_trg_enc_lengths() {
+        let mut rng = StdRng::seed_from_u64(48);
+        for curve in CurveId::ALL {
+            let alice = TrgEnc::init_with_rng(curve, &mut rng);
+            for len in [0, 1, 15, 16, 17, 1000] {
+                let mut m = vec![0u8; len];
+                rng.fill_bytes(&mut m);
+                let c = alice
+                    .enc_with_rng(&m, alice.get_ref_q_a(), Kem::EciesX963Sha256, &mut rng)
+                    .unwrap();
+                let container = Container::from_bytes(&c).unwrap();
+                assert_eq!(container.curve, curve);
+                assert_eq!(container.body.len(), len + TAG_LEN);
+                assert_eq!(container.to_bytes(), c);
+                assert_eq!(alice.dec(&c, None).unwrap(), m, "{} {len}", curve.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_trg_enc_tampering() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let bob = TrgEnc::init_with_rng(CurveId::P256, &mut rng);
+        let alice = TrgEnc::init_with_rng(CurveId::P256, &mut rng);
+        let message = b"some file contents".to_vec();
+
+        let c = bob
+            .enc_with_rng(
+                &message,
+                alice.get_ref_q_a(),
+                Kem::EciesX963Sha256,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(alice.dec(&c, None).unwrap(), message);
+        // a fresh ephemeral key every time
+        let c_2 = bob
+            .enc_with_rng(
+                &message,
+                alice.get_ref_q_a(),
+                Kem::EciesX963Sha256,
+                &mut rng,
+            )
+            .unwrap();
+        let ephemeral = |c: &[u8]| Container::from_bytes(c).unwrap().ephemeral;
+        assert_ne!(ephemeral(&c), ephemeral(&c_2));
+
+        // only the recipient can decrypt
+        assert!(bob.dec(&c, None).is_err());
+        for i in 0..c.len() {
+            let mut tampered = c.clone();
+            tampered[i] ^= 0x01;
+            assert!(alice.dec(&tampered, None).is_err(), "byte {i}");
+        }
+        for len in 0..c.len() {
+            assert!(alice.dec(&c[..len], None).is_err(), "length {len}");
+        }
+        let mut extended = c.clone();
+        extended.push(0);
+        assert!(alice.dec(&extended, None).is_err());
+
+        // a container for another curve is refused before any arithmetic
+        let carol = TrgEnc::init_with_rng(CurveId::P384, &mut rng);
+        let c = carol
+            .enc_with_rng(
+                &message,
+                carol.get_ref_q_a(),
+                Kem::EciesX963Sha256,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(
+            alice.dec(&c, None),
+            Err(String::from("Container is for another curve"))
+        );
+    }
+
+    // Same keys and nonce as above with A's static key mixed in, cross-checked
+    // against pyca/cryptography
+    #[test]
+    fn test_unified_model_known_answer() {
+        let curve = CurveId::P256;
+        let key_pair = |d: &str| {
+            let ec = curve.ec();
+            let d = bui::from_hex(d).unwrap();
+            let q = ec.scalar_mul(ec.get_ref_p(), &d);
+            TrgEnc {
+                curve,
+                ec,
+                q_a: Some(q),
+                e_a: Some(d),
+            }
+        };
+        let alice = key_pair("519b423d715f8b581f4fa8ee59f4771a5b44c8130b4e3eacca54a56dda72b464");
+        let bob = key_pair("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721");
+        assert_eq!(
+            hex::encode(alice.ec.encode_point(alice.get_ref_q_a(), false)),
+            "041ccbe91c075fc7f4f033bfa248db8fccd3565de94bbfb12f3c59ff46c271bf83ce4014c68811f9a21a1fdb2c0e6113e06db7ca93b7404e78dc7ccd5ca89a4ca9"
+        );
+        let r = bui::from_hex("6b3c5ea8f9b2bcd2c1e0bd5ae8b6c41c8b7f6f4d0f8c3a2e1d5b9c7a3e2f1d0c")
+            .unwrap();
+        let message = b"Attack at dawn";
+
+        let c = alice
+            .enc_with_k(message, bob.get_ref_q_a(), Kem::UnifiedModelX963Sha256, &r)
+            .unwrap();
+        assert_eq!(
+            hex::encode(&c),
+            "5452474501082a8648ce3d03010702010041041ee100a1be7ea8d2c9da2cfaffdc9132edb3cbb7fad3a5df2f43db83b09e3cf246ec796e0a4ea08f7cdf509b4698695eea1e99a7b839f8a50216a62bbc5c1bc559daaedd0dcaad81d77c1ec33e02a820d94684066415547ef0764b281a2f"
+        );
+        assert_eq!(bob.dec(&c, Some(alice.get_ref_q_a())).unwrap(), message);
+    }
+
+    #[test]
+    fn test_modes() {
+        let mut rng = StdRng::seed_from_u64(49);
+        let alice = TrgEnc::init_with_rng(CurveId::P256, &mut rng);
+        let bob = TrgEnc::init_with_rng(CurveId::P256, &mut rng);
+        let mallory = TrgEnc::init_with_rng(CurveId::P256, &mut rng);
+        let q_a = alice.get_ref_q_a();
+        let q_b = bob.get_ref_q_a();
+        let message = b"from alice";
+
+        // anonymous: Mallory's ciphertext is as good as Alice's, and neither
+        // says who sent it
+        for sender in [&alice, &mallory] {
+            let c = sender
+                .enc_with_rng(message, q_b, Kem::EciesX963Sha256, &mut rng)
+                .unwrap();
+            assert_eq!(bob.dec(&c, None).unwrap(), message);
+            assert_eq!(
+                bob.dec(&c, Some(q_a)),
+                Err(String::from("Container is not sender-authenticated"))
+            );
+        }
+
+        // authenticated: only Alice's static key opens it
+        let c = alice
+            .enc_with_rng(message, q_b, Kem::UnifiedModelX963Sha256, &mut rng)
+            .unwrap();
+        assert_eq!(bob.dec(&c, Some(q_a)).unwrap(), message);
+        assert_eq!(
+            bob.dec(&c, None),
+            Err(String::from("Container needs the sender's static key"))
+        );
+        assert!(bob.dec(&c, Some(mallory.get_ref_q_a())).is_err());
+        let forged = mallory
+            .enc_with_rng(message, q_b, Kem::UnifiedModelX963Sha256, &mut rng)
+            .unwrap();
+        assert!(bob.dec(&forged, Some(q_a)).is_err());
+        // relabelling the container's mode breaks the tag
+        let mut relabelled = c.clone();
+        relabelled[14] = Kem::EciesX963Sha256 as u8;
+        assert!(bob.dec(&relabelled, None).is_err());
+
+        // a fresh ephemeral key still gives every message its own key
+        let c_2 = alice
+            .enc_with_rng(message, q_b, Kem::UnifiedModelX963Sha256, &mut rng)
+            .unwrap();
+        assert_ne!(c, c_2);
+
+        // no non-repudiation: Bob can make a ciphertext that opens as Alice's
+        // by deriving the same key with d_B and R
+        let r = rng.gen_bigint_range(&bui::one(), bob.ec.get_ref_n());
+        let ec = &bob.ec;
+        let mut container = Container {
+            curve: CurveId::P256,
+            kem: Kem::UnifiedModelX963Sha256,
+            dem: Dem::Aes256Gcm,
+            ephemeral: ec.encode_point(&ec.scalar_mul(ec.get_ref_p(), &r), false),
+            body: Vec::new(),
+        };
+        let d_b = bob.e_a.as_ref().unwrap();
+        let k = bob
+            .dem_key(
+                &container,
+                &ec.scalar_mul(q_b, &r),
+                Some(&ec.scalar_mul(q_a, d_b)),
+                Some(q_a),
+                q_b,
+            )
+            .unwrap();
+        let aad = container.header();
+        container.body = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&k))
+            .encrypt(
+                Nonce::from_slice(&[0; 12]),
+                Payload {
+                    msg: b"forged by bob",
+                    aad: &aad,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            bob.dec(&container.to_bytes(), Some(q_a)).unwrap(),
+            b"forged by bob"
+        );
+    }
+
+    #[test]
+    fn test_container_parsing() {
+        let container = Container {
+            curve: CurveId::P256,
+            kem: Kem::EciesX963Sha256,
+            dem: Dem::Aes256Gcm,
+            ephemeral: vec![4; 65],
+            body: vec![0; TAG_LEN],
+        };
+        let bytes = container.to_bytes();
+        assert_eq!(Container::from_bytes(&bytes).unwrap(), container);
+
+        let with = |i: usize, b: u8| {
+            let mut bytes = bytes.clone();
+            bytes[i] = b;
+            Container::from_bytes(&bytes)
+        };
+        assert_eq!(with(0, b'X'), Err(String::from("Not a TrgEnc container")));
+        assert_eq!(
+            with(4, 2),
+            Err(String::from("Unsupported container version 2"))
+        );
+        assert_eq!(with(7, 0), Err(String::from("Unknown curve")));
+        assert_eq!(with(14, 9), Err(String::from("Unknown KEM 9")));
+        assert_eq!(with(15, 0), Err(String::from("Unknown DEM 0")));
+        assert_eq!(
+            with(17, 33),
+            Err(String::from("Invalid ephemeral key length"))
+        );
+        assert_eq!(
+            Container::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(String::from("Container is truncated"))
+        );
+    }
 }
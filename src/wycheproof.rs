@@ -0,0 +1,341 @@
+// Runner for the Project Wycheproof ECDSA (ecdsa_verify_schema_v1), ECDH
+// (ecdh_test_schema_v1), Ed25519 (eddsa_verify_schema_v1) and X25519/X448
+// (xdh_comp_schema_v1) JSON files. A "valid" case has to pass, an "invalid"
+// one has to be rejected and an "acceptable" one may go either way.
+
+use std::fs;
+use std::path::Path;
+
+use crate::bigint_utils::FromHex;
+use crate::cavp::{hash, Outcome, Report};
+use crate::curves;
+use crate::curves::CurveId;
+use crate::der;
+use crate::dh_exchange::DH;
+use crate::ed25519::{self, Verification};
+use crate::sign_ecdsa::VerifyingKey;
+use crate::signature::Verifier;
+
+use num_bigint::BigInt as bui;
+use serde_json::Value;
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Result<&'a str, String> {
+    value[key].as_str().ok_or_else(|| format!("missing {key}"))
+}
+
+fn bytes_field(value: &Value, key: &str) -> Result<Vec<u8>, String> {
+    hex::decode(str_field(value, key)?).map_err(|e| format!("{key}: {e}"))
+}
+
+fn judge(expected: &str, accepted: Result<(), String>) -> Outcome {
+    match (expected, accepted) {
+        ("acceptable", _) | ("valid", Ok(())) | ("invalid", Err(_)) => Outcome::Pass,
+        ("valid", Err(why)) => Outcome::Fail(why),
+        ("invalid", Ok(())) => Outcome::Fail(String::from("invalid case accepted")),
+        (other, _) => Outcome::Skip(format!("unknown result {other}")),
+    }
+}
+
+fn run(
+    file: &str,
+    json: &Value,
+    group_check: impl Fn(&Value) -> Result<Option<String>, String>,
+    check: impl Fn(&Value, &Value) -> Result<(), String>,
+) -> Result<Report, String> {
+    let groups = json["testGroups"].as_array().ok_or("missing testGroups")?;
+    let mut results = Vec::new();
+    for group in groups {
+        let skip = group_check(group)?;
+        for test in group["tests"].as_array().ok_or("missing tests")? {
+            let name = format!("{file} #{}", test["tcId"]);
+            let outcome = match &skip {
+                Some(why) => Outcome::Skip(why.clone()),
+                None => judge(str_field(test, "result")?, check(group, test)),
+            };
+            results.push((name, outcome));
+        }
+    }
+    Ok(Report { results })
+}
+
+fn group_curve(group: &Value, key: &str) -> Result<Option<CurveId>, String> {
+    let curve = str_field(group, key)?;
+    Ok(CurveId::from_name(curve))
+}
+
+pub fn run_ecdsa(file: &str, json: &Value) -> Result<Report, String> {
+    run(
+        file,
+        json,
+        |group| {
+            let curve = str_field(&group["publicKey"], "curve")?;
+            let sha = str_field(group, "sha")?;
+            Ok(if CurveId::from_name(curve).is_none() {
+                Some(format!("unsupported curve {curve}"))
+            } else if hash(sha, b"").is_none() {
+                Some(format!("unsupported hash {sha}"))
+            } else {
+                None
+            })
+        },
+        |group, test| {
+            let key = &group["publicKey"];
+            let id = group_curve(key, "curve")?.ok_or("unsupported curve")?;
+            let ec = id.ec();
+            let q_a = ec.decode_point(&bytes_field(key, "uncompressed")?)?;
+            let q_a = VerifyingKey::from_point(ec, q_a)?;
+
+            let digest = hash(str_field(group, "sha")?, &bytes_field(test, "msg")?)
+                .ok_or("unsupported hash")?;
+            let sign = der::decode_signature(&bytes_field(test, "sig")?)?;
+            q_a.verify_digest(&digest, &sign)
+        },
+    )
+}
+
+pub fn run_ecdh(file: &str, json: &Value) -> Result<Report, String> {
+    run(
+        file,
+        json,
+        |group| {
+            let curve = str_field(group, "curve")?;
+            let encoding = str_field(group, "encoding")?;
+            Ok(if CurveId::from_name(curve).is_none() {
+                Some(format!("unsupported curve {curve}"))
+            } else if encoding != "asn" {
+                Some(format!("unsupported encoding {encoding}"))
+            } else {
+                None
+            })
+        },
+        |group, test| {
+            let id = group_curve(group, "curve")?.ok_or("unsupported curve")?;
+            let (key_id, point) = der::decode_spki(&bytes_field(test, "public")?)?;
+            if key_id != id {
+                return Err(format!("public key is on {}", key_id.name()));
+            }
+            let ec = id.ec();
+            let q_b = ec.decode_point(&point)?;
+
+            let d =
+                bui::from_hex(str_field(test, "private")?).map_err(|e| format!("private: {e}"))?;
+            let shared = DH::from_scalar(ec.clone(), d)?.recieve(&q_b)?;
+            let mut x = ec.encode_point(&shared, true);
+            x.remove(0);
+            if x == bytes_field(test, "shared")? {
+                Ok(())
+            } else {
+                Err(String::from("shared secret does not match"))
+            }
+        },
+    )
+}
+
+// Ed25519 cases are checked with the cofactorless equation
+pub fn run_eddsa(file: &str, json: &Value) -> Result<Report, String> {
+    run(
+        file,
+        json,
+        |group| {
+            let curve = str_field(&group["publicKey"], "curve")?;
+            Ok((curve != "edwards25519").then(|| format!("unsupported curve {curve}")))
+        },
+        |group, test| {
+            let pk = bytes_field(&group["publicKey"], "pk")?;
+            let q_a = ed25519::VerifyingKey::from_bytes(&pk, Verification::Cofactorless)?;
+            let sign: [u8; 64] = bytes_field(test, "sig")?
+                .try_into()
+                .map_err(|_| String::from("Signature must be 64 bytes"))?;
+            q_a.verify(&bytes_field(test, "msg")?, &sign)
+        },
+    )
+}
+
+// Compares the raw X25519/X448 output; the zero secrets of small-order
+// points are "acceptable" cases
+pub fn run_xdh(file: &str, json: &Value) -> Result<Report, String> {
+    let curve = |group: &Value| match str_field(group, "curve") {
+        Ok("curve25519") => Ok(Some(curves::curve25519())),
+        Ok("curve448") => Ok(Some(curves::curve448())),
+        Ok(_) => Ok(None),
+        Err(e) => Err(e),
+    };
+    run(
+        file,
+        json,
+        |group| {
+            Ok(curve(group)?
+                .is_none()
+                .then(|| format!("unsupported curve {}", group["curve"])))
+        },
+        |group, test| {
+            let curve = curve(group)?.ok_or("unsupported curve")?;
+            let shared = curve.scalar_mul(
+                &bytes_field(test, "private")?,
+                &bytes_field(test, "public")?,
+            )?;
+            if shared == bytes_field(test, "shared")? {
+                Ok(())
+            } else {
+                Err(String::from("shared secret does not match"))
+            }
+        },
+    )
+}
+
+// Runs every *.json file in the directory whose schema is supported
+pub fn run_dir(dir: &Path) -> Result<Report, String> {
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut results = Vec::new();
+    for path in paths {
+        let file = path.file_name().unwrap().to_string_lossy().into_owned();
+        let text = fs::read_to_string(&path).map_err(|e| format!("{file}: {e}"))?;
+        let json: Value = serde_json::from_str(&text).map_err(|e| format!("{file}: {e}"))?;
+
+        let report = match str_field(&json, "schema")? {
+            "ecdsa_verify_schema_v1.json" => run_ecdsa(&file, &json)?,
+            "ecdh_test_schema_v1.json" => run_ecdh(&file, &json)?,
+            "eddsa_verify_schema_v1.json" => run_eddsa(&file, &json)?,
+            "xdh_comp_schema_v1.json" => run_xdh(&file, &json)?,
+            schema => Report {
+                results: vec![(file, Outcome::Skip(format!("unsupported schema {schema}")))],
+            },
+        };
+        results.extend(report.results);
+    }
+    Ok(Report { results })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir() -> String {
+        format!("{}/test_vectors/wycheproof", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn load(name: &str) -> Value {
+        let path = format!("{}/{name}", dir());
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+        serde_json::from_str(&text).unwrap()
+    }
+
+    fn check(report: Report) {
+        let failures = report.failures();
+        assert!(failures.is_empty(), "{failures:?}");
+        assert!(report.count(true) > 0);
+    }
+
+    #[test]
+    fn test_judge() {
+        assert!(matches!(judge("valid", Ok(())), Outcome::Pass));
+        assert!(matches!(
+            judge("valid", Err(String::new())),
+            Outcome::Fail(_)
+        ));
+        assert!(matches!(judge("invalid", Ok(())), Outcome::Fail(_)));
+        assert!(matches!(
+            judge("invalid", Err(String::new())),
+            Outcome::Pass
+        ));
+        assert!(matches!(judge("acceptable", Ok(())), Outcome::Pass));
+        assert!(matches!(
+            judge("acceptable", Err(String::new())),
+            Outcome::Pass
+        ));
+    }
+
+    #[test]
+    fn test_ecdsa_p224() {
+        let file = "ecdsa_secp224r1_sha224_test.json";
+        check(run_ecdsa(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdsa_p256() {
+        let file = "ecdsa_secp256r1_sha256_test.json";
+        check(run_ecdsa(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdsa_secp256k1() {
+        let file = "ecdsa_secp256k1_sha256_test.json";
+        check(run_ecdsa(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdsa_p384() {
+        let file = "ecdsa_secp384r1_sha384_test.json";
+        check(run_ecdsa(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdsa_p521() {
+        let file = "ecdsa_secp521r1_sha512_test.json";
+        check(run_ecdsa(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdh_p224() {
+        let file = "ecdh_secp224r1_test.json";
+        check(run_ecdh(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdh_p256() {
+        let file = "ecdh_secp256r1_test.json";
+        check(run_ecdh(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdh_secp256k1() {
+        let file = "ecdh_secp256k1_test.json";
+        check(run_ecdh(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdh_p384() {
+        let file = "ecdh_secp384r1_test.json";
+        check(run_ecdh(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_ecdh_p521() {
+        let file = "ecdh_secp521r1_test.json";
+        check(run_ecdh(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_eddsa_ed25519() {
+        let file = "ed25519_test.json";
+        check(run_eddsa(file, &load(file)).unwrap());
+    }
+
+    #[test]
+    fn test_xdh() {
+        for file in ["x25519_test.json", "x448_test.json"] {
+            check(run_xdh(file, &load(file)).unwrap());
+        }
+    }
+
+    // the whole directory in one report, files without a test of their own
+    // included; about two minutes: cargo test -- --ignored
+    #[test]
+    #[ignore]
+    fn test_run_dir() {
+        let report = run_dir(Path::new(&dir())).unwrap();
+        println!(
+            "wycheproof: {} passed, {} failed, {} cases",
+            report.count(true),
+            report.count(false),
+            report.results.len()
+        );
+        check(report);
+    }
+}